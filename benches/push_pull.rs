@@ -0,0 +1,53 @@
+//! Times `push` and `pull` on arrays of 10,000 elements, against appending
+//! by reading the whole array and writing it back.
+//!
+//! Run with `cargo bench --bench push_pull`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+use serde_json::{json, Value};
+
+/// The number of elements of the arrays.
+const ELEMENTS: usize = 10_000;
+
+/// The number of times every operation is run.
+const ITERATIONS: u32 = 200;
+
+fn main() {
+    let driver = SQLiteDriver::temp().expect("cannot create the database");
+    let elements: Vec<Value> = (0..ELEMENTS)
+        .map(|i| json!({ "id": i, "name": format!("item-{}", i) }))
+        .collect();
+
+    driver.set("push", &elements).unwrap();
+    report("push", || driver.push("push", json!({ "id": -1 })).unwrap());
+
+    driver.set("rewrite", &elements).unwrap();
+    report("get + set", || {
+        let mut array: Vec<Value> = driver.get("rewrite").unwrap().unwrap_or_default();
+        array.push(json!({ "id": -1 }));
+        driver.set("rewrite", &array).unwrap();
+        array
+    });
+
+    driver.set("pull", &elements).unwrap();
+    report("pull", || driver.pull("pull", json!({ "id": -1 })).unwrap());
+}
+
+/// Runs an operation `ITERATIONS` times and prints its mean duration.
+fn report<T, F>(name: &str, mut run: F)
+where
+    F: FnMut() -> T,
+{
+    let started = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(run());
+    }
+    let mean: Duration = started.elapsed() / ITERATIONS;
+    println!(
+        "{:<10} {:>10.1?} per call on {} elements",
+        name, mean, ELEMENTS
+    );
+}
@@ -0,0 +1,220 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::error::{ChromoeError, Result};
+
+/// An operation queued in a [`Batch`].
+#[derive(Debug, Clone)]
+enum BatchOperation {
+    Set(String, Value),
+    Delete(String),
+    Add(String, f64),
+    Subtract(String, f64),
+    Push(String, Value),
+    Pull(String, Value),
+}
+
+/// The result of an operation of a committed [`Batch`].
+///
+/// # Variants
+///
+/// - `Set`: The value was stored.
+/// - `Deleted`: Whether there was a value to delete.
+/// - `Number`: The new value after adding or subtracting.
+/// - `Array`: The array after pushing or pulling.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchResult {
+    /// The result of `set`.
+    Set,
+    /// The result of `delete`.
+    Deleted(bool),
+    /// The result of `add` and `subtract`.
+    Number(f64),
+    /// The result of `push` and `pull`.
+    Array(Vec<Value>),
+}
+
+/// Operations on several keys applied atomically.
+///
+/// A `Batch` is obtained from `SQLiteDriver::batch`. Operations are queued
+/// and run by [`Batch::commit`] in a single immediate transaction, so either
+/// all of them are applied or, if one fails, none of them are, and other
+/// connections never see the keys half updated.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::batch::BatchResult;
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use serde_json::json;
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// driver.set("b", 2).unwrap();
+///
+/// let results = driver
+///     .batch()
+///     .set("a", 1)
+///     .delete("b")
+///     .push("c", "x")
+///     .commit()
+///     .unwrap();
+///
+/// assert_eq!(
+///     results,
+///     vec![BatchResult::Set, BatchResult::Deleted(true), BatchResult::Array(vec![json!("x")])]
+/// );
+/// assert_eq!(driver.get::<i64>("a").unwrap(), Some(1));
+/// ```
+#[derive(Debug)]
+pub struct Batch<'a> {
+    driver: &'a SQLiteDriver,
+    operations: Vec<BatchOperation>,
+    /// The first value that could not be serialised, returned by `commit`.
+    error: Option<ChromoeError>,
+}
+
+impl<'a> Batch<'a> {
+    /// Creates an empty batch of operations on `driver`.
+    pub(crate) fn new(driver: &'a SQLiteDriver) -> Self {
+        Batch {
+            driver,
+            operations: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Returns the number of queued operations.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Returns whether no operation is queued.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Queues storing a value at a key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the value, which may be nested.
+    /// - `value`: The value to store.
+    pub fn set<T>(self, key: &str, value: T) -> Self
+    where
+        T: Serialize,
+    {
+        self.queue_value(value, |value| BatchOperation::Set(key.to_string(), value))
+    }
+
+    /// Queues deleting the value at a key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the value, which may be nested.
+    pub fn delete(mut self, key: &str) -> Self {
+        self.operations
+            .push(BatchOperation::Delete(key.to_string()));
+        self
+    }
+
+    /// Queues adding to the number at a key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the number, which may be nested.
+    /// - `value`: The amount to add.
+    pub fn add(mut self, key: &str, value: f64) -> Self {
+        self.operations
+            .push(BatchOperation::Add(key.to_string(), value));
+        self
+    }
+
+    /// Queues subtracting from the number at a key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the number, which may be nested.
+    /// - `value`: The amount to subtract.
+    pub fn subtract(mut self, key: &str, value: f64) -> Self {
+        self.operations
+            .push(BatchOperation::Subtract(key.to_string(), value));
+        self
+    }
+
+    /// Queues appending a value to the array at a key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the array, which may be nested.
+    /// - `value`: The value to append.
+    pub fn push<T>(self, key: &str, value: T) -> Self
+    where
+        T: Serialize,
+    {
+        self.queue_value(value, |value| BatchOperation::Push(key.to_string(), value))
+    }
+
+    /// Queues removing every occurrence of a value from the array at a key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the array, which may be nested.
+    /// - `value`: The value to remove.
+    pub fn pull<T>(self, key: &str, value: T) -> Self
+    where
+        T: Serialize,
+    {
+        self.queue_value(value, |value| BatchOperation::Pull(key.to_string(), value))
+    }
+
+    /// Runs the queued operations in a single transaction, in the order they
+    /// were queued.
+    ///
+    /// # Returns
+    /// A `Result` containing the result of every operation, in order, or the
+    /// first error, in which case no operation is applied.
+    pub fn commit(self) -> Result<Vec<BatchResult>> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+
+        let driver = self.driver;
+        driver.atomic(|| {
+            self.operations
+                .into_iter()
+                .map(|operation| {
+                    Ok(match operation {
+                        BatchOperation::Set(key, value) => {
+                            driver.set(&key, value)?;
+                            BatchResult::Set
+                        }
+                        BatchOperation::Delete(key) => BatchResult::Deleted(driver.delete(&key)?),
+                        BatchOperation::Add(key, value) => {
+                            BatchResult::Number(driver.add(&key, value)?)
+                        }
+                        BatchOperation::Subtract(key, value) => {
+                            BatchResult::Number(driver.subtract(&key, value)?)
+                        }
+                        BatchOperation::Push(key, value) => {
+                            BatchResult::Array(driver.push(&key, value)?)
+                        }
+                        BatchOperation::Pull(key, value) => {
+                            BatchResult::Array(driver.pull(&key, value)?)
+                        }
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Serialises a value and queues the operation built from it, or keeps
+    /// the error for `commit`.
+    fn queue_value<T, F>(mut self, value: T, operation: F) -> Self
+    where
+        T: Serialize,
+        F: FnOnce(Value) -> BatchOperation,
+    {
+        match serde_json::to_value(value) {
+            Ok(value) => self.operations.push(operation(value)),
+            Err(err) => {
+                self.error.get_or_insert(err.into());
+            }
+        }
+        self
+    }
+}
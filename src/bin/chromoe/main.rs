@@ -0,0 +1,194 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+mod shell;
+
+use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+use chromoe_db::error::Result;
+use chromoe_db::structure::{ConflictStrategy, SQLiteDriverOptions, SQLiteOpenMode};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Inspect and edit chromoe-db stores from the command line.
+#[derive(Debug, Parser)]
+#[command(name = "chromoe", version)]
+struct Cli {
+    /// The SQLite database file to open.
+    #[arg(long, global = true, default_value = "json.sqlite")]
+    db: String,
+
+    /// The table to operate on.
+    #[arg(long, global = true, default_value = "json")]
+    table: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Print the value stored at a key as JSON.
+    Get {
+        /// The key to read, which may be nested using dot notation.
+        key: String,
+    },
+    /// Store a value at a key. Values that are not valid JSON are stored as strings.
+    Set {
+        /// The key to write, which may be nested using dot notation.
+        key: String,
+        /// The value to store.
+        value: String,
+    },
+    /// Delete the value stored at a key.
+    Del {
+        /// The key to delete, which may be nested using dot notation.
+        key: String,
+    },
+    /// List the keys of the table.
+    Keys {
+        /// Only list keys starting with this prefix.
+        #[arg(long)]
+        prefix: Option<String>,
+    },
+    /// Write every entry of the table as a JSON object.
+    Export {
+        /// The file to write to instead of standard output.
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+    /// Store every entry of a JSON object.
+    Import {
+        /// The file to read, or `-` for standard input.
+        input: String,
+        /// How entries are combined with existing keys: `skip`, `overwrite`,
+        /// `merge`, or `lww:<path>` to keep the value with the newer timestamp at `path`.
+        #[arg(long, default_value = "overwrite", value_parser = parse_conflict_strategy)]
+        on_conflict: ConflictStrategy,
+    },
+    /// Print storage statistics for the database.
+    Stats,
+    /// Print the entries added, removed and changed in another database as JSON.
+    Diff {
+        /// The SQLite database file to compare with.
+        other: String,
+        /// The table of the other database, if it differs from `--table`.
+        #[arg(long)]
+        other_table: Option<String>,
+    },
+    /// Start an interactive shell with key completion and dot-path navigation.
+    Shell,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    match run(cli) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    let driver = SQLiteDriver::new(Some(SQLiteDriverOptions {
+        file_name: cli.db,
+        table_name: cli.table.clone(),
+        ..Default::default()
+    }))?;
+
+    match cli.command {
+        Command::Get { key } => match driver.get::<Value>(&key)? {
+            Some(value) => print_json(&value)?,
+            None => {
+                eprintln!("no value at `{}`", key);
+                return Ok(ExitCode::FAILURE);
+            }
+        },
+        Command::Set { key, value } => {
+            let value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+            driver.set(&key, value)?;
+        }
+        Command::Del { key } => {
+            if !driver.delete(&key)? {
+                eprintln!("no value at `{}`", key);
+                return Ok(ExitCode::FAILURE);
+            }
+        }
+        Command::Keys { prefix } => {
+            let mut keys: Vec<String> = driver
+                .all()?
+                .into_iter()
+                .map(|(key, _)| key)
+                .filter(|key| {
+                    prefix
+                        .as_deref()
+                        .is_none_or(|prefix| key.starts_with(prefix))
+                })
+                .collect();
+            keys.sort();
+
+            let mut stdout = io::stdout().lock();
+            for key in keys {
+                writeln!(stdout, "{}", key)?;
+            }
+        }
+        Command::Export { output } => {
+            let entries: Map<String, Value> = driver.all()?.into_iter().collect();
+            let json = serde_json::to_string_pretty(&entries)?;
+            match output {
+                Some(path) => fs::write(path, json + "\n")?,
+                None => writeln!(io::stdout(), "{}", json)?,
+            }
+        }
+        Command::Import { input, on_conflict } => {
+            let contents = if input == "-" {
+                let mut contents = String::new();
+                io::stdin().read_to_string(&mut contents)?;
+                contents
+            } else {
+                fs::read_to_string(&input)?
+            };
+
+            let entries: Map<String, Value> = serde_json::from_str(&contents)?;
+            let count = entries.len();
+            let written =
+                driver.with_transaction(|tx| chromoe_db::import(&**tx, entries, &on_conflict))?;
+            eprintln!("imported {} of {} entries", written, count);
+        }
+        Command::Stats => {
+            print_json(&driver.stats()?)?;
+        }
+        Command::Diff { other, other_table } => {
+            let other = SQLiteDriver::new(Some(SQLiteDriverOptions {
+                file_name: other,
+                table_name: other_table.unwrap_or(cli.table),
+                open_mode: SQLiteOpenMode::ReadOnly,
+                ..Default::default()
+            }))?;
+            print_json(&chromoe_db::diff(&driver, &other)?)?;
+        }
+        Command::Shell => shell::run(&driver)?,
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Parses the `--on-conflict` option of `import`.
+fn parse_conflict_strategy(name: &str) -> std::result::Result<ConflictStrategy, String> {
+    ConflictStrategy::parse(name).ok_or_else(|| {
+        format!(
+            "unknown strategy `{}`, expected skip, overwrite, merge or lww:<path>",
+            name
+        )
+    })
+}
+
+/// Writes a value to standard output as pretty-printed JSON.
+fn print_json<T: Serialize>(value: &T) -> Result<()> {
+    writeln!(io::stdout(), "{}", serde_json::to_string_pretty(value)?)?;
+    Ok(())
+}
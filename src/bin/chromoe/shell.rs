@@ -0,0 +1,349 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+use chromoe_db::error::{ChromoeError, Result};
+use chromoe_db::structure::ListOptions;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use rustyline::{Context, Editor, Helper, Highlighter, Hinter, Validator};
+use serde_json::Value;
+
+/// The result of a shell command, whose error is printed to the user.
+type CommandResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+const COMMANDS: &[&str] = &[
+    "cd", "del", "exit", "get", "help", "ls", "pwd", "quit", "set",
+];
+
+const HELP: &str = "\
+Paths are separated by the path separator of the driver, `.` by default, and
+relative to the current path; start a path with `/` to make it absolute, and
+use `..` to refer to the parent.
+
+  ls [path]           list the keys or fields at a path
+  cd [path]           change the current path, or return to the root
+  pwd                 print the current path
+  get [path]          print the value at a path as JSON
+  set <path> <value>  store a value, parsed as JSON or stored as a string
+  del <path>          delete the value at a path
+  help                show this message
+  exit, quit          leave the shell";
+
+/// Runs the interactive shell until the user exits or closes standard input.
+pub fn run(driver: &SQLiteDriver) -> Result<()> {
+    let cwd = Rc::new(RefCell::new(Vec::new()));
+    let mut editor: Editor<ShellHelper<'_>, DefaultHistory> =
+        Editor::new().map_err(readline_error)?;
+    editor.set_helper(Some(ShellHelper {
+        driver,
+        cwd: cwd.clone(),
+    }));
+
+    println!(
+        "chromoe shell on table `{}`, type `help` for commands",
+        driver.table
+    );
+
+    let separator = driver.options.path_separator;
+    loop {
+        let prompt = format!("{}> ", display_path(&cwd.borrow(), separator));
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => return Ok(()),
+            Err(err) => return Err(readline_error(err)),
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+
+        let (command, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let rest = rest.trim();
+        let path = resolve(&cwd.borrow(), rest, separator);
+
+        let outcome: CommandResult<()> = match command {
+            "exit" | "quit" => return Ok(()),
+            "help" => {
+                println!("{}", HELP);
+                Ok(())
+            }
+            "pwd" => {
+                println!("{}", display_path(&cwd.borrow(), separator));
+                Ok(())
+            }
+            "ls" => list(driver, &path),
+            "get" => get(driver, &path),
+            "cd" => change_directory(driver, &cwd, path),
+            "set" => set(driver, &cwd.borrow(), rest),
+            "del" => delete(driver, &path),
+            _ => Err(format!("unknown command `{}`, type `help` for commands", command).into()),
+        };
+
+        if let Err(err) = outcome {
+            eprintln!("error: {}", err);
+        }
+    }
+}
+
+/// Completes command names and key paths.
+#[derive(Helper, Hinter, Highlighter, Validator)]
+struct ShellHelper<'a> {
+    driver: &'a SQLiteDriver,
+    cwd: Rc<RefCell<Vec<String>>>,
+}
+
+impl Completer for ShellHelper<'_> {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let start = line.rfind(' ').map_or(0, |space| space + 1);
+        let word = &line[start..];
+
+        if start == 0 {
+            let commands = COMMANDS
+                .iter()
+                .filter(|command| command.starts_with(word))
+                .map(|command| command.to_string())
+                .collect();
+            return Ok((0, commands));
+        }
+
+        // Complete the last segment of the path, keeping the segments before it.
+        let separator = self.driver.options.path_separator;
+        let (parent, partial) = match word.rfind(separator) {
+            Some(end) => (&word[..end], &word[end + separator.len_utf8()..]),
+            None if word.starts_with('/') => ("/", &word[1..]),
+            None => ("", word),
+        };
+        let candidates = resolve(&self.cwd.borrow(), parent, separator);
+        let offset = start + word.len() - partial.len();
+
+        let children = children(self.driver, &candidates, partial).unwrap_or_default();
+        Ok((offset, children))
+    }
+}
+
+/// Resolves a path typed by the user against the current path.
+fn resolve(cwd: &[String], input: &str, separator: char) -> Vec<String> {
+    let (mut path, input) = match input.strip_prefix('/') {
+        Some(absolute) => (Vec::new(), absolute),
+        None => (cwd.to_vec(), input),
+    };
+
+    for segment in input.split(separator).filter(|segment| !segment.is_empty()) {
+        if segment == ".." {
+            path.pop();
+        } else {
+            path.push(segment.to_string());
+        }
+    }
+    path
+}
+
+/// Formats a path for display, with the root shown as `/`.
+fn display_path(path: &[String], separator: char) -> String {
+    format!("/{}", join(path, separator))
+}
+
+/// Joins a path into a key of the driver.
+fn join(path: &[String], separator: char) -> String {
+    path.join(separator.encode_utf8(&mut [0; 4]))
+}
+
+/// Reads the value at a path, descending into objects and arrays.
+fn lookup(driver: &SQLiteDriver, path: &[String]) -> Result<Option<Value>> {
+    let Some((root, rest)) = path.split_first() else {
+        return Ok(Some(Value::Object(driver.all()?.into_iter().collect())));
+    };
+
+    let mut value = match driver.get::<Value>(root)? {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    for segment in rest {
+        let child = match &mut value {
+            Value::Object(object) => object.remove(segment),
+            Value::Array(array) => segment
+                .parse::<usize>()
+                .ok()
+                .filter(|index| *index < array.len())
+                .map(|index| array.swap_remove(index)),
+            _ => None,
+        };
+        match child {
+            Some(child) => value = child,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(value))
+}
+
+/// Returns the names of the keys, fields or indices directly below a path
+/// that start with `partial`.
+fn children(driver: &SQLiteDriver, path: &[String], partial: &str) -> Result<Vec<String>> {
+    // At the root only the rows matching the typed prefix are read.
+    if path.is_empty() {
+        let options = ListOptions {
+            prefix: Some(partial.to_string()),
+            ..Default::default()
+        };
+        return Ok(driver
+            .all_with(options)?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect());
+    }
+
+    let names: Vec<String> = match lookup(driver, path)? {
+        Some(Value::Object(object)) => object.keys().cloned().collect(),
+        Some(Value::Array(array)) => (0..array.len()).map(|index| index.to_string()).collect(),
+        _ => Vec::new(),
+    };
+    Ok(names
+        .into_iter()
+        .filter(|name| name.starts_with(partial))
+        .collect())
+}
+
+fn list(driver: &SQLiteDriver, path: &[String]) -> CommandResult<()> {
+    let Some(value) = lookup(driver, path)? else {
+        return Err(not_found(driver, path));
+    };
+
+    let entries: Vec<(String, Value)> = match value {
+        Value::Object(object) => {
+            let mut entries: Vec<(String, Value)> = object.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            entries
+        }
+        Value::Array(array) => array
+            .into_iter()
+            .enumerate()
+            .map(|(index, value)| (index.to_string(), value))
+            .collect(),
+        value => {
+            println!("{}", value);
+            return Ok(());
+        }
+    };
+
+    for (name, value) in entries {
+        let summary = match &value {
+            Value::Object(object) => format!("{{{} fields}}", object.len()),
+            Value::Array(array) => format!("[{} items]", array.len()),
+            value => value.to_string(),
+        };
+        println!("{:<24} {}", name, summary);
+    }
+    Ok(())
+}
+
+fn get(driver: &SQLiteDriver, path: &[String]) -> CommandResult<()> {
+    match lookup(driver, path)? {
+        Some(value) => {
+            println!("{}", serde_json::to_string_pretty(&value)?);
+            Ok(())
+        }
+        None => Err(not_found(driver, path)),
+    }
+}
+
+fn change_directory(
+    driver: &SQLiteDriver,
+    cwd: &Rc<RefCell<Vec<String>>>,
+    path: Vec<String>,
+) -> CommandResult<()> {
+    match lookup(driver, &path)? {
+        Some(Value::Object(_)) | Some(Value::Array(_)) => {
+            *cwd.borrow_mut() = path;
+            Ok(())
+        }
+        Some(_) => Err(format!(
+            "`{}` is not an object or array",
+            display_path(&path, driver.options.path_separator)
+        )
+        .into()),
+        None => Err(not_found(driver, &path)),
+    }
+}
+
+fn set(driver: &SQLiteDriver, cwd: &[String], input: &str) -> CommandResult<()> {
+    let (path, value) = input.split_once(' ').ok_or("usage: set <path> <value>")?;
+    let separator = driver.options.path_separator;
+    let path = resolve(cwd, path, separator);
+    if path.is_empty() {
+        return Err("cannot set the root".into());
+    }
+
+    let value = value.trim();
+    let value = serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()));
+    // The driver only creates missing parents, so every existing parent must be an object.
+    for depth in 1..path.len() {
+        match lookup(driver, &path[..depth])? {
+            Some(Value::Object(_)) => {}
+            Some(_) => {
+                return Err(format!(
+                    "`{}` is not an object",
+                    display_path(&path[..depth], separator)
+                )
+                .into())
+            }
+            None => break,
+        }
+    }
+
+    Ok(driver.set(&join(&path, separator), value)?)
+}
+
+fn delete(driver: &SQLiteDriver, path: &[String]) -> CommandResult<()> {
+    let Some((last, parent)) = path.split_last() else {
+        return Err("cannot delete the root".into());
+    };
+    if parent.is_empty() {
+        driver.delete(last)?;
+        return Ok(());
+    }
+
+    // Rewrite the parent, so fields of any depth and array items can be removed.
+    let removed = match lookup(driver, parent)? {
+        Some(Value::Object(mut object)) => object.remove(last).map(|_| Value::Object(object)),
+        Some(Value::Array(mut array)) => match last.parse::<usize>() {
+            Ok(index) if index < array.len() => {
+                array.remove(index);
+                Some(Value::Array(array))
+            }
+            _ => None,
+        },
+        _ => None,
+    };
+
+    match removed {
+        Some(parent_value) => {
+            Ok(driver.set(&join(parent, driver.options.path_separator), parent_value)?)
+        }
+        None => Err(not_found(driver, path)),
+    }
+}
+
+fn not_found(driver: &SQLiteDriver, path: &[String]) -> Box<dyn std::error::Error> {
+    format!(
+        "nothing at `{}`",
+        display_path(path, driver.options.path_separator)
+    )
+    .into()
+}
+
+fn readline_error(err: ReadlineError) -> ChromoeError {
+    ChromoeError::Io(std::io::Error::other(err))
+}
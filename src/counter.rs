@@ -0,0 +1,176 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::error::Result;
+
+/// The time window after which a windowed [`Counter`] starts again from zero.
+///
+/// Windows are aligned to the Unix epoch, so daily counters roll over at
+/// midnight UTC and hourly counters at the start of every hour.
+///
+/// # Variants
+///
+/// - `Minute`: The counter resets every minute.
+/// - `Hour`: The counter resets every hour.
+/// - `Day`: The counter resets every day.
+/// - `Custom`: The counter resets after the given duration, rounded down to whole seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    /// A window of one minute.
+    Minute,
+    /// A window of one hour.
+    Hour,
+    /// A window of one day.
+    Day,
+    /// A window of the given duration.
+    Custom(Duration),
+}
+
+impl Window {
+    /// Returns the length of the window in seconds, never less than one.
+    pub fn as_secs(&self) -> u64 {
+        match self {
+            Window::Minute => 60,
+            Window::Hour => 60 * 60,
+            Window::Day => 24 * 60 * 60,
+            Window::Custom(duration) => duration.as_secs().max(1),
+        }
+    }
+
+    /// Returns the index of the window containing the current time.
+    fn current(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        now / self.as_secs()
+    }
+}
+
+/// The stored representation of a windowed counter.
+#[derive(Debug, Serialize, Deserialize)]
+struct WindowedCount {
+    window: u64,
+    count: i64,
+}
+
+/// An integer counter stored at a key, such as a command usage statistic.
+///
+/// A `Counter` is obtained from `SQLiteDriver::counter`. Increments run in an
+/// immediate transaction, so concurrent increments from several processes are
+/// never lost. A windowed counter, created with [`Counter::windowed`], starts
+/// again from zero whenever a new window begins, which makes it suitable for
+/// rate limits and per-day usage figures.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::counter::Window;
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+///
+/// let uses = driver.counter("cmd_uses:ping");
+/// uses.incr().unwrap();
+/// assert_eq!(uses.incr_by(2).unwrap(), 3);
+///
+/// let today = driver.counter("cmd_uses_today:ping").windowed(Window::Day);
+/// assert_eq!(today.incr().unwrap(), 1);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Counter<'a> {
+    driver: &'a SQLiteDriver,
+    key: &'a str,
+    window: Option<Window>,
+}
+
+impl<'a> Counter<'a> {
+    /// Creates a handle to the counter stored at `key`.
+    pub(crate) fn new(driver: &'a SQLiteDriver, key: &'a str) -> Self {
+        Counter {
+            driver,
+            key,
+            window: None,
+        }
+    }
+
+    /// Turns this counter into one that resets at the start of every window.
+    ///
+    /// # Parameters
+    /// - `window`: The window after which the counter starts again from zero.
+    ///
+    /// # Returns
+    /// The windowed counter.
+    pub fn windowed(self, window: Window) -> Self {
+        Counter {
+            window: Some(window),
+            ..self
+        }
+    }
+
+    /// Returns the key where the counter is stored.
+    pub fn key(&self) -> &str {
+        self.key
+    }
+
+    /// Increments the counter by one.
+    ///
+    /// # Returns
+    /// A `Result` containing the counter value after the increment.
+    pub fn incr(&self) -> Result<i64> {
+        self.incr_by(1)
+    }
+
+    /// Increments the counter by `amount`, which may be negative.
+    ///
+    /// # Parameters
+    /// - `amount`: The amount to add to the counter.
+    ///
+    /// # Returns
+    /// A `Result` containing the counter value after the increment.
+    pub fn incr_by(&self, amount: i64) -> Result<i64> {
+        self.driver.atomic(|| {
+            let count = self.get()?.saturating_add(amount);
+            self.store(count)?;
+            Ok(count)
+        })
+    }
+
+    /// Returns the current value of the counter, which is `0` if it was never
+    /// incremented or a new window has begun.
+    pub fn get(&self) -> Result<i64> {
+        match self.window {
+            None => Ok(self.driver.get::<i64>(self.key)?.unwrap_or(0)),
+            Some(window) => {
+                let stored: Option<Value> = self.driver.get(self.key)?;
+                let count = stored
+                    .and_then(|value| serde_json::from_value::<WindowedCount>(value).ok())
+                    .filter(|stored| stored.window == window.current())
+                    .map_or(0, |stored| stored.count);
+                Ok(count)
+            }
+        }
+    }
+
+    /// Resets the counter to zero.
+    pub fn reset(&self) -> Result<()> {
+        self.store(0)
+    }
+
+    /// Writes `count` as the value of the counter in the current window.
+    fn store(&self, count: i64) -> Result<()> {
+        match self.window {
+            None => self.driver.set(self.key, count),
+            Some(window) => self.driver.set(
+                self.key,
+                WindowedCount {
+                    window: window.current(),
+                    count,
+                },
+            ),
+        }
+    }
+}
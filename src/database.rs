@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, OnceLock, RwLock};
+
+use serde_json::Value;
+
+use crate::driver::Driver;
+use crate::error::{ChromoeError, Result};
+use crate::structure::{DataSet, DriverOptions};
+use crate::uri::ConnectionUri;
+
+/// A function constructing a driver from a parsed connection string, as
+/// registered with [`Database::register`].
+pub type DriverFactory = dyn Fn(&ConnectionUri) -> Result<Box<dyn Driver>> + Send + Sync;
+
+/// The drivers registered by applications and third-party crates, keyed by scheme.
+fn registry() -> &'static RwLock<HashMap<String, Arc<DriverFactory>>> {
+    static REGISTRY: OnceLock<RwLock<HashMap<String, Arc<DriverFactory>>>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// A database opened from a connection string, and the recommended entry point
+/// to chromoe-db.
+///
+/// [`Database::open`] selects the driver from the scheme of the connection
+/// string, so a deployment can switch backends without code changes. Schemes
+/// registered with [`Database::register`] are looked up first, which lets
+/// third-party crates plug in their own drivers, followed by the drivers built
+/// into chromoe-db, as accepted by [`DriverOptions::from_uri`].
+///
+/// A `Database` dereferences to the underlying [`Driver`], and implements the
+/// trait itself so it can be passed wherever a driver is expected.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::Database;
+/// use serde_json::json;
+///
+/// let db = Database::open("sqlite://database_example.sqlite?mode=memory").unwrap();
+///
+/// db.set("user.coins", json!(100)).unwrap();
+/// assert_eq!(db.get("user.coins").unwrap(), Some(json!(100)));
+/// ```
+pub struct Database {
+    scheme: String,
+    driver: Box<dyn Driver>,
+}
+
+impl Database {
+    /// Opens the database described by a connection string.
+    ///
+    /// # Parameters
+    /// - `uri`: The connection string, such as `sqlite://json.sqlite?table=economy`.
+    ///
+    /// # Returns
+    /// A `Result` containing the opened `Database`, or an `InvalidUri` error if
+    /// no driver handles the scheme, or the error of the driver if it cannot be
+    /// opened.
+    pub fn open(uri: &str) -> Result<Self> {
+        Self::open_uri(&ConnectionUri::parse(uri)?)
+    }
+
+    /// Opens the database described by an already parsed connection string.
+    ///
+    /// # Parameters
+    /// - `uri`: The parsed connection string.
+    ///
+    /// # Returns
+    /// A `Result` containing the opened `Database`, or an error as described
+    /// for [`Database::open`].
+    pub fn open_uri(uri: &ConnectionUri) -> Result<Self> {
+        // The lock is released before calling the factory, which may register drivers itself.
+        let factory = registry()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&uri.scheme)
+            .cloned();
+
+        let driver = match factory {
+            Some(factory) => factory(uri)?,
+            None => DriverOptions::from_connection_uri(uri)?.build()?,
+        };
+
+        Ok(Database {
+            scheme: uri.scheme.clone(),
+            driver,
+        })
+    }
+
+    /// Registers a driver for a connection string scheme, such as `postgres`.
+    ///
+    /// Registered drivers take precedence over the built-in drivers, so a
+    /// built-in scheme can also be overridden. Registering a scheme again
+    /// replaces the previous driver.
+    ///
+    /// # Parameters
+    /// - `scheme`: The scheme handled by the driver, matched case-insensitively.
+    /// - `factory`: A function constructing the driver from the parsed connection string.
+    ///
+    /// # Returns
+    /// An `InvalidUri` error if the scheme is empty or contains characters that
+    /// cannot appear in a scheme.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::{SQLiteDriverOptions, SQLiteOpenMode};
+    /// use chromoe_db::Database;
+    ///
+    /// Database::register("scratch", |uri| {
+    ///     let options = SQLiteDriverOptions {
+    ///         table_name: uri.param("table").unwrap_or("json").to_string(),
+    ///         open_mode: SQLiteOpenMode::Memory,
+    ///         ..Default::default()
+    ///     };
+    ///     Ok(Box::new(SQLiteDriver::new(Some(options))?))
+    /// })
+    /// .unwrap();
+    ///
+    /// let db = Database::open("scratch://?table=economy").unwrap();
+    /// assert_eq!(db.scheme(), "scratch");
+    /// ```
+    pub fn register<F>(scheme: &str, factory: F) -> Result<()>
+    where
+        F: Fn(&ConnectionUri) -> Result<Box<dyn Driver>> + Send + Sync + 'static,
+    {
+        let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+            && scheme
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+        if !valid {
+            return Err(ChromoeError::InvalidUri(format!(
+                "`{}` is not a valid scheme",
+                scheme
+            )));
+        }
+
+        registry()
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(scheme.to_ascii_lowercase(), Arc::new(factory));
+        Ok(())
+    }
+
+    /// Removes the driver registered for a scheme.
+    ///
+    /// # Parameters
+    /// - `scheme`: The scheme to unregister.
+    ///
+    /// # Returns
+    /// `true` if a driver was registered for the scheme.
+    pub fn unregister(scheme: &str) -> bool {
+        registry()
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&scheme.to_ascii_lowercase())
+            .is_some()
+    }
+
+    /// Returns the scheme of the connection string the database was opened with.
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Returns the underlying driver.
+    pub fn driver(&self) -> &dyn Driver {
+        self.driver.as_ref()
+    }
+
+    /// Consumes the database, returning the underlying driver.
+    pub fn into_driver(self) -> Box<dyn Driver> {
+        self.driver
+    }
+}
+
+impl fmt::Debug for Database {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Database")
+            .field("scheme", &self.scheme)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Deref for Database {
+    type Target = dyn Driver;
+
+    fn deref(&self) -> &Self::Target {
+        self.driver.as_ref()
+    }
+}
+
+impl Driver for Database {
+    fn ping(&self) -> Result<()> {
+        self.driver.ping()
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        self.driver.all()
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        self.driver.get(key)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        self.driver.set(key, value)
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        self.driver.has(key)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        self.driver.delete(key)
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        self.driver.delete_all()
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.driver.add(key, value)
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.driver.subtract(key, value)
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        self.driver.push(key, value)
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        self.driver.pull(key, value)
+    }
+
+    fn insert(&self, value: Value) -> Result<String> {
+        self.driver.insert(value)
+    }
+
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        self.driver.replace(key, value)
+    }
+
+    fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        self.driver.delete_many_nested(key, paths)
+    }
+
+    fn entry(&self, key: &str) -> Result<Option<DataSet>> {
+        self.driver.entry(key)
+    }
+
+    fn entries(&self) -> Result<Vec<DataSet>> {
+        self.driver.entries()
+    }
+
+    fn split_key(&self, key: &str) -> Vec<String> {
+        self.driver.split_key(key)
+    }
+
+    fn join_key(&self, path: &[String]) -> Option<String> {
+        self.driver.join_key(path)
+    }
+}
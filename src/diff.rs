@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::driver::Driver;
+use crate::error::Result;
+use crate::structure::{Diff, FieldDiff, KeyDiff};
+
+/// Compares the entries of two stores, such as a staging and a production
+/// database.
+///
+/// Both stores are read with `all`, so they can use different backends. The
+/// values of entries in both stores are compared field by field, see
+/// [`KeyDiff`].
+///
+/// # Parameters
+/// - `a`: The first store.
+/// - `b`: The second store.
+///
+/// # Returns
+/// A `Result` containing the entries added in `b`, removed from `a` and
+/// changed between them.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::structure::FieldDiff;
+/// use serde_json::json;
+///
+/// let staging = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// staging.set("guild", json!({ "prefix": "!", "locale": "en" })).unwrap();
+/// staging.set("old", 1).unwrap();
+///
+/// let production = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// production.set("guild", json!({ "prefix": "?", "locale": "en" })).unwrap();
+/// production.set("new", 2).unwrap();
+///
+/// let diff = chromoe_db::diff(&staging, &production).unwrap();
+///
+/// assert_eq!(diff.added, vec![("new".to_string(), json!(2))]);
+/// assert_eq!(diff.removed, vec![("old".to_string(), json!(1))]);
+/// assert_eq!(diff.changed[0].key, "guild");
+/// assert_eq!(
+///     diff.changed[0].fields,
+///     vec![FieldDiff {
+///         path: "prefix".to_string(),
+///         before: Some(json!("!")),
+///         after: Some(json!("?")),
+///     }]
+/// );
+/// ```
+pub fn diff<A, B>(a: &A, b: &B) -> Result<Diff>
+where
+    A: Driver + ?Sized,
+    B: Driver + ?Sized,
+{
+    let before: BTreeMap<String, Value> = a.all()?.into_iter().collect();
+    let mut after: BTreeMap<String, Value> = b.all()?.into_iter().collect();
+
+    let mut diff = Diff::default();
+    for (key, value) in before {
+        match after.remove(&key) {
+            Some(other) if other == value => {}
+            Some(other) => {
+                let mut fields = Vec::new();
+                diff_values(String::new(), Some(value), Some(other), &mut fields);
+                fields.sort_by(|a, b| a.path.cmp(&b.path));
+                diff.changed.push(KeyDiff { key, fields });
+            }
+            None => diff.removed.push((key, value)),
+        }
+    }
+    diff.added = after.into_iter().collect();
+    Ok(diff)
+}
+
+/// Collects the fields that differ between two values at a path, recursing
+/// into objects present on both sides.
+fn diff_values(
+    path: String,
+    before: Option<Value>,
+    after: Option<Value>,
+    fields: &mut Vec<FieldDiff>,
+) {
+    match (before, after) {
+        (Some(Value::Object(mut before)), Some(Value::Object(after))) => {
+            for (field, value) in after {
+                let previous = before.remove(&field);
+                if previous.as_ref() != Some(&value) {
+                    diff_values(join(&path, &field), previous, Some(value), fields);
+                }
+            }
+            for (field, value) in before {
+                diff_values(join(&path, &field), Some(value), None, fields);
+            }
+        }
+        (before, after) => fields.push(FieldDiff {
+            path,
+            before,
+            after,
+        }),
+    }
+}
+
+/// Appends a field to a dot-separated path.
+fn join(path: &str, field: &str) -> String {
+    if path.is_empty() {
+        field.to_string()
+    } else {
+        format!("{}.{}", path, field)
+    }
+}
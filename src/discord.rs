@@ -0,0 +1,141 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use serde_json::Value;
+use serenity::model::id::GuildId;
+use typemap_rev::TypeMapKey;
+
+use crate::driver::blocking_driver::BlockingDriver;
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::driver::AsyncDriver;
+use crate::error::Result;
+
+/// The key of a shared driver in the data map of a serenity client.
+///
+/// Serenity handlers run concurrently on Tokio, so the driver is stored as an
+/// `Arc<D>` of a driver that can be used from several tasks. `D` is a
+/// [`BlockingDriver`] wrapping a `SQLiteDriver` unless another driver is
+/// named, such as `ChromoeKey<Box<dyn AsyncDriver + Send + Sync>>`. Poise
+/// bots keep the same `Arc` in their data struct instead, and
+/// [`GuildData::guild`] scopes keys to a guild.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use chromoe_db::discord::{ChromoeKey, GuildData};
+/// use chromoe_db::driver::blocking_driver::BlockingDriver;
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use serde_json::json;
+/// use serenity::model::id::GuildId;
+/// use typemap_rev::TypeMap;
+///
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// // With serenity, `client.data.write().await` gives the data map.
+/// let mut data = TypeMap::new();
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// data.insert::<ChromoeKey>(Arc::new(BlockingDriver::new(driver)));
+///
+/// // In a handler, `ctx.data.read().await` gives it back.
+/// let db = data.get::<ChromoeKey>().unwrap().clone();
+/// let guild = db.guild(GuildId::new(81384788765712384));
+/// guild.set("settings.prefix", json!("!")).await.unwrap();
+///
+/// assert_eq!(guild.get("settings.prefix").await.unwrap(), Some(json!("!")));
+/// assert_eq!(guild.key("settings.prefix"), "guild:81384788765712384:settings.prefix");
+/// # });
+/// ```
+pub struct ChromoeKey<D = BlockingDriver<SQLiteDriver>>(PhantomData<fn() -> D>);
+
+impl<D> TypeMapKey for ChromoeKey<D>
+where
+    D: Send + Sync + 'static,
+{
+    type Value = Arc<D>;
+}
+
+/// Scopes the keys of a driver to a guild.
+pub trait GuildData: AsyncDriver {
+    /// Returns the keys of a guild, stored in the driver with the prefix
+    /// `guild:<id>:`.
+    ///
+    /// # Parameters
+    /// - `guild_id`: The ID of the guild.
+    ///
+    /// # Returns
+    /// The `GuildNamespace` of the guild.
+    fn guild(&self, guild_id: GuildId) -> GuildNamespace<'_, Self> {
+        GuildNamespace {
+            driver: self,
+            prefix: format!("guild:{}:", guild_id),
+        }
+    }
+}
+
+impl<D> GuildData for D where D: AsyncDriver + ?Sized {}
+
+/// The keys of a guild, obtained from [`GuildData::guild`].
+///
+/// Every key is stored with the prefix of the guild, so `settings.prefix`
+/// refers to the `prefix` field of the `guild:<id>:settings` entry. Keys may
+/// refer to nested values as with the driver.
+pub struct GuildNamespace<'a, D: ?Sized> {
+    driver: &'a D,
+    prefix: String,
+}
+
+impl<D> GuildNamespace<'_, D>
+where
+    D: AsyncDriver + ?Sized,
+{
+    /// Returns the key of the driver a key of the guild is stored at.
+    pub fn key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+
+    /// Retrieves the value stored at a key of the guild. See
+    /// [`AsyncDriver::get`].
+    pub async fn get(&self, key: &str) -> Result<Option<Value>> {
+        self.driver.get(&self.key(key)).await
+    }
+
+    /// Sets the value stored at a key of the guild. See [`AsyncDriver::set`].
+    pub async fn set(&self, key: &str, value: Value) -> Result<()> {
+        self.driver.set(&self.key(key), value).await
+    }
+
+    /// Returns whether a key of the guild exists. See [`AsyncDriver::has`].
+    pub async fn has(&self, key: &str) -> Result<bool> {
+        self.driver.has(&self.key(key)).await
+    }
+
+    /// Deletes a key of the guild. See [`AsyncDriver::delete`].
+    pub async fn delete(&self, key: &str) -> Result<bool> {
+        self.driver.delete(&self.key(key)).await
+    }
+
+    /// Adds to the number stored at a key of the guild. See
+    /// [`AsyncDriver::add`].
+    pub async fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.driver.add(&self.key(key), value).await
+    }
+
+    /// Subtracts from the number stored at a key of the guild. See
+    /// [`AsyncDriver::subtract`].
+    pub async fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.driver.subtract(&self.key(key), value).await
+    }
+
+    /// Appends a value to the array stored at a key of the guild. See
+    /// [`AsyncDriver::push`].
+    pub async fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        self.driver.push(&self.key(key), value).await
+    }
+
+    /// Removes a value from the array stored at a key of the guild. See
+    /// [`AsyncDriver::pull`].
+    pub async fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        self.driver.pull(&self.key(key), value).await
+    }
+}
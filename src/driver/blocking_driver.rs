@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "sqlite")]
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::driver::{AsyncDriver, Driver};
+use crate::error::{ChromoeError, Result};
+#[cfg(feature = "sqlite")]
+use crate::structure::CheckpointMode;
+
+/// Adapter implementing [`AsyncDriver`] for any blocking [`Driver`].
+///
+/// Every operation runs on Tokio's blocking thread pool through
+/// `tokio::task::spawn_blocking`, so blocking drivers such as the
+/// `SQLiteDriver` can be awaited without stalling the runtime's worker threads.
+/// Operations are run one at a time, since most drivers, like SQLite
+/// connections, cannot be used from several threads at once. The adapter must
+/// be awaited from within a Tokio runtime.
+///
+/// Concurrent `get` calls for the same key share one query, so a hot key read
+/// by many simultaneous events, such as the configuration of a guild, reaches
+/// the driver once. A call that starts while the shared query is running
+/// receives its result; if the query fails, every waiting call reads the key
+/// again on its own, so errors are reported by the calls that hit them. A
+/// write ends the sharing of the queries running when it completes, so calls
+/// starting after it read the value it wrote.
+///
+/// # Example Usage
+///
+/// ```rust,no_run
+/// use chromoe_db::driver::blocking_driver::BlockingDriver;
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::driver::AsyncDriver;
+/// use serde_json::json;
+///
+/// # async fn run() -> chromoe_db::error::Result<()> {
+/// let driver: Box<dyn AsyncDriver> = Box::new(BlockingDriver::new(SQLiteDriver::new(None)?));
+///
+/// driver.set("user.coins", json!(100)).await?;
+/// assert_eq!(driver.add("user.coins", 50.0).await?, 150.0);
+/// # Ok(())
+/// # }
+/// ```
+pub struct BlockingDriver<D> {
+    driver: Arc<Mutex<D>>,
+    flights: Mutex<HashMap<String, broadcast::Sender<Option<Value>>>>,
+}
+
+/// A `get` in progress that concurrent reads of the same key wait for, removed
+/// from the in-progress reads when it completes or is cancelled.
+struct Flight<'a> {
+    flights: &'a Mutex<HashMap<String, broadcast::Sender<Option<Value>>>>,
+    key: &'a str,
+    sender: broadcast::Sender<Option<Value>>,
+}
+
+impl Flight<'_> {
+    /// Sends the value read to the waiting reads.
+    fn complete(self, value: &Option<Value>) {
+        self.remove();
+        let _ = self.sender.send(value.clone());
+    }
+
+    /// Removes the flight from the in-progress reads, unless a write already
+    /// removed it and a later read of the key took its place.
+    fn remove(&self) {
+        let mut flights = self.flights.lock().unwrap_or_else(|e| e.into_inner());
+        if flights
+            .get(self.key)
+            .is_some_and(|sender| sender.same_channel(&self.sender))
+        {
+            flights.remove(self.key);
+        }
+    }
+}
+
+impl Drop for Flight<'_> {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+impl<D> BlockingDriver<D>
+where
+    D: Driver + Send + 'static,
+{
+    /// Wraps a blocking driver so it can be used as an [`AsyncDriver`].
+    ///
+    /// # Parameters
+    /// - `driver`: The driver to run on the blocking thread pool.
+    ///
+    /// # Returns
+    /// The `BlockingDriver` wrapping `driver`.
+    pub fn new(driver: D) -> Self {
+        BlockingDriver {
+            driver: Arc::new(Mutex::new(driver)),
+            flights: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Runs an operation of the driver on the blocking thread pool.
+    async fn run<T, F>(&self, operation: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&D) -> Result<T> + Send + 'static,
+    {
+        let driver = Arc::clone(&self.driver);
+        join(tokio::task::spawn_blocking(move || {
+            let driver = driver.lock().unwrap_or_else(|e| e.into_inner());
+            operation(&driver)
+        }))
+        .await
+    }
+
+    /// Runs a write of the driver on the blocking thread pool, then stops
+    /// sharing the reads in progress, which may have read the value before the
+    /// write.
+    async fn write<T, F>(&self, operation: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&D) -> Result<T> + Send + 'static,
+    {
+        let result = self.run(operation).await;
+        self.flights
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        result
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl BlockingDriver<SQLiteDriver> {
+    /// Writes every buffered write of the driver to the database. See
+    /// `SQLiteDriver::flush`.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of rows written.
+    pub async fn flush(&self) -> Result<usize> {
+        self.run(|driver| driver.flush()).await
+    }
+
+    /// Opens a handle to another table of the same database. See
+    /// `SQLiteDriver::table`.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the table.
+    ///
+    /// # Returns
+    /// A `Result` containing a `BlockingDriver` for the table, or an error if
+    /// the name is invalid or the database is in memory.
+    pub async fn table(&self, name: &str) -> Result<BlockingDriver<SQLiteDriver>> {
+        let name = name.to_string();
+        let driver = self.run(move |driver| driver.table(&name)).await?;
+        Ok(BlockingDriver::new(driver))
+    }
+
+    /// Closes the driver, making sure everything written is in the database
+    /// file before returning. See `SQLiteDriver::close`.
+    ///
+    /// If an operation whose future was dropped is still running, the driver
+    /// is flushed and checkpointed instead, and closed once that operation
+    /// finishes.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the driver was closed, or the first error
+    /// encountered closing it.
+    pub async fn close(self) -> Result<()> {
+        let driver = self.driver;
+        join(tokio::task::spawn_blocking(move || {
+            match Arc::try_unwrap(driver) {
+                Ok(driver) => driver
+                    .into_inner()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .close(),
+                Err(driver) => {
+                    let driver = driver.lock().unwrap_or_else(|e| e.into_inner());
+                    driver.flush()?;
+                    driver.checkpoint(CheckpointMode::Truncate)?;
+                    Ok(())
+                }
+            }
+        }))
+        .await
+    }
+}
+
+/// Waits for an operation run on the blocking thread pool, resuming its panic
+/// if it panicked.
+async fn join<T>(task: JoinHandle<Result<T>>) -> Result<T> {
+    match task.await {
+        Ok(result) => result,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(err) => Err(ChromoeError::Io(io::Error::other(err))),
+    }
+}
+
+#[async_trait::async_trait]
+impl<D> AsyncDriver for BlockingDriver<D>
+where
+    D: Driver + Send + 'static,
+{
+    async fn ping(&self) -> Result<()> {
+        self.run(|driver| driver.ping()).await
+    }
+
+    async fn all(&self) -> Result<Vec<(String, Value)>> {
+        self.run(|driver| driver.all()).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Value>> {
+        let waiting = {
+            let mut flights = self.flights.lock().unwrap_or_else(|e| e.into_inner());
+            match flights.get(key) {
+                Some(sender) => Err(sender.subscribe()),
+                None => {
+                    let sender = broadcast::channel(1).0;
+                    flights.insert(key.to_string(), sender.clone());
+                    Ok(sender)
+                }
+            }
+        };
+
+        let owned = key.to_string();
+        let sender = match waiting {
+            Ok(sender) => sender,
+            Err(mut receiver) => {
+                // A failed or cancelled shared read closes the channel, and the
+                // key is read again.
+                if let Ok(value) = receiver.recv().await {
+                    return Ok(value);
+                }
+                return self.run(move |driver| driver.get(&owned)).await;
+            }
+        };
+
+        let flight = Flight {
+            flights: &self.flights,
+            key,
+            sender,
+        };
+        let value = self.run(move |driver| driver.get(&owned)).await?;
+        flight.complete(&value);
+        Ok(value)
+    }
+
+    async fn set(&self, key: &str, value: Value) -> Result<()> {
+        let key = key.to_string();
+        self.write(move |driver| driver.set(&key, value)).await
+    }
+
+    async fn has(&self, key: &str) -> Result<bool> {
+        let key = key.to_string();
+        self.run(move |driver| driver.has(&key)).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool> {
+        let key = key.to_string();
+        self.write(move |driver| driver.delete(&key)).await
+    }
+
+    async fn delete_all(&self) -> Result<bool> {
+        self.write(|driver| driver.delete_all()).await
+    }
+
+    async fn add(&self, key: &str, value: f64) -> Result<f64> {
+        let key = key.to_string();
+        self.write(move |driver| driver.add(&key, value)).await
+    }
+
+    async fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        let key = key.to_string();
+        self.write(move |driver| driver.subtract(&key, value)).await
+    }
+
+    async fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        let key = key.to_string();
+        self.write(move |driver| driver.push(&key, value)).await
+    }
+
+    async fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        let key = key.to_string();
+        self.write(move |driver| driver.pull(&key, value)).await
+    }
+}
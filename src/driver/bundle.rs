@@ -0,0 +1,95 @@
+use std::io::{Read, Write};
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::error::{ChromoeError, Result};
+
+/// The bytes starting every bundle, ending with the version of the format.
+const MAGIC: &[u8; 8] = b"CHROMOE\x01";
+
+/// The length of the random salt the key is derived with.
+const SALT_LEN: usize = 16;
+
+/// The length of the AES-GCM nonce.
+const NONCE_LEN: usize = 12;
+
+/// Compresses and encrypts data into a bundle.
+///
+/// A bundle is the magic bytes, a random salt and nonce, then the data
+/// compressed with DEFLATE and encrypted with AES-256-GCM under a key derived
+/// from the passphrase and the salt with Argon2id. The magic bytes and the
+/// salt are authenticated along with the data.
+pub(crate) fn seal(data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    let mut compressed = DeflateEncoder::new(Vec::new(), Compression::default());
+    compressed.write_all(data)?;
+    let compressed = compressed.finish()?;
+
+    let mut salt = [0; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let mut bundle = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + compressed.len());
+    bundle.extend_from_slice(MAGIC);
+    bundle.extend_from_slice(&salt);
+    let ciphertext = cipher(passphrase, &salt)?
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: &compressed,
+                aad: &bundle,
+            },
+        )
+        .map_err(|_| ChromoeError::Config("cannot encrypt the bundle".to_string()))?;
+    bundle.extend_from_slice(&nonce);
+    bundle.extend_from_slice(&ciphertext);
+    Ok(bundle)
+}
+
+/// Decrypts and decompresses the data of a bundle made by [`seal`].
+///
+/// # Returns
+/// A `Result` containing the data, or a `Config` error if the bundle is not
+/// one, or the passphrase is wrong, or the bundle was modified.
+pub(crate) fn open(bundle: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+    if bundle.len() < MAGIC.len() + SALT_LEN + NONCE_LEN || !bundle.starts_with(MAGIC) {
+        return Err(ChromoeError::Config(
+            "not an encrypted chromoe-db bundle".to_string(),
+        ));
+    }
+
+    let (header, rest) = bundle.split_at(MAGIC.len() + SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let compressed = cipher(passphrase, &header[MAGIC.len()..])?
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| {
+            ChromoeError::Config(
+                "cannot decrypt the bundle: the passphrase is wrong or the bundle is damaged"
+                    .to_string(),
+            )
+        })?;
+
+    let mut data = Vec::new();
+    DeflateDecoder::new(compressed.as_slice()).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+/// Builds the cipher keyed with the key derived from a passphrase and salt.
+fn cipher(passphrase: &str, salt: &[u8]) -> Result<Aes256Gcm> {
+    let mut key = Key::<Aes256Gcm>::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| ChromoeError::Config(format!("cannot derive the key: {}", e)))?;
+    Ok(Aes256Gcm::new(&key))
+}
@@ -0,0 +1,466 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{from_str, Value};
+use worker::wasm_bindgen::JsValue;
+use worker::{D1Database, D1PreparedStatement};
+
+use crate::driver::json_path::{lookup, remove_path, replace, split_key};
+use crate::driver::number;
+#[cfg(target_arch = "wasm32")]
+use crate::driver::AsyncDriver;
+use crate::error::{ChromoeError, Result};
+use crate::uri::is_valid_identifier;
+
+/// The number of times a conflicting write is retried before giving up.
+const MAX_ATTEMPTS: usize = 8;
+
+/// Driver storing JSON data in a Cloudflare D1 database, for use from Workers.
+///
+/// The `D1Driver` uses the same table layout as the `SQLiteDriver`, so data can
+/// be exported from a local database and imported into D1 as is. Workers are
+/// asynchronous, so the driver exposes the same operations as `async` methods
+/// and implements [`AsyncDriver`] rather than the blocking [`Driver`] trait.
+///
+/// D1 has no interactive transactions, so read-modify-write operations, such
+/// as `add` and writes to nested keys, only write back if the row is unchanged
+/// since it was read, and retry otherwise. This keeps them atomic when several
+/// Workers update the same key.
+///
+/// # Fields
+///
+/// - `table`: The name of the table the driver operates on.
+/// - `lenient_numbers`: Whether `add` and `subtract` treat a stored value that
+///   is not a number as `0` and replace it, instead of returning a
+///   `ChromoeError::TypeMismatch`. It is off for a new driver.
+///
+/// # Example Usage
+///
+/// ```rust,ignore
+/// use chromoe_db::driver::d1_driver::D1Driver;
+/// use worker::{event, Env, Request, Response, Result};
+///
+/// #[event(fetch)]
+/// async fn fetch(_req: Request, env: Env, _ctx: worker::Context) -> Result<Response> {
+///     let driver = D1Driver::new(env.d1("DB")?, "economy").await?;
+///     let coins = driver.add("coins", 1.0).await?;
+///     Response::ok(format!("coins: {}", coins))
+/// }
+/// ```
+///
+/// [`AsyncDriver`]: crate::driver::AsyncDriver
+/// [`Driver`]: crate::driver::Driver
+pub struct D1Driver {
+    /// The name of the table the driver operates on.
+    pub table: String,
+    /// Whether `add` and `subtract` treat a stored value that is not a number
+    /// as `0`.
+    pub lenient_numbers: bool,
+    database: D1Database,
+}
+
+/// A row of the driver's table.
+#[derive(Deserialize)]
+struct Row {
+    #[serde(rename = "ID")]
+    id: String,
+    #[serde(rename = "JSON")]
+    json: Option<String>,
+}
+
+impl D1Driver {
+    /// Creates a new instance of the `D1Driver`, creating the table if it
+    /// doesn't already exist.
+    ///
+    /// # Parameters
+    /// - `database`: The D1 binding, such as `env.d1("DB")?`.
+    /// - `table_name`: The name of the table to operate on.
+    ///
+    /// # Returns
+    /// A `Result` containing either the `D1Driver` instance or an error if the
+    /// table name is invalid or the table cannot be created.
+    pub async fn new(database: D1Database, table_name: &str) -> Result<Self> {
+        if !is_valid_identifier(table_name) {
+            return Err(ChromoeError::Config(format!(
+                "`{}` is not a valid table name",
+                table_name
+            )));
+        }
+
+        let driver = D1Driver {
+            table: table_name.to_string(),
+            lenient_numbers: false,
+            database,
+        };
+        driver
+            .statement(
+                &format!(
+                    "CREATE TABLE IF NOT EXISTS {} (ID TEXT PRIMARY KEY, JSON TEXT)",
+                    driver.table
+                ),
+                &[],
+            )?
+            .run()
+            .await?;
+        Ok(driver)
+    }
+
+    /// Checks that the database is reachable and the table can be read.
+    ///
+    /// # Returns
+    /// A `Result` which is an error if the table cannot be read.
+    pub async fn ping(&self) -> Result<()> {
+        self.statement(
+            &format!(
+                "SELECT COUNT(*) FROM (SELECT 1 FROM {} LIMIT 1)",
+                self.table
+            ),
+            &[],
+        )?
+        .run()
+        .await?;
+        Ok(())
+    }
+
+    /// Adds a value to an existing entry or creates a new entry if it doesn't exist.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry to update.
+    /// - `value`: The value to add to the current entry.
+    ///
+    /// # Returns
+    /// The new value after adding `value` to the existing entry.
+    pub async fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.modify(key, |current| {
+            let new_value = number(current, self.lenient_numbers)? + value;
+            Ok((Some(Value::from(new_value)), new_value))
+        })
+        .await
+    }
+
+    /// Retrieves all data entries from the table as a vector of tuples.
+    ///
+    /// # Returns
+    /// A `Result` containing a vector of key and value pairs.
+    pub async fn all(&self) -> Result<Vec<(String, Value)>> {
+        let rows: Vec<Row> = self
+            .statement(&format!("SELECT ID, JSON FROM {}", self.table), &[])?
+            .all()
+            .await?
+            .results()?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let json = row
+                    .json
+                    .and_then(|json| from_str(&json).ok())
+                    .unwrap_or(Value::Null);
+                (row.id, json)
+            })
+            .collect())
+    }
+
+    /// Deletes a specific entry by key, or a nested field within an entry.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to delete.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if a value was deleted, or `false` if there
+    /// was no value at `key`.
+    pub async fn delete(&self, key: &str) -> Result<bool> {
+        if !key.contains('.') {
+            let result = self
+                .statement(&format!("DELETE FROM {} WHERE ID = ?", self.table), &[key])?
+                .run()
+                .await?;
+            let changes = result.meta()?.and_then(|meta| meta.changes).unwrap_or(0);
+            return Ok(changes > 0);
+        }
+
+        self.modify(key, |current| Ok((None, current.is_some())))
+            .await
+    }
+
+    /// Deletes several nested fields of the value at a key with a single write.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the value holding the fields, which may itself be nested.
+    /// - `paths`: The paths of the fields to delete, relative to `key`.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of fields deleted, or a `TypeMismatch`
+    /// error if a path leads through a value that is not an object.
+    pub async fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        self.modify(key, |current| {
+            let mut value = current.cloned();
+            let mut deleted = 0;
+            if let Some(value) = value.as_mut() {
+                for path in paths {
+                    if remove_path(value, &path.split('.').collect::<Vec<_>>())? {
+                        deleted += 1;
+                    }
+                }
+            }
+            Ok((value, deleted))
+        })
+        .await
+    }
+
+    /// Deletes all entries in the table.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the deletion was successful.
+    pub async fn delete_all(&self) -> Result<bool> {
+        self.statement(&format!("DELETE FROM {}", self.table), &[])?
+            .run()
+            .await?;
+        Ok(true)
+    }
+
+    /// Retrieves the value for a given key, deserialising it into the specified type.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` of the deserialised value.
+    pub async fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned + Default,
+    {
+        let (root, path) = split_key(key);
+        let value = self
+            .read_row(root)
+            .await?
+            .and_then(|json| from_str::<Value>(&json).ok())
+            .and_then(|root| lookup(&root, &path).cloned());
+
+        Ok(value.map(|value| serde_json::from_value(value).unwrap_or_default()))
+    }
+
+    /// Checks if a given key exists.
+    ///
+    /// # Parameters
+    /// - `key`: The key to check.
+    ///
+    /// # Returns
+    /// A `Result` containing a boolean indicating whether the key exists.
+    pub async fn has(&self, key: &str) -> Result<bool> {
+        Ok(self.get::<Value>(key).await?.is_some())
+    }
+
+    /// Removes a specific value from an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to remove from the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after removal.
+    pub async fn pull<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + std::cmp::PartialEq + Clone + Serialize,
+    {
+        self.modify(key, |current| {
+            let mut arr: Vec<T> = current
+                .and_then(|current| serde_json::from_value(current.clone()).ok())
+                .unwrap_or_default();
+            arr.retain(|x| x != &value);
+            Ok((Some(serde_json::to_value(&arr)?), arr))
+        })
+        .await
+    }
+
+    /// Appends a value to an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to append to the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after the value is appended.
+    pub async fn push<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + Clone + Serialize,
+    {
+        self.modify(key, |current| {
+            let mut arr: Vec<T> = current
+                .and_then(|current| serde_json::from_value(current.clone()).ok())
+                .unwrap_or_default();
+            arr.push(value.clone());
+            Ok((Some(serde_json::to_value(&arr)?), arr))
+        })
+        .await
+    }
+
+    /// Sets or updates the value for a given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the value was stored.
+    pub async fn set<T>(&self, key: &str, value: T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        if !key.contains('.') {
+            self.statement(
+                &format!(
+                    "INSERT INTO {} (ID, JSON) VALUES (?1, ?2) ON CONFLICT(ID) DO UPDATE SET JSON = ?2",
+                    self.table
+                ),
+                &[key, &value.to_string()],
+            )?
+            .run()
+            .await?;
+            return Ok(());
+        }
+
+        self.modify(key, |_| Ok((Some(value.clone()), ()))).await
+    }
+
+    /// Subtracts a value from an existing entry. If the entry does not exist,
+    /// it initialises it with the result.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to subtract from.
+    /// - `value`: The value to subtract from the current value.
+    ///
+    /// # Returns
+    /// The new value after subtraction.
+    pub async fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.modify(key, |current| {
+            let new_value = number(current, self.lenient_numbers)? - value;
+            Ok((Some(Value::from(new_value)), new_value))
+        })
+        .await
+    }
+
+    /// Replaces the value at a key, retrying if the row changes between reading
+    /// and writing it.
+    ///
+    /// `update` receives the current value at the key and returns its new value,
+    /// `None` removing it, along with the result of the operation.
+    async fn modify<T, F>(&self, key: &str, mut update: F) -> Result<T>
+    where
+        F: FnMut(Option<&Value>) -> Result<(Option<Value>, T)>,
+    {
+        let (root, path) = split_key(key);
+
+        for _ in 0..MAX_ATTEMPTS {
+            let current = self.read_row(root).await?;
+            let mut root_value = current.as_deref().and_then(|json| from_str(json).ok());
+
+            let (value, result) = update(root_value.as_ref().and_then(|root| lookup(root, &path)))?;
+            replace(&mut root_value, &path, value)?;
+
+            let statement = match (&current, &root_value) {
+                (None, None) => return Ok(result),
+                (None, Some(new)) => self.statement(
+                    &format!(
+                        "INSERT INTO {} (ID, JSON) VALUES (?, ?) ON CONFLICT(ID) DO NOTHING",
+                        self.table
+                    ),
+                    &[root, &new.to_string()],
+                )?,
+                (Some(old), Some(new)) => self.statement(
+                    &format!(
+                        "UPDATE {} SET JSON = ? WHERE ID = ? AND JSON = ?",
+                        self.table
+                    ),
+                    &[&new.to_string(), root, old],
+                )?,
+                (Some(old), None) => self.statement(
+                    &format!("DELETE FROM {} WHERE ID = ? AND JSON = ?", self.table),
+                    &[root, old],
+                )?,
+            };
+
+            let changes = statement
+                .run()
+                .await?
+                .meta()?
+                .and_then(|meta| meta.changes)
+                .unwrap_or(0);
+            if changes > 0 {
+                return Ok(result);
+            }
+        }
+
+        Err(worker::Error::RustError(format!(
+            "`{}` kept changing while it was being updated",
+            root
+        ))
+        .into())
+    }
+
+    /// Reads the serialised value of a row.
+    async fn read_row(&self, key: &str) -> Result<Option<String>> {
+        Ok(self
+            .statement(
+                &format!("SELECT JSON FROM {} WHERE ID = ?", self.table),
+                &[key],
+            )?
+            .first::<String>(Some("JSON"))
+            .await?)
+    }
+
+    /// Prepares a statement, binding every argument as text.
+    fn statement(&self, sql: &str, args: &[&str]) -> Result<D1PreparedStatement> {
+        let args: Vec<JsValue> = args.iter().map(|arg| JsValue::from_str(arg)).collect();
+        Ok(self.database.prepare(sql).bind(&args)?)
+    }
+}
+
+// D1's futures hold JavaScript values, which can only be awaited on `wasm32`.
+#[cfg(target_arch = "wasm32")]
+#[async_trait::async_trait(?Send)]
+impl AsyncDriver for D1Driver {
+    async fn ping(&self) -> Result<()> {
+        D1Driver::ping(self).await
+    }
+
+    async fn all(&self) -> Result<Vec<(String, Value)>> {
+        D1Driver::all(self).await
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Value>> {
+        D1Driver::get(self, key).await
+    }
+
+    async fn set(&self, key: &str, value: Value) -> Result<()> {
+        D1Driver::set(self, key, value).await
+    }
+
+    async fn has(&self, key: &str) -> Result<bool> {
+        D1Driver::has(self, key).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<bool> {
+        D1Driver::delete(self, key).await
+    }
+
+    async fn delete_all(&self) -> Result<bool> {
+        D1Driver::delete_all(self).await
+    }
+
+    async fn add(&self, key: &str, value: f64) -> Result<f64> {
+        D1Driver::add(self, key, value).await
+    }
+
+    async fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        D1Driver::subtract(self, key, value).await
+    }
+
+    async fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        D1Driver::push(self, key, value).await
+    }
+
+    async fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        D1Driver::pull(self, key, value).await
+    }
+}
@@ -0,0 +1,216 @@
+use std::sync::Mutex;
+
+use serde_json::{json, Value};
+
+use crate::driver::{number, Driver};
+use crate::error::Result;
+use crate::structure::DataSet;
+
+/// A write skipped by a [`DryRunDriver`].
+///
+/// # Fields
+///
+/// - `operation`: The name of the driver method, such as `set` or `add`.
+/// - `key`: The key the write targets, or `None` for `delete_all`.
+/// - `value`: The value the key would have held after the write, or `None`
+///   if it would have been deleted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DryRunWrite {
+    /// The name of the driver method.
+    pub operation: &'static str,
+    /// The key the write targets.
+    pub key: Option<String>,
+    /// The value the key would have held after the write.
+    pub value: Option<Value>,
+}
+
+/// A driver wrapper turning every write into a logged no-op, for trying
+/// migration scripts and new features against production data safely.
+///
+/// Reads are passed to the wrapped driver, while writes are recorded and
+/// answered with the result they would have had, computed from the data as it
+/// is, so `add` still returns the new total and `delete` whether there was a
+/// value to delete. Since nothing is written, reads made after a skipped write
+/// don't see its effect.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::dry_run_driver::DryRunDriver;
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::driver::Driver;
+/// use serde_json::json;
+///
+/// let sqlite = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// sqlite.set("user.coins", json!(100)).unwrap();
+///
+/// let driver = DryRunDriver::new(sqlite);
+/// assert_eq!(Driver::add(&driver, "user.coins", 50.0).unwrap(), 150.0);
+/// assert!(Driver::delete(&driver, "user").unwrap());
+///
+/// assert_eq!(Driver::get(&driver, "user.coins").unwrap(), Some(json!(100)));
+/// assert_eq!(driver.writes().len(), 2);
+/// assert_eq!(driver.writes()[0].value, Some(json!(150.0)));
+/// ```
+#[derive(Debug)]
+pub struct DryRunDriver<D> {
+    driver: D,
+    writes: Mutex<Vec<DryRunWrite>>,
+}
+
+impl<D> DryRunDriver<D>
+where
+    D: Driver,
+{
+    /// Wraps a driver so its writes are skipped.
+    ///
+    /// # Parameters
+    /// - `driver`: The driver to read from.
+    ///
+    /// # Returns
+    /// The `DryRunDriver` wrapping `driver`.
+    pub fn new(driver: D) -> Self {
+        DryRunDriver {
+            driver,
+            writes: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the wrapped driver, which writes for real.
+    pub fn inner(&self) -> &D {
+        &self.driver
+    }
+
+    /// Consumes the wrapper, returning the wrapped driver.
+    pub fn into_inner(self) -> D {
+        self.driver
+    }
+
+    /// Returns the writes skipped so far, in the order they were made.
+    pub fn writes(&self) -> Vec<DryRunWrite> {
+        self.writes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Returns the writes skipped so far and clears the log.
+    pub fn take_writes(&self) -> Vec<DryRunWrite> {
+        std::mem::take(&mut *self.writes.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    /// Records a skipped write.
+    fn record(&self, operation: &'static str, key: Option<&str>, value: Option<Value>) {
+        #[cfg(feature = "tracing")]
+        tracing::info!(operation, key, "dry run skipped a write");
+
+        self.writes
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(DryRunWrite {
+                operation,
+                key: key.map(str::to_string),
+                value,
+            });
+    }
+
+    /// Reads the number stored at a key, treating a missing value as `0`.
+    fn number(&self, key: &str) -> Result<f64> {
+        number(self.driver.get(key)?.as_ref(), false)
+    }
+
+    /// Reads the array stored at a key, treating anything else as empty.
+    fn array(&self, key: &str) -> Result<Vec<Value>> {
+        Ok(match self.driver.get(key)? {
+            Some(Value::Array(values)) => values,
+            _ => Vec::new(),
+        })
+    }
+}
+
+impl<D> Driver for DryRunDriver<D>
+where
+    D: Driver,
+{
+    fn ping(&self) -> Result<()> {
+        self.driver.ping()
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        self.driver.all()
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        self.driver.get(key)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        self.record("set", Some(key), Some(value));
+        Ok(())
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        self.driver.has(key)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        let existed = self.driver.has(key)?;
+        if existed {
+            self.record("delete", Some(key), None);
+        }
+        Ok(existed)
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        self.record("delete_all", None, None);
+        Ok(true)
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        let total = self.number(key)? + value;
+        self.record("add", Some(key), Some(json!(total)));
+        Ok(total)
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        let total = self.number(key)? - value;
+        self.record("subtract", Some(key), Some(json!(total)));
+        Ok(total)
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        let mut values = self.array(key)?;
+        values.push(value);
+        self.record("push", Some(key), Some(Value::Array(values.clone())));
+        Ok(values)
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        let mut values = self.array(key)?;
+        values.retain(|existing| existing != &value);
+        self.record("pull", Some(key), Some(Value::Array(values.clone())));
+        Ok(values)
+    }
+
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        let previous = self.driver.get(key)?;
+        self.record("replace", Some(key), Some(value));
+        Ok(previous)
+    }
+
+    fn entry(&self, key: &str) -> Result<Option<DataSet>> {
+        self.driver.entry(key)
+    }
+
+    fn entries(&self) -> Result<Vec<DataSet>> {
+        self.driver.entries()
+    }
+
+    fn split_key(&self, key: &str) -> Vec<String> {
+        self.driver.split_key(key)
+    }
+
+    fn join_key(&self, path: &[String]) -> Option<String> {
+        self.driver.join_key(path)
+    }
+}
@@ -0,0 +1,702 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Lines, Read};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::driver::json_path::{lookup, remove_path, replace, split_key};
+use crate::driver::{number, Driver};
+use crate::error::{transport_error, ChromoeError, Result};
+use crate::structure::{DataSet, EtcdDriverOptions, KeyChange};
+use crate::uri::is_valid_identifier;
+
+/// The number of times a conflicting write is retried before giving up.
+const MAX_ATTEMPTS: usize = 8;
+
+/// Driver storing JSON data in an etcd cluster, such as one holding cluster
+/// configuration.
+///
+/// Each key of a table is stored as the etcd key `<table>/<key>`, with the value
+/// serialised as JSON, so keys can also be read with `etcdctl`. The driver speaks
+/// etcd's v3 JSON gateway over HTTP. Read-modify-write operations, such as `add`
+/// and writes to nested keys, are written in a transaction that only applies if
+/// the key is unchanged since it was read, and retry otherwise, which keeps them
+/// atomic across clients.
+///
+/// [`subscribe`] maps onto etcd's watch API, yielding every change to the keys
+/// of the table as it happens.
+///
+/// # Fields
+///
+/// - `options`: Configuration options for the etcd driver.
+///
+/// # Example Usage
+///
+/// ```rust,no_run
+/// use chromoe_db::driver::etcd_driver::EtcdDriver;
+///
+/// let driver = EtcdDriver::from_uri("etcd://127.0.0.1:2379?table=config").unwrap();
+///
+/// driver.set("features", serde_json::json!({ "leveling": true })).unwrap();
+///
+/// for change in driver.subscribe("features").unwrap() {
+///     let change = change.unwrap();
+///     println!("{} is now {:?}", change.key, change.value);
+/// }
+/// ```
+///
+/// [`subscribe`]: EtcdDriver::subscribe
+#[derive(Debug)]
+pub struct EtcdDriver {
+    /// Configuration options for the etcd driver.
+    pub options: EtcdDriverOptions,
+    agent: ureq::Agent,
+    token: Option<String>,
+}
+
+/// A stream of changes to the keys of a table, returned by [`EtcdDriver::subscribe`].
+///
+/// Iterating blocks until the next change arrives, and ends if the connection
+/// to the server is closed.
+pub struct Subscription {
+    lines: Lines<BufReader<Box<dyn Read + Send + Sync>>>,
+    prefix: String,
+    pending: VecDeque<KeyChange>,
+}
+
+impl EtcdDriver {
+    /// Creates a new instance of the `EtcdDriver`, authenticating with the
+    /// cluster if credentials are configured.
+    ///
+    /// # Parameters
+    /// - `options`: Configuration options for the etcd driver.
+    ///
+    /// # Returns
+    /// A `Result` containing either the `EtcdDriver` instance or an error if
+    /// the table name is invalid or authentication fails.
+    pub fn new(options: EtcdDriverOptions) -> Result<Self> {
+        if !is_valid_identifier(&options.table_name) {
+            return Err(ChromoeError::Config(format!(
+                "`{}` is not a valid table name",
+                options.table_name
+            )));
+        }
+
+        let agent = ureq::AgentBuilder::new().timeout(options.timeout).build();
+        let mut driver = EtcdDriver {
+            options,
+            agent,
+            token: None,
+        };
+
+        if let (Some(username), Some(password)) =
+            (&driver.options.username, &driver.options.password)
+        {
+            let response = driver.call(
+                "/v3/auth/authenticate",
+                json!({ "name": username, "password": password }),
+            )?;
+            driver.token = response["token"].as_str().map(String::from);
+        }
+        Ok(driver)
+    }
+
+    /// Creates a new instance of the `EtcdDriver` from a connection string.
+    ///
+    /// # Parameters
+    /// - `uri`: A connection string as accepted by [`EtcdDriverOptions::from_uri`].
+    ///
+    /// # Returns
+    /// A `Result` containing the `EtcdDriver` instance, or an error if the
+    /// connection string is invalid or authentication fails.
+    pub fn from_uri(uri: &str) -> Result<Self> {
+        Self::new(EtcdDriverOptions::from_uri(uri)?)
+    }
+
+    /// Checks that the cluster is reachable and the table can be read.
+    ///
+    /// # Returns
+    /// A `Result` which is an error if the cluster cannot be reached.
+    pub fn ping(&self) -> Result<()> {
+        let (key, range_end) = self.range("");
+        self.call(
+            "/v3/kv/range",
+            json!({ "key": key, "range_end": range_end, "limit": 1, "keys_only": true }),
+        )?;
+        Ok(())
+    }
+
+    /// Adds a value to an existing entry or creates a new entry if it doesn't exist.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry to update.
+    /// - `value`: The value to add to the current entry.
+    ///
+    /// # Returns
+    /// The new value after adding `value` to the existing entry.
+    pub fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.modify(key, |current| {
+            let new_value = number(current, self.options.lenient_numbers)? + value;
+            Ok((Some(Value::from(new_value)), new_value))
+        })
+    }
+
+    /// Retrieves all data entries from the table as a vector of tuples.
+    ///
+    /// # Returns
+    /// A `Result` containing a vector of key and value pairs, in key order.
+    pub fn all(&self) -> Result<Vec<(String, Value)>> {
+        Ok(self
+            .entries()?
+            .into_iter()
+            .map(|entry| (entry.id, entry.value))
+            .collect())
+    }
+
+    /// Retrieves all data entries from the table along with their version, the
+    /// number of times each key was written since it was created.
+    ///
+    /// # Returns
+    /// A `Result` containing every entry of the table, in key order.
+    pub fn entries(&self) -> Result<Vec<DataSet>> {
+        let (key, range_end) = self.range("");
+        let response = self.call(
+            "/v3/kv/range",
+            json!({ "key": key, "range_end": range_end }),
+        )?;
+
+        let prefix = self.prefix();
+        Ok(response["kvs"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|kv| {
+                let key = decode(&kv["key"])?;
+                let key = key.strip_prefix(&prefix)?.to_string();
+                let value = decode(&kv["value"])
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or(Value::Null);
+
+                let mut entry = DataSet::new(key, value);
+                entry.version = int64(&kv["version"]);
+                Some(entry)
+            })
+            .collect())
+    }
+
+    /// Retrieves the value for a given key along with the version of the entry
+    /// holding it, deserialising the value into the specified type.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing the entry, or `None` if the key doesn't exist.
+    pub fn entry<T>(&self, key: &str) -> Result<Option<DataSet<T>>>
+    where
+        T: DeserializeOwned + Default,
+    {
+        let (root, path) = split_key(key);
+        let response = self.call("/v3/kv/range", json!({ "key": self.encoded_key(root) }))?;
+        let kv = &response["kvs"][0];
+
+        let value = decode(&kv["value"])
+            .and_then(|json| serde_json::from_str::<Value>(&json).ok())
+            .and_then(|root| lookup(&root, &path).cloned());
+
+        Ok(value.map(|value| {
+            let mut entry = DataSet::new(key, serde_json::from_value(value).unwrap_or_default());
+            entry.version = int64(&kv["version"]);
+            entry
+        }))
+    }
+
+    /// Deletes a specific entry by key, or a nested field within an entry.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to delete.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if a value was deleted, or `false` if there
+    /// was no value at `key`.
+    pub fn delete(&self, key: &str) -> Result<bool> {
+        if !key.contains('.') {
+            let response = self.call(
+                "/v3/kv/deleterange",
+                json!({ "key": self.encoded_key(key) }),
+            )?;
+            return Ok(int64(&response["deleted"]).unwrap_or(0) > 0);
+        }
+
+        self.modify(key, |current| Ok((None, current.is_some())))
+    }
+
+    /// Deletes several nested fields of the value at a key with a single write.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the value holding the fields, which may itself be nested.
+    /// - `paths`: The paths of the fields to delete, relative to `key`.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of fields deleted, or a `TypeMismatch`
+    /// error if a path leads through a value that is not an object.
+    pub fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        self.modify(key, |current| {
+            let mut value = current.cloned();
+            let mut deleted = 0;
+            if let Some(value) = value.as_mut() {
+                for path in paths {
+                    if remove_path(value, &path.split('.').collect::<Vec<_>>())? {
+                        deleted += 1;
+                    }
+                }
+            }
+            Ok((value, deleted))
+        })
+    }
+
+    /// Deletes all entries in the table.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the deletion was successful.
+    pub fn delete_all(&self) -> Result<bool> {
+        let (key, range_end) = self.range("");
+        self.call(
+            "/v3/kv/deleterange",
+            json!({ "key": key, "range_end": range_end }),
+        )?;
+        Ok(true)
+    }
+
+    /// Retrieves the value for a given key, deserialising it into the specified type.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` of the deserialised value.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned + Default,
+    {
+        let (root, path) = split_key(key);
+        let value = self
+            .read(root)?
+            .and_then(|(json, _)| serde_json::from_str::<Value>(&json).ok())
+            .and_then(|root| lookup(&root, &path).cloned());
+
+        Ok(value.map(|value| serde_json::from_value(value).unwrap_or_default()))
+    }
+
+    /// Checks if a given key exists.
+    ///
+    /// # Parameters
+    /// - `key`: The key to check.
+    ///
+    /// # Returns
+    /// A `Result` containing a boolean indicating whether the key exists.
+    pub fn has(&self, key: &str) -> Result<bool> {
+        Ok(self.get::<Value>(key)?.is_some())
+    }
+
+    /// Removes a specific value from an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to remove from the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after removal.
+    pub fn pull<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + std::cmp::PartialEq + Clone + Serialize,
+    {
+        self.modify(key, |current| {
+            let mut arr: Vec<T> = current
+                .and_then(|current| serde_json::from_value(current.clone()).ok())
+                .unwrap_or_default();
+            arr.retain(|x| x != &value);
+            Ok((Some(serde_json::to_value(&arr)?), arr))
+        })
+    }
+
+    /// Appends a value to an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to append to the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after the value is appended.
+    pub fn push<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + Clone + Serialize,
+    {
+        self.modify(key, |current| {
+            let mut arr: Vec<T> = current
+                .and_then(|current| serde_json::from_value(current.clone()).ok())
+                .unwrap_or_default();
+            arr.push(value.clone());
+            Ok((Some(serde_json::to_value(&arr)?), arr))
+        })
+    }
+
+    /// Sets the value for a given key and returns the value it replaced, with
+    /// the same guarantees against concurrent writers as `set`.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` containing the previous value, or `None` if the key didn't
+    /// exist, or a `Json` error if the previous value cannot be deserialised
+    /// into the type of `value`.
+    pub fn replace<T>(&self, key: &str, value: T) -> Result<Option<T>>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let value = serde_json::to_value(value)?;
+        let previous = self.modify(key, |current| Ok((Some(value.clone()), current.cloned())))?;
+        Ok(previous.map(serde_json::from_value).transpose()?)
+    }
+
+    /// Sets or updates the value for a given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the value was stored.
+    pub fn set<T>(&self, key: &str, value: T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        if !key.contains('.') {
+            self.call(
+                "/v3/kv/put",
+                json!({ "key": self.encoded_key(key), "value": STANDARD.encode(value.to_string()) }),
+            )?;
+            return Ok(());
+        }
+
+        self.modify(key, |_| Ok((Some(value.clone()), ())))
+    }
+
+    /// Subtracts a value from an existing entry. If the entry does not exist,
+    /// it initialises it with the result.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to subtract from.
+    /// - `value`: The value to subtract from the current value.
+    ///
+    /// # Returns
+    /// The new value after subtraction.
+    pub fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.modify(key, |current| {
+            let new_value = number(current, self.options.lenient_numbers)? - value;
+            Ok((Some(Value::from(new_value)), new_value))
+        })
+    }
+
+    /// Watches the keys of the table starting with a prefix, yielding every
+    /// change made to them from now on.
+    ///
+    /// # Parameters
+    /// - `prefix`: The prefix of the keys to watch, or `""` for the whole table.
+    ///
+    /// # Returns
+    /// A `Result` containing a [`Subscription`] iterating over the changes, or
+    /// an error if the watch cannot be created.
+    pub fn subscribe(&self, prefix: &str) -> Result<Subscription> {
+        let (key, range_end) = self.range(prefix);
+        // Watches stay open indefinitely, so only connecting is bounded by the timeout.
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(self.options.timeout)
+            .build();
+
+        let request = self.authorize(agent.post(&self.url("/v3/watch")));
+        let body = json!({ "create_request": { "key": key, "range_end": range_end } });
+        let response = self
+            .options
+            .reconnect
+            .send(&request, Some(&body))
+            .map_err(etcd_error)?;
+
+        Ok(Subscription {
+            lines: BufReader::new(response.into_reader()).lines(),
+            prefix: self.prefix(),
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// Replaces the value at a key, retrying if the key changes between reading
+    /// and writing it.
+    ///
+    /// `update` receives the current value at the key and returns its new value,
+    /// `None` removing it, along with the result of the operation.
+    fn modify<T, F>(&self, key: &str, mut update: F) -> Result<T>
+    where
+        F: FnMut(Option<&Value>) -> Result<(Option<Value>, T)>,
+    {
+        let (root, path) = split_key(key);
+        let encoded_key = self.encoded_key(root);
+
+        for _ in 0..MAX_ATTEMPTS {
+            let (current, revision) = match self.read(root)? {
+                Some((json, revision)) => (Some(json), revision),
+                None => (None, "0".to_string()),
+            };
+            let mut root_value = current
+                .as_deref()
+                .and_then(|json| serde_json::from_str(json).ok());
+
+            let (value, result) = update(root_value.as_ref().and_then(|root| lookup(root, &path)))?;
+            replace(&mut root_value, &path, value)?;
+
+            let operation = match (&current, &root_value) {
+                (None, None) => return Ok(result),
+                (_, Some(new)) => json!({ "request_put": {
+                    "key": encoded_key,
+                    "value": STANDARD.encode(new.to_string()),
+                } }),
+                (Some(_), None) => json!({ "request_delete_range": { "key": encoded_key } }),
+            };
+
+            // A key that doesn't exist has a modification revision of zero.
+            let response = self.call(
+                "/v3/kv/txn",
+                json!({
+                    "compare": [{
+                        "key": encoded_key,
+                        "target": "MOD",
+                        "result": "EQUAL",
+                        "mod_revision": revision,
+                    }],
+                    "success": [operation],
+                }),
+            )?;
+            if response["succeeded"].as_bool().unwrap_or(false) {
+                return Ok(result);
+            }
+        }
+
+        Err(ChromoeError::Http(format!(
+            "`{}` kept changing while it was being updated",
+            root
+        )))
+    }
+
+    /// Reads the serialised value of a key along with its modification revision.
+    fn read(&self, key: &str) -> Result<Option<(String, String)>> {
+        let response = self.call("/v3/kv/range", json!({ "key": self.encoded_key(key) }))?;
+        Ok(response["kvs"].get(0).and_then(|kv| {
+            let revision = int64(&kv["mod_revision"])?.to_string();
+            Some((decode(&kv["value"])?, revision))
+        }))
+    }
+
+    /// Returns the prefix of the etcd keys holding the table's entries.
+    fn prefix(&self) -> String {
+        format!("{}/", self.options.table_name)
+    }
+
+    /// Returns the base64-encoded etcd key holding an entry.
+    fn encoded_key(&self, key: &str) -> String {
+        STANDARD.encode(format!("{}{}", self.prefix(), key))
+    }
+
+    /// Returns the base64-encoded range of etcd keys holding the entries whose
+    /// key starts with `prefix`.
+    fn range(&self, prefix: &str) -> (String, String) {
+        let start = format!("{}{}", self.prefix(), prefix).into_bytes();
+
+        // The range ends at the first key that doesn't start with `start`.
+        let mut end = start.clone();
+        while let Some(last) = end.pop() {
+            if last < u8::MAX {
+                end.push(last + 1);
+                break;
+            }
+        }
+        (STANDARD.encode(start), STANDARD.encode(end))
+    }
+
+    /// Sends a JSON request to the gateway and returns the JSON response.
+    fn call(&self, path: &str, body: Value) -> Result<Value> {
+        let request = self.authorize(self.agent.post(&self.url(path)));
+        Ok(self
+            .options
+            .reconnect
+            .send(&request, Some(&body))
+            .map_err(etcd_error)?
+            .into_json()?)
+    }
+
+    /// Adds the authentication token to a request, if the driver has one.
+    fn authorize(&self, request: ureq::Request) -> ureq::Request {
+        match &self.token {
+            Some(token) => request.set("Authorization", token),
+            None => request,
+        }
+    }
+
+    /// Returns the URL of a gateway endpoint.
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.options.url.trim_end_matches('/'), path)
+    }
+}
+
+impl Iterator for Subscription {
+    type Item = Result<KeyChange>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(change) = self.pending.pop_front() {
+                return Some(Ok(change));
+            }
+
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.into())),
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            let message: Value = match serde_json::from_str(&line) {
+                Ok(message) => message,
+                Err(err) => return Some(Err(err.into())),
+            };
+            if let Some(error) = message.get("error") {
+                return Some(Err(ChromoeError::Http(format!(
+                    "watch failed: {}",
+                    error["message"].as_str().unwrap_or("unknown error")
+                ))));
+            }
+
+            for event in message["result"]["events"].as_array().into_iter().flatten() {
+                let Some(key) = decode(&event["kv"]["key"]) else {
+                    continue;
+                };
+                let Some(key) = key.strip_prefix(&self.prefix) else {
+                    continue;
+                };
+
+                // Puts are the default event type, which the gateway leaves out.
+                let value = if event["type"] == "DELETE" {
+                    None
+                } else {
+                    Some(
+                        decode(&event["kv"]["value"])
+                            .and_then(|json| serde_json::from_str(&json).ok())
+                            .unwrap_or(Value::Null),
+                    )
+                };
+                self.pending.push_back(KeyChange {
+                    key: key.to_string(),
+                    value,
+                });
+            }
+        }
+    }
+}
+
+impl Driver for EtcdDriver {
+    fn ping(&self) -> Result<()> {
+        EtcdDriver::ping(self)
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        EtcdDriver::all(self)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        EtcdDriver::get(self, key)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        EtcdDriver::set(self, key, value)
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        EtcdDriver::has(self, key)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        EtcdDriver::delete(self, key)
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        EtcdDriver::delete_all(self)
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        EtcdDriver::add(self, key, value)
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        EtcdDriver::subtract(self, key, value)
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        EtcdDriver::push(self, key, value)
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        EtcdDriver::pull(self, key, value)
+    }
+
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        EtcdDriver::replace(self, key, value)
+    }
+
+    fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        EtcdDriver::delete_many_nested(self, key, paths)
+    }
+
+    fn entry(&self, key: &str) -> Result<Option<DataSet>> {
+        EtcdDriver::entry(self, key)
+    }
+
+    fn entries(&self) -> Result<Vec<DataSet>> {
+        EtcdDriver::entries(self)
+    }
+}
+
+/// Reads a 64-bit integer returned by the gateway, which encodes them as strings.
+fn int64(value: &Value) -> Option<u64> {
+    match value {
+        Value::String(value) => value.parse().ok(),
+        value => value.as_u64(),
+    }
+}
+
+/// Decodes a base64-encoded key or value returned by the gateway.
+fn decode(value: &Value) -> Option<String> {
+    let bytes = STANDARD.decode(value.as_str()?).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
+/// Converts a failed request into a `ChromoeError`, keeping etcd's error message.
+fn etcd_error(err: ureq::Error) -> ChromoeError {
+    match err {
+        ureq::Error::Status(status, response) => {
+            let message = response
+                .into_json::<Value>()
+                .ok()
+                .and_then(|body| {
+                    body.get("message")
+                        .and_then(Value::as_str)
+                        .map(String::from)
+                })
+                .unwrap_or_else(|| "no error message".to_string());
+            ChromoeError::Http(format!("etcd responded with {}: {}", status, message))
+        }
+        ureq::Error::Transport(transport) => transport_error(transport),
+    }
+}
@@ -0,0 +1,353 @@
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::driver::Driver;
+use crate::error::{ChromoeError, Result};
+use crate::structure::DataSet;
+
+/// Which backends are in use, and the writes made to the secondary backend
+/// while the primary backend was unavailable.
+#[derive(Debug, Default)]
+struct FailoverState {
+    /// When the primary backend last failed, or `None` while it is in use.
+    failed_at: Option<Instant>,
+    /// The keys written to the secondary backend, in the order they were first
+    /// written, when replaying writes is enabled.
+    pending: Vec<String>,
+    /// Whether every entry of the secondary backend was deleted, which is
+    /// replayed before the keys in `pending`.
+    cleared: bool,
+}
+
+/// A driver composed of a primary and a secondary backend, which keeps working
+/// through an outage of the primary backend by running operations against the
+/// secondary backend.
+///
+/// Operations run against the primary backend until it fails with an error
+/// indicating it is unavailable, such as a connection, I/O or timeout error,
+/// in which case the operation is retried against the secondary backend.
+/// Errors describing the data, such as a value of the wrong type, are returned
+/// as they are. Operations then go to the secondary backend directly, until the
+/// retry interval has passed and the primary backend is tried again.
+///
+/// Writes made to the secondary backend are not applied to the primary backend
+/// unless replaying is enabled with [`FailoverDriver::with_replay`], so reads
+/// after a recovery only see them if the backends replicate each other. The
+/// secondary backend should hold a copy of the data, such as a replica or a
+/// local cache, for reads made during an outage to be meaningful.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use std::time::Duration;
+///
+/// use chromoe_db::driver::failover_driver::FailoverDriver;
+/// use chromoe_db::driver::Driver;
+/// use chromoe_db::Database;
+/// use serde_json::json;
+///
+/// let primary = Database::open("sqlite://:memory:").unwrap();
+/// let local = Database::open("sqlite://:memory:").unwrap();
+///
+/// let driver = FailoverDriver::new(primary, local)
+///     .with_retry_interval(Duration::from_secs(10))
+///     .with_replay(true);
+///
+/// driver.set("user.coins", json!(100)).unwrap();
+/// assert!(!driver.is_failed_over());
+/// assert_eq!(driver.primary().get("user.coins").unwrap(), Some(json!(100)));
+/// ```
+#[derive(Debug)]
+pub struct FailoverDriver<P, S> {
+    primary: P,
+    secondary: S,
+    retry_interval: Duration,
+    replay: bool,
+    state: Mutex<FailoverState>,
+}
+
+impl<P, S> FailoverDriver<P, S>
+where
+    P: Driver,
+    S: Driver,
+{
+    /// Creates a driver failing over from `primary` to `secondary`, retrying
+    /// the primary backend every five seconds during an outage, without
+    /// replaying writes.
+    ///
+    /// # Parameters
+    /// - `primary`: The backend used while it is available.
+    /// - `secondary`: The backend used while the primary backend is unavailable.
+    ///
+    /// # Returns
+    /// The `FailoverDriver` composed of both backends.
+    pub fn new(primary: P, secondary: S) -> Self {
+        FailoverDriver {
+            primary,
+            secondary,
+            retry_interval: Duration::from_secs(5),
+            replay: false,
+            state: Mutex::new(FailoverState::default()),
+        }
+    }
+
+    /// Sets how long operations go to the secondary backend after the primary
+    /// backend failed, before the primary backend is tried again.
+    ///
+    /// # Parameters
+    /// - `interval`: The time between attempts to use the primary backend,
+    ///   where zero tries it on every operation.
+    ///
+    /// # Returns
+    /// The `FailoverDriver` with the retry interval set.
+    pub fn with_retry_interval(mut self, interval: Duration) -> Self {
+        self.retry_interval = interval;
+        self
+    }
+
+    /// Sets whether writes made to the secondary backend during an outage are
+    /// replayed to the primary backend once it is available again.
+    ///
+    /// The keys written are recorded, and their values are copied from the
+    /// secondary backend to the primary backend before the next operation
+    /// that reaches the primary backend, overwriting changes made to the
+    /// primary backend in the meantime.
+    ///
+    /// # Parameters
+    /// - `replay`: Whether to replay writes.
+    ///
+    /// # Returns
+    /// The `FailoverDriver` with replaying set.
+    pub fn with_replay(mut self, replay: bool) -> Self {
+        self.replay = replay;
+        self
+    }
+
+    /// Returns the primary backend.
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+
+    /// Returns the secondary backend.
+    pub fn secondary(&self) -> &S {
+        &self.secondary
+    }
+
+    /// Returns whether operations currently go to the secondary backend.
+    pub fn is_failed_over(&self) -> bool {
+        self.state().failed_at.is_some()
+    }
+
+    /// Returns the keys written to the secondary backend that are waiting to
+    /// be replayed to the primary backend.
+    pub fn pending_writes(&self) -> Vec<String> {
+        self.state().pending.clone()
+    }
+
+    /// Locks the failover state.
+    fn state(&self) -> MutexGuard<'_, FailoverState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Runs an operation against the primary backend, or against the secondary
+    /// backend if the primary backend is unavailable.
+    ///
+    /// # Returns
+    /// A `Result` containing the result of the operation, and whether it ran
+    /// against the secondary backend.
+    fn run<T, F>(&self, operation: F) -> Result<(T, bool)>
+    where
+        F: Fn(&dyn Driver) -> Result<T>,
+    {
+        let failed_at = self.state().failed_at;
+        let try_primary = failed_at.is_none_or(|at| at.elapsed() >= self.retry_interval);
+
+        if try_primary {
+            let recovered = failed_at.is_none() || self.replay_writes().is_ok();
+            if recovered {
+                match operation(&self.primary) {
+                    Ok(value) => {
+                        if failed_at.is_some() {
+                            #[cfg(feature = "tracing")]
+                            tracing::info!("primary backend recovered");
+                            self.state().failed_at = None;
+                        }
+                        return Ok((value, false));
+                    }
+                    Err(err) if is_unavailable(&err) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(error = %err, "primary backend failed, using the secondary backend");
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+            self.state().failed_at = Some(Instant::now());
+        }
+
+        Ok((operation(&self.secondary)?, true))
+    }
+
+    /// Runs a write of a key, recording it for replay if it ran against the
+    /// secondary backend.
+    fn write<T, F>(&self, key: &str, operation: F) -> Result<T>
+    where
+        F: Fn(&dyn Driver) -> Result<T>,
+    {
+        let (value, on_secondary) = self.run(operation)?;
+        if on_secondary {
+            self.record(key);
+        }
+        Ok(value)
+    }
+
+    /// Records a key written to the secondary backend.
+    fn record(&self, key: &str) {
+        if !self.replay {
+            return;
+        }
+        let mut state = self.state();
+        if !state.pending.iter().any(|pending| pending == key) {
+            state.pending.push(key.to_string());
+        }
+    }
+
+    /// Copies the values of the keys written to the secondary backend to the
+    /// primary backend, keeping the keys that could not be copied.
+    fn replay_writes(&self) -> Result<()> {
+        let (cleared, pending) = {
+            let state = self.state();
+            (state.cleared, state.pending.clone())
+        };
+
+        if cleared {
+            self.primary.delete_all()?;
+            self.state().cleared = false;
+        }
+        for key in pending {
+            match self.secondary.get(&key)? {
+                Some(value) => self.primary.set(&key, value)?,
+                None => {
+                    self.primary.delete(&key)?;
+                }
+            }
+            self.state().pending.retain(|pending| pending != &key);
+        }
+        Ok(())
+    }
+}
+
+impl<P, S> Driver for FailoverDriver<P, S>
+where
+    P: Driver,
+    S: Driver,
+{
+    fn ping(&self) -> Result<()> {
+        self.run(|driver| driver.ping()).map(|_| ())
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        Ok(self.run(|driver| driver.all())?.0)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        Ok(self.run(|driver| driver.get(key))?.0)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        self.write(key, |driver| driver.set(key, value.clone()))
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        Ok(self.run(|driver| driver.has(key))?.0)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        self.write(key, |driver| driver.delete(key))
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        let (deleted, on_secondary) = self.run(|driver| driver.delete_all())?;
+        if on_secondary && self.replay {
+            let mut state = self.state();
+            state.cleared = true;
+            state.pending.clear();
+        }
+        Ok(deleted)
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.write(key, |driver| driver.add(key, value))
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.write(key, |driver| driver.subtract(key, value))
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        self.write(key, |driver| driver.push(key, value.clone()))
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        self.write(key, |driver| driver.pull(key, value.clone()))
+    }
+
+    fn insert(&self, value: Value) -> Result<String> {
+        let (key, on_secondary) = self.run(|driver| driver.insert(value.clone()))?;
+        if on_secondary {
+            self.record(&key);
+        }
+        Ok(key)
+    }
+
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        self.write(key, |driver| driver.replace(key, value.clone()))
+    }
+
+    fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        self.write(key, |driver| driver.delete_many_nested(key, paths))
+    }
+
+    fn entry(&self, key: &str) -> Result<Option<DataSet>> {
+        Ok(self.run(|driver| driver.entry(key))?.0)
+    }
+
+    fn entries(&self) -> Result<Vec<DataSet>> {
+        Ok(self.run(|driver| driver.entries())?.0)
+    }
+
+    fn split_key(&self, key: &str) -> Vec<String> {
+        self.primary.split_key(key)
+    }
+
+    fn join_key(&self, path: &[String]) -> Option<String> {
+        self.primary.join_key(path)
+    }
+}
+
+/// Returns whether an error indicates that a backend is unavailable, rather
+/// than a problem with the data or the request.
+fn is_unavailable(err: &ChromoeError) -> bool {
+    match err {
+        #[cfg(feature = "sqlite")]
+        ChromoeError::Sqlite(_) => true,
+        #[cfg(feature = "d1")]
+        ChromoeError::D1(_) => true,
+        #[cfg(feature = "rocksdb")]
+        ChromoeError::RocksDb(_) => true,
+        #[cfg(feature = "scylla")]
+        ChromoeError::Scylla(_) => true,
+        ChromoeError::Io(_) | ChromoeError::Http(_) | ChromoeError::Timeout(_) => true,
+        ChromoeError::Json(_)
+        | ChromoeError::InvalidUri(_)
+        | ChromoeError::Config(_)
+        | ChromoeError::RateLimited(_)
+        | ChromoeError::QuotaExceeded(_)
+        | ChromoeError::PermissionDenied(_)
+        | ChromoeError::InvalidPath(_)
+        | ChromoeError::TypeMismatch { .. }
+        | ChromoeError::Corrupted { .. }
+        | ChromoeError::ChecksumMismatch { .. } => false,
+    }
+}
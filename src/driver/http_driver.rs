@@ -0,0 +1,366 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::driver::json_path::{join_escaped, split_escaped};
+use crate::driver::Driver;
+use crate::error::{transport_error, ChromoeError, Result};
+use crate::structure::HttpDriverOptions;
+use crate::uri::encode_segment;
+
+/// Driver operating on a table served by a remote chromoe-db server.
+///
+/// The `HttpDriver` speaks the REST API of `chromoe_db::server`, which lets a
+/// separate process, such as a web dashboard, share a bot's database without
+/// opening the SQLite file itself. Every operation is a single request, and
+/// read-modify-write operations such as `add` and `push` run atomically on the
+/// server. Requests that fail because the server could not be reached, and
+/// reads that fail because the connection dropped, are retried with the
+/// backoff of the `reconnect` option.
+///
+/// # Fields
+///
+/// - `options`: Configuration options for the HTTP driver.
+///
+/// # Example Usage
+///
+/// ```rust,no_run
+/// use chromoe_db::driver::http_driver::HttpDriver;
+///
+/// let driver = HttpDriver::from_uri("http://127.0.0.1:8080?table=economy&token=secret").unwrap();
+///
+/// driver.set("coins", 100).unwrap();
+/// assert_eq!(driver.add("coins", 50.0).unwrap(), 150.0);
+/// ```
+#[derive(Debug)]
+pub struct HttpDriver {
+    /// Configuration options for the HTTP driver.
+    pub options: HttpDriverOptions,
+    agent: ureq::Agent,
+}
+
+impl HttpDriver {
+    /// Creates a new instance of the `HttpDriver`. No request is made until the
+    /// driver is used; call `ping` to check that the server is reachable.
+    ///
+    /// # Parameters
+    /// - `options`: Configuration options for the HTTP driver.
+    ///
+    /// # Returns
+    /// A `Result` containing the `HttpDriver` instance.
+    pub fn new(options: HttpDriverOptions) -> Result<Self> {
+        let agent = ureq::AgentBuilder::new().timeout(options.timeout).build();
+        Ok(HttpDriver { options, agent })
+    }
+
+    /// Creates a new instance of the `HttpDriver` from a connection string.
+    ///
+    /// # Parameters
+    /// - `uri`: A connection string as accepted by [`HttpDriverOptions::from_uri`].
+    ///
+    /// # Returns
+    /// A `Result` containing the `HttpDriver` instance, or an error if the
+    /// connection string is invalid.
+    pub fn from_uri(uri: &str) -> Result<Self> {
+        Self::new(HttpDriverOptions::from_uri(uri)?)
+    }
+
+    /// Checks that the server is reachable and its database is responding.
+    ///
+    /// # Returns
+    /// A `Result` which is an error if the server cannot be reached.
+    pub fn ping(&self) -> Result<()> {
+        self.send(self.request("GET", "/ping"), None)?;
+        Ok(())
+    }
+
+    /// Adds a value to an existing entry or creates a new entry if it doesn't exist.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry to update.
+    /// - `value`: The value to add to the current entry.
+    ///
+    /// # Returns
+    /// The new value after adding `value` to the existing entry.
+    pub fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.update(key, "add", Value::from(value))
+    }
+
+    /// Retrieves all data entries of the table as a vector of tuples.
+    ///
+    /// # Returns
+    /// A `Result` containing the entries of the table.
+    pub fn all(&self) -> Result<Vec<(String, Value)>> {
+        let entries: Map<String, Value> = self.receive(self.table_request("GET", None))?;
+        Ok(entries.into_iter().collect())
+    }
+
+    /// Deletes a specific entry by key, or a nested field within an entry.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to delete.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if a value was deleted, or `false` if there
+    /// was no value at `key`. An unknown table is an error.
+    pub fn delete(&self, key: &str) -> Result<bool> {
+        match self.call(&self.table_request("DELETE", Some(key)), None) {
+            Ok(_) => Ok(true),
+            Err(ureq::Error::Status(404, response)) => missing_value(response).map(|()| false),
+            Err(err) => Err(http_error(err)),
+        }
+    }
+
+    /// Deletes all entries in the table.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the deletion was successful.
+    pub fn delete_all(&self) -> Result<bool> {
+        self.send(self.table_request("DELETE", None), None)?;
+        Ok(true)
+    }
+
+    /// Retrieves the value for a given key, deserialising it into the specified type.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` of the deserialised value. An unknown
+    /// table is an error.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned + Default,
+    {
+        match self.call(&self.table_request("GET", Some(key)), None) {
+            Ok(response) => Ok(Some(response.into_json()?)),
+            Err(ureq::Error::Status(404, response)) => missing_value(response).map(|()| None),
+            Err(err) => Err(http_error(err)),
+        }
+    }
+
+    /// Checks if a given key exists.
+    ///
+    /// # Parameters
+    /// - `key`: The key to check.
+    ///
+    /// # Returns
+    /// A `Result` containing a boolean indicating whether the key exists.
+    pub fn has(&self, key: &str) -> Result<bool> {
+        Ok(self.get::<Value>(key)?.is_some())
+    }
+
+    /// Removes a specific value from an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to remove from the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after removal.
+    pub fn pull<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + std::cmp::PartialEq + Clone + Serialize,
+    {
+        self.update(key, "pull", serde_json::to_value(value)?)
+    }
+
+    /// Appends a value to an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to append to the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after the value is appended.
+    pub fn push<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + Clone + Serialize,
+    {
+        self.update(key, "push", serde_json::to_value(value)?)
+    }
+
+    /// Sets or updates the value for a given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the value was stored.
+    pub fn set<T>(&self, key: &str, value: T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        self.send(self.table_request("PUT", Some(key)), Some(value))?;
+        Ok(())
+    }
+
+    /// Subtracts a value from an existing entry. If the entry does not exist,
+    /// it initialises it with the result.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to subtract from.
+    /// - `value`: The value to subtract from the current value.
+    ///
+    /// # Returns
+    /// The new value after subtraction.
+    pub fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.update(key, "subtract", Value::from(value))
+    }
+
+    /// Runs one of the server's atomic update operations on a key.
+    fn update<T>(&self, key: &str, operation: &str, value: Value) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        let path = format!(
+            "/tables/{}/{}/{}",
+            self.options.table_name,
+            encode_segment(key),
+            operation
+        );
+        self.receive_with(self.request("POST", &path), Some(value))
+    }
+
+    /// Builds a request for the table, or for a key of the table.
+    fn table_request(&self, method: &str, key: Option<&str>) -> ureq::Request {
+        let path = match key {
+            Some(key) => format!(
+                "/tables/{}/{}",
+                self.options.table_name,
+                encode_segment(key)
+            ),
+            None => format!("/tables/{}", self.options.table_name),
+        };
+        self.request(method, &path)
+    }
+
+    /// Builds an authenticated request for a path of the server.
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        let url = format!("{}{}", self.options.url.trim_end_matches('/'), path);
+        let request = self.agent.request(method, &url);
+        match &self.options.token {
+            Some(token) => request.set("Authorization", &format!("Bearer {}", token)),
+            None => request,
+        }
+    }
+
+    /// Sends a request, with an optional JSON body.
+    fn send(&self, request: ureq::Request, body: Option<Value>) -> Result<ureq::Response> {
+        self.call(&request, body.as_ref()).map_err(http_error)
+    }
+
+    /// Sends a request, retrying it as configured by the `reconnect` option
+    /// if the connection to the server drops.
+    // The error of ureq is returned as is so status codes can be matched.
+    #[allow(clippy::result_large_err)]
+    fn call(
+        &self,
+        request: &ureq::Request,
+        body: Option<&Value>,
+    ) -> std::result::Result<ureq::Response, ureq::Error> {
+        self.options.reconnect.send(request, body)
+    }
+
+    /// Sends a request without a body and deserialises the JSON response.
+    fn receive<T>(&self, request: ureq::Request) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        self.receive_with(request, None)
+    }
+
+    /// Sends a request and deserialises the JSON response.
+    fn receive_with<T>(&self, request: ureq::Request, body: Option<Value>) -> Result<T>
+    where
+        T: DeserializeOwned,
+    {
+        Ok(self.send(request, body)?.into_json()?)
+    }
+}
+
+impl Driver for HttpDriver {
+    fn ping(&self) -> Result<()> {
+        HttpDriver::ping(self)
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        HttpDriver::all(self)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        HttpDriver::get(self, key)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        HttpDriver::set(self, key, value)
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        HttpDriver::has(self, key)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        HttpDriver::delete(self, key)
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        HttpDriver::delete_all(self)
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        HttpDriver::add(self, key, value)
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        HttpDriver::subtract(self, key, value)
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        HttpDriver::push(self, key, value)
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        HttpDriver::pull(self, key, value)
+    }
+
+    // The server splits keys like `SQLiteDriver` with the default separator.
+    fn split_key(&self, key: &str) -> Vec<String> {
+        split_escaped(key, '.')
+    }
+
+    fn join_key(&self, path: &[String]) -> Option<String> {
+        Some(join_escaped(path, '.'))
+    }
+}
+
+/// Converts a failed request into a `ChromoeError`, keeping the server's error message.
+fn http_error(err: ureq::Error) -> ChromoeError {
+    match err {
+        ureq::Error::Status(status, response) => {
+            status_error(status, &response.into_json().unwrap_or_default())
+        }
+        ureq::Error::Transport(transport) => transport_error(transport),
+    }
+}
+
+/// Checks that a `404` response is for a key without a value, rather than for
+/// an unknown table, which the server also responds to with `404`.
+fn missing_value(response: ureq::Response) -> Result<()> {
+    let body: Value = response.into_json().unwrap_or_default();
+    match body.get("code").and_then(Value::as_str) {
+        Some("no_value") => Ok(()),
+        _ => Err(status_error(404, &body)),
+    }
+}
+
+/// Builds the error for a response with an error status from its JSON body.
+fn status_error(status: u16, body: &Value) -> ChromoeError {
+    let message = body
+        .get("error")
+        .and_then(Value::as_str)
+        .unwrap_or("no error message");
+    ChromoeError::Http(format!("server responded with {}: {}", status, message))
+}
@@ -0,0 +1,127 @@
+use serde_json::{Map, Value};
+
+use crate::error::{ChromoeError, Result};
+use crate::structure::JsonType;
+
+/// Splits a key into the row key and the path of the nested value within it.
+pub(crate) fn split_key(key: &str) -> (&str, Vec<&str>) {
+    let mut parts = key.split('.');
+    let root = parts.next().unwrap_or(key);
+    (root, parts.collect())
+}
+
+/// Splits a key into the row key and the path of the nested value within it,
+/// at every unescaped separator. A backslash escapes the character following
+/// it, so `\.` stands for a literal `.` and `\\` for a literal backslash.
+#[cfg(any(feature = "sqlite", feature = "http"))]
+pub(crate) fn split_escaped(key: &str, separator: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = key.chars();
+
+    while let Some(c) = chars.next() {
+        let part = parts.last_mut().expect("parts is never empty");
+        match c {
+            '\\' => part.push(chars.next().unwrap_or('\\')),
+            c if c == separator => parts.push(String::new()),
+            c => part.push(c),
+        }
+    }
+    parts
+}
+
+/// Builds the key of a nested path split by `split_escaped`, escaping the
+/// separators and backslashes within its parts.
+#[cfg(any(feature = "sqlite", feature = "http"))]
+pub(crate) fn join_escaped(path: &[String], separator: char) -> String {
+    let mut key = String::new();
+    for (index, part) in path.iter().enumerate() {
+        if index > 0 {
+            key.push(separator);
+        }
+        for c in part.chars() {
+            if c == '\\' || c == separator {
+                key.push('\\');
+            }
+            key.push(c);
+        }
+    }
+    key
+}
+
+/// Returns the nested value at a path within a row's value.
+pub(crate) fn lookup<'a>(root: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(root, |value, part| value.get(*part))
+}
+
+/// Removes the nested field at a path within a row's value.
+///
+/// # Returns
+/// A `Result` containing whether the field existed, or a `TypeMismatch` error
+/// if the path leads through a value that is not an object.
+pub(crate) fn remove_path(root: &mut Value, path: &[&str]) -> Result<bool> {
+    let Some((last, parents)) = path.split_last() else {
+        return Ok(false);
+    };
+
+    let mut current = root;
+    for part in parents {
+        let object = as_object(current)?;
+        match object.get_mut(*part) {
+            Some(value) => current = value,
+            None => return Ok(false),
+        }
+    }
+
+    let object = as_object(current)?;
+    Ok(object.remove(*last).is_some())
+}
+
+/// Returns the fields of a value that must be an object to hold a nested path.
+///
+/// # Returns
+/// A `Result` containing the fields, or a `TypeMismatch` error if the value is
+/// not an object.
+fn as_object(value: &mut Value) -> Result<&mut Map<String, Value>> {
+    let found = JsonType::of(value);
+    value
+        .as_object_mut()
+        .ok_or_else(|| ChromoeError::TypeMismatch {
+            expected: "object".to_string(),
+            found,
+        })
+}
+
+/// Replaces the nested value at a path within a row's value, creating missing
+/// objects along the way. Replacing the root with `None` removes the row, while
+/// removing a nested value whose parents are missing leaves the row unchanged.
+pub(crate) fn replace(root: &mut Option<Value>, path: &[&str], value: Option<Value>) -> Result<()> {
+    let Some((last, parents)) = path.split_last() else {
+        *root = value;
+        return Ok(());
+    };
+    if root.is_none() && value.is_none() {
+        return Ok(());
+    }
+
+    let mut current = root.get_or_insert_with(|| Value::Object(Map::new()));
+    for part in parents {
+        let object = as_object(current)?;
+        if value.is_none() && !object.contains_key(*part) {
+            return Ok(());
+        }
+        current = object
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Object(Map::new()));
+    }
+
+    let object = as_object(current)?;
+    match value {
+        Some(value) => {
+            object.insert(last.to_string(), value);
+        }
+        None => {
+            object.remove(*last);
+        }
+    }
+    Ok(())
+}
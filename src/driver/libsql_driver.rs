@@ -0,0 +1,542 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{from_str, json, Value};
+
+use crate::driver::json_path::{lookup, remove_path, replace, split_key};
+use crate::driver::{number, Driver};
+use crate::error::{transport_error, ChromoeError, Result};
+use crate::structure::LibsqlDriverOptions;
+use crate::uri::is_valid_identifier;
+
+/// Driver storing JSON data in a remote libSQL database, such as one hosted by Turso.
+///
+/// The `LibsqlDriver` uses the same table layout as the `SQLiteDriver`, so a
+/// database can be moved between a local file and a libSQL server as is. It
+/// speaks the Hrana protocol over HTTP, sending each operation as a single
+/// pipeline request. Read-modify-write operations, including writes to nested
+/// keys, run in an interactive transaction on the server, so they are atomic
+/// with respect to other clients.
+///
+/// # Fields
+///
+/// - `options`: Configuration options for the libSQL driver.
+///
+/// # Example Usage
+///
+/// ```rust,no_run
+/// use chromoe_db::driver::libsql_driver::LibsqlDriver;
+///
+/// let driver = LibsqlDriver::from_uri("libsql://economy-reina.turso.io?authToken=secret").unwrap();
+///
+/// driver.set("coins", 100).unwrap();
+/// assert_eq!(driver.add("coins", 50.0).unwrap(), 150.0);
+/// ```
+#[derive(Debug)]
+pub struct LibsqlDriver {
+    /// Configuration options for the libSQL driver.
+    pub options: LibsqlDriverOptions,
+    agent: ureq::Agent,
+}
+
+/// An open Hrana stream, identified by the baton returned by the server.
+struct Stream {
+    url: String,
+    baton: Option<String>,
+}
+
+impl LibsqlDriver {
+    /// Creates a new instance of the `LibsqlDriver`, creating the table on the
+    /// server if it doesn't already exist.
+    ///
+    /// # Parameters
+    /// - `options`: Configuration options for the libSQL driver.
+    ///
+    /// # Returns
+    /// A `Result` containing either the `LibsqlDriver` instance or an error if
+    /// the table name is invalid or the server cannot be reached.
+    pub fn new(options: LibsqlDriverOptions) -> Result<Self> {
+        if !is_valid_identifier(&options.table_name) {
+            return Err(ChromoeError::Config(format!(
+                "`{}` is not a valid table name",
+                options.table_name
+            )));
+        }
+
+        let agent = ureq::AgentBuilder::new().timeout(options.timeout).build();
+        let driver = LibsqlDriver { options, agent };
+        driver.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {} (ID TEXT PRIMARY KEY, JSON TEXT)",
+                driver.options.table_name
+            ),
+            &[],
+        )?;
+        Ok(driver)
+    }
+
+    /// Creates a new instance of the `LibsqlDriver` from a connection string.
+    ///
+    /// # Parameters
+    /// - `uri`: A connection string as accepted by [`LibsqlDriverOptions::from_uri`].
+    ///
+    /// # Returns
+    /// A `Result` containing the `LibsqlDriver` instance, or an error if the
+    /// connection string is invalid or the server cannot be reached.
+    pub fn from_uri(uri: &str) -> Result<Self> {
+        Self::new(LibsqlDriverOptions::from_uri(uri)?)
+    }
+
+    /// Checks that the server is reachable and the table can be read.
+    ///
+    /// # Returns
+    /// A `Result` which is an error if the server cannot be reached.
+    pub fn ping(&self) -> Result<()> {
+        self.execute(
+            &format!(
+                "SELECT COUNT(*) FROM (SELECT 1 FROM {} LIMIT 1)",
+                self.options.table_name
+            ),
+            &[],
+        )?;
+        Ok(())
+    }
+
+    /// Adds a value to an existing entry or creates a new entry if it doesn't exist.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry to update.
+    /// - `value`: The value to add to the current entry.
+    ///
+    /// # Returns
+    /// The new value after adding `value` to the existing entry.
+    pub fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.modify(key, |current| {
+            let new_value = number(current, self.options.lenient_numbers)? + value;
+            Ok((Some(Value::from(new_value)), new_value))
+        })
+    }
+
+    /// Retrieves all data entries from the table as a vector of tuples.
+    ///
+    /// # Returns
+    /// A `Result` containing a vector of key and value pairs.
+    pub fn all(&self) -> Result<Vec<(String, Value)>> {
+        let rows = self.execute(
+            &format!("SELECT ID, JSON FROM {}", self.options.table_name),
+            &[],
+        )?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| {
+                let id = text(&row[0]).unwrap_or_default();
+                let json = text(&row[1])
+                    .and_then(|json| from_str(&json).ok())
+                    .unwrap_or(Value::Null);
+                (id, json)
+            })
+            .collect())
+    }
+
+    /// Deletes a specific entry by key, or a nested field within an entry.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to delete.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if a value was deleted, or `false` if there
+    /// was no value at `key`.
+    pub fn delete(&self, key: &str) -> Result<bool> {
+        if !key.contains('.') {
+            let deleted = self.execute(
+                &format!(
+                    "DELETE FROM {} WHERE ID = ? RETURNING ID",
+                    self.options.table_name
+                ),
+                &[json!(key)],
+            )?;
+            return Ok(!deleted.is_empty());
+        }
+
+        self.modify(key, |current| Ok((None, current.is_some())))
+    }
+
+    /// Deletes several nested fields of the value at a key with a single write.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the value holding the fields, which may itself be nested.
+    /// - `paths`: The paths of the fields to delete, relative to `key`.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of fields deleted, or a `TypeMismatch`
+    /// error if a path leads through a value that is not an object.
+    pub fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        self.modify(key, |current| {
+            let mut value = current.cloned();
+            let mut deleted = 0;
+            if let Some(value) = value.as_mut() {
+                for path in paths {
+                    if remove_path(value, &path.split('.').collect::<Vec<_>>())? {
+                        deleted += 1;
+                    }
+                }
+            }
+            Ok((value, deleted))
+        })
+    }
+
+    /// Deletes all entries in the table.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the deletion was successful.
+    pub fn delete_all(&self) -> Result<bool> {
+        self.execute(&format!("DELETE FROM {}", self.options.table_name), &[])?;
+        Ok(true)
+    }
+
+    /// Retrieves the value for a given key, deserialising it into the specified type.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` of the deserialised value.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned + Default,
+    {
+        let (root, path) = split_key(key);
+        let rows = self.execute(
+            &format!("SELECT JSON FROM {} WHERE ID = ?", self.options.table_name),
+            &[json!(root)],
+        )?;
+
+        let value = rows
+            .first()
+            .and_then(|row| text(&row[0]))
+            .and_then(|json| from_str::<Value>(&json).ok())
+            .and_then(|root| lookup(&root, &path).cloned());
+
+        Ok(value.map(|value| serde_json::from_value(value).unwrap_or_default()))
+    }
+
+    /// Checks if a given key exists.
+    ///
+    /// # Parameters
+    /// - `key`: The key to check.
+    ///
+    /// # Returns
+    /// A `Result` containing a boolean indicating whether the key exists.
+    pub fn has(&self, key: &str) -> Result<bool> {
+        Ok(self.get::<Value>(key)?.is_some())
+    }
+
+    /// Removes a specific value from an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to remove from the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after removal.
+    pub fn pull<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + std::cmp::PartialEq + Clone + Serialize,
+    {
+        self.modify(key, |current| {
+            let mut arr: Vec<T> = current
+                .and_then(|current| serde_json::from_value(current.clone()).ok())
+                .unwrap_or_default();
+            arr.retain(|x| x != &value);
+            Ok((Some(serde_json::to_value(&arr)?), arr))
+        })
+    }
+
+    /// Appends a value to an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to append to the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after the value is appended.
+    pub fn push<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + Clone + Serialize,
+    {
+        self.modify(key, |current| {
+            let mut arr: Vec<T> = current
+                .and_then(|current| serde_json::from_value(current.clone()).ok())
+                .unwrap_or_default();
+            arr.push(value);
+            Ok((Some(serde_json::to_value(&arr)?), arr))
+        })
+    }
+
+    /// Sets the value for a given key and returns the value it replaced, with
+    /// the same guarantees against concurrent writers as `set`.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` containing the previous value, or `None` if the key didn't
+    /// exist, or a `Json` error if the previous value cannot be deserialised
+    /// into the type of `value`.
+    pub fn replace<T>(&self, key: &str, value: T) -> Result<Option<T>>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let value = serde_json::to_value(value)?;
+        let previous = self.modify(key, |current| Ok((Some(value.clone()), current.cloned())))?;
+        Ok(previous.map(serde_json::from_value).transpose()?)
+    }
+
+    /// Sets or updates the value for a given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the value was stored.
+    pub fn set<T>(&self, key: &str, value: T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        if !key.contains('.') {
+            self.execute(&self.upsert_sql(), &[json!(key), json!(value.to_string())])?;
+            return Ok(());
+        }
+
+        self.modify(key, |_| Ok((Some(value), ())))
+    }
+
+    /// Subtracts a value from an existing entry. If the entry does not exist,
+    /// it initialises it with the result.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to subtract from.
+    /// - `value`: The value to subtract from the current value.
+    ///
+    /// # Returns
+    /// The new value after subtraction.
+    pub fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.modify(key, |current| {
+            let new_value = number(current, self.options.lenient_numbers)? - value;
+            Ok((Some(Value::from(new_value)), new_value))
+        })
+    }
+
+    /// Replaces the value at a key within a transaction on the server.
+    ///
+    /// `update` receives the current value at the key and returns its new value,
+    /// `None` removing it, along with the result of the operation.
+    fn modify<T, F>(&self, key: &str, update: F) -> Result<T>
+    where
+        F: FnOnce(Option<&Value>) -> Result<(Option<Value>, T)>,
+    {
+        let (root, path) = split_key(key);
+        let mut stream = Stream {
+            url: self.options.url.trim_end_matches('/').to_string(),
+            baton: None,
+        };
+
+        let mut results = self.pipeline(
+            &mut stream,
+            vec![
+                statement("BEGIN IMMEDIATE", &[]),
+                statement(
+                    &format!("SELECT JSON FROM {} WHERE ID = ?", self.options.table_name),
+                    &[json!(root)],
+                ),
+            ],
+        )?;
+
+        let mut root_value = results
+            .pop()
+            .and_then(|rows| rows.into_iter().next())
+            .and_then(|row| text(&row[0]))
+            .and_then(|json| from_str::<Value>(&json).ok());
+
+        let outcome = update(root_value.as_ref().and_then(|root| lookup(root, &path))).and_then(
+            |(value, result)| {
+                replace(&mut root_value, &path, value)?;
+                Ok(result)
+            },
+        );
+
+        let write = match (&outcome, &root_value) {
+            (Err(_), _) => statement("ROLLBACK", &[]),
+            (Ok(_), Some(root_value)) => statement(
+                &self.upsert_sql(),
+                &[json!(root), json!(root_value.to_string())],
+            ),
+            (Ok(_), None) => statement(
+                &format!("DELETE FROM {} WHERE ID = ?", self.options.table_name),
+                &[json!(root)],
+            ),
+        };
+        let mut requests = vec![write];
+        if outcome.is_ok() {
+            requests.push(statement("COMMIT", &[]));
+        }
+        requests.push(json!({ "type": "close" }));
+
+        let closed = self.pipeline(&mut stream, requests);
+        let result = outcome?;
+        closed?;
+        Ok(result)
+    }
+
+    /// Returns the statement inserting or replacing a row of the table.
+    fn upsert_sql(&self) -> String {
+        format!(
+            "INSERT INTO {} (ID, JSON) VALUES (?1, ?2) ON CONFLICT(ID) DO UPDATE SET JSON = ?2",
+            self.options.table_name
+        )
+    }
+
+    /// Executes a single statement on a new stream and returns its rows.
+    fn execute(&self, sql: &str, args: &[Value]) -> Result<Vec<Vec<Value>>> {
+        let mut stream = Stream {
+            url: self.options.url.trim_end_matches('/').to_string(),
+            baton: None,
+        };
+        let mut results = self.pipeline(
+            &mut stream,
+            vec![statement(sql, args), json!({ "type": "close" })],
+        )?;
+        Ok(results.swap_remove(0))
+    }
+
+    /// Sends a pipeline of requests on a stream and returns the rows of every
+    /// statement executed, failing on the first request rejected by the server.
+    fn pipeline(&self, stream: &mut Stream, requests: Vec<Value>) -> Result<Vec<Vec<Vec<Value>>>> {
+        let mut request = self.agent.post(&format!("{}/v2/pipeline", stream.url));
+        if let Some(token) = &self.options.auth_token {
+            request = request.set("Authorization", &format!("Bearer {}", token));
+        }
+
+        let body = json!({ "baton": stream.baton, "requests": requests });
+        let response: Value = self
+            .options
+            .reconnect
+            .send(&request, Some(&body))
+            .map_err(|err| match err {
+                err @ ureq::Error::Status(..) => ChromoeError::Http(err.to_string()),
+                ureq::Error::Transport(transport) => transport_error(transport),
+            })?
+            .into_json()?;
+
+        stream.baton = response["baton"].as_str().map(String::from);
+        if let Some(base_url) = response["base_url"].as_str() {
+            stream.url = base_url.trim_end_matches('/').to_string();
+        }
+
+        let mut rows = Vec::new();
+        for result in response["results"].as_array().into_iter().flatten() {
+            if result["type"] == "error" {
+                return Err(ChromoeError::Http(format!(
+                    "libsql error: {}",
+                    result["error"]["message"]
+                        .as_str()
+                        .unwrap_or("unknown error")
+                )));
+            }
+
+            let response = &result["response"];
+            if response["type"] == "execute" {
+                rows.push(
+                    response["result"]["rows"]
+                        .as_array()
+                        .cloned()
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|row| match row {
+                            Value::Array(values) => values,
+                            _ => Vec::new(),
+                        })
+                        .collect(),
+                );
+            }
+        }
+        Ok(rows)
+    }
+}
+
+impl Driver for LibsqlDriver {
+    fn ping(&self) -> Result<()> {
+        LibsqlDriver::ping(self)
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        LibsqlDriver::all(self)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        LibsqlDriver::get(self, key)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        LibsqlDriver::set(self, key, value)
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        LibsqlDriver::has(self, key)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        LibsqlDriver::delete(self, key)
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        LibsqlDriver::delete_all(self)
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        LibsqlDriver::add(self, key, value)
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        LibsqlDriver::subtract(self, key, value)
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        LibsqlDriver::push(self, key, value)
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        LibsqlDriver::pull(self, key, value)
+    }
+
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        LibsqlDriver::replace(self, key, value)
+    }
+
+    fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        LibsqlDriver::delete_many_nested(self, key, paths)
+    }
+}
+
+/// Builds a Hrana `execute` request binding every argument as text.
+fn statement(sql: &str, args: &[Value]) -> Value {
+    let args: Vec<Value> = args
+        .iter()
+        .map(|arg| match arg {
+            Value::String(text) => json!({ "type": "text", "value": text }),
+            other => json!({ "type": "text", "value": other.to_string() }),
+        })
+        .collect();
+    json!({ "type": "execute", "stmt": { "sql": sql, "args": args } })
+}
+
+/// Returns the text of a Hrana value, or `None` for any other type.
+fn text(value: &Value) -> Option<String> {
+    match value["type"].as_str() {
+        Some("text") => value["value"].as_str().map(String::from),
+        _ => None,
+    }
+}
@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::driver::json_path::{lookup, remove_path, replace, split_key};
+use crate::driver::{number, Driver};
+use crate::error::{ChromoeError, Result};
+use crate::structure::KeyGenerator;
+
+/// An operation performed on a [`MockDriver`].
+///
+/// # Fields
+///
+/// - `operation`: The name of the driver method, such as `get` or `add`.
+/// - `key`: The key passed to the method, or `None` for methods without one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockOperation {
+    /// The name of the driver method.
+    pub operation: &'static str,
+    /// The key passed to the method.
+    pub key: Option<String>,
+}
+
+/// An in-memory driver for tests, whose responses can be scripted.
+///
+/// Without scripted responses, the driver behaves like any other driver,
+/// storing values in memory. Responses queued with
+/// [`MockDriver::push_response`] and errors queued with
+/// [`MockDriver::push_error`] are returned by the next calls of an operation
+/// instead, without touching the stored values, which makes error paths such
+/// as a busy database or a timeout easy to reach. Every call is recorded, so
+/// tests can assert on the operations performed.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::mock_driver::{self, MockDriver};
+/// use chromoe_db::driver::Driver;
+/// use chromoe_db::error::ChromoeError;
+/// use serde_json::json;
+///
+/// let driver = MockDriver::new();
+/// driver.set("user.coins", json!(100)).unwrap();
+///
+/// driver.push_error("add", mock_driver::timeout_error());
+/// driver.push_response("get", json!(5));
+///
+/// assert!(matches!(driver.add("user.coins", 50.0), Err(ChromoeError::Timeout(_))));
+/// assert_eq!(driver.get("user.coins").unwrap(), Some(json!(5)));
+/// assert_eq!(driver.get("user.coins").unwrap(), Some(json!(100)));
+///
+/// assert_eq!(driver.operation_names(), ["set", "add", "get", "get"]);
+/// ```
+#[derive(Debug, Default)]
+pub struct MockDriver {
+    data: Mutex<BTreeMap<String, Value>>,
+    responses: Mutex<HashMap<String, VecDeque<Result<Value>>>>,
+    operations: Mutex<Vec<MockOperation>>,
+}
+
+impl MockDriver {
+    /// Creates an empty mock driver.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a mock driver holding the given entries.
+    ///
+    /// # Parameters
+    /// - `entries`: The keys and values stored initially.
+    ///
+    /// # Returns
+    /// The `MockDriver` holding `entries`.
+    pub fn with_data<I, K>(entries: I) -> Self
+    where
+        I: IntoIterator<Item = (K, Value)>,
+        K: Into<String>,
+    {
+        let driver = Self::new();
+        lock(&driver.data).extend(entries.into_iter().map(|(key, value)| (key.into(), value)));
+        driver
+    }
+
+    /// Queues a response for the next call of an operation that has no other
+    /// response queued.
+    ///
+    /// The value is deserialised into the return type of the operation, so
+    /// `get` expects the value or `null`, `has` and `delete` a boolean, `add`
+    /// a number and `all` an array of key and value pairs. A value of another
+    /// shape is returned as a `Json` error.
+    ///
+    /// # Parameters
+    /// - `operation`: The name of the driver method, such as `get`.
+    /// - `value`: The value returned by the call.
+    pub fn push_response(&self, operation: &str, value: Value) {
+        self.push(operation, Ok(value));
+    }
+
+    /// Queues an error for the next call of an operation that has no other
+    /// response queued.
+    ///
+    /// # Parameters
+    /// - `operation`: The name of the driver method, such as `set`.
+    /// - `err`: The error returned by the call, such as [`timeout_error`].
+    pub fn push_error(&self, operation: &str, err: ChromoeError) {
+        self.push(operation, Err(err));
+    }
+
+    /// Returns the operations performed so far, in the order they were called.
+    pub fn operations(&self) -> Vec<MockOperation> {
+        lock(&self.operations).clone()
+    }
+
+    /// Returns the names of the operations performed so far, in the order
+    /// they were called.
+    pub fn operation_names(&self) -> Vec<&'static str> {
+        lock(&self.operations)
+            .iter()
+            .map(|operation| operation.operation)
+            .collect()
+    }
+
+    /// Returns the operations performed so far and clears the log.
+    pub fn take_operations(&self) -> Vec<MockOperation> {
+        std::mem::take(&mut *lock(&self.operations))
+    }
+
+    /// Queues a response for an operation.
+    fn push(&self, operation: &str, response: Result<Value>) {
+        lock(&self.responses)
+            .entry(operation.to_string())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Records a call, then returns its queued response, or runs it against
+    /// the stored values if there is none.
+    fn call<T, F>(&self, operation: &'static str, key: Option<&str>, run: F) -> Result<T>
+    where
+        T: DeserializeOwned,
+        F: FnOnce(&mut BTreeMap<String, Value>) -> Result<T>,
+    {
+        lock(&self.operations).push(MockOperation {
+            operation,
+            key: key.map(str::to_string),
+        });
+
+        let response = lock(&self.responses)
+            .get_mut(operation)
+            .and_then(VecDeque::pop_front);
+        match response {
+            Some(response) => Ok(serde_json::from_value(response?)?),
+            None => run(&mut lock(&self.data)),
+        }
+    }
+}
+
+impl Driver for MockDriver {
+    fn ping(&self) -> Result<()> {
+        self.call("ping", None, |_| Ok(()))
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        self.call("all", None, |data| {
+            Ok(data
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect())
+        })
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        self.call("get", Some(key), |data| Ok(read(data, key)))
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        self.call("set", Some(key), |data| write(data, key, Some(value)))
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        self.call("has", Some(key), |data| Ok(read(data, key).is_some()))
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        self.call("delete", Some(key), |data| {
+            let existed = read(data, key).is_some();
+            write(data, key, None)?;
+            Ok(existed)
+        })
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        self.call("delete_all", None, |data| {
+            data.clear();
+            Ok(true)
+        })
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.call("add", Some(key), |data| {
+            let total = number(read(data, key).as_ref(), false)? + value;
+            write(data, key, Some(json!(total)))?;
+            Ok(total)
+        })
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.call("subtract", Some(key), |data| {
+            let total = number(read(data, key).as_ref(), false)? - value;
+            write(data, key, Some(json!(total)))?;
+            Ok(total)
+        })
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        self.call("push", Some(key), |data| {
+            let mut values = array(data, key);
+            values.push(value);
+            write(data, key, Some(Value::Array(values.clone())))?;
+            Ok(values)
+        })
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        self.call("pull", Some(key), |data| {
+            let mut values = array(data, key);
+            values.retain(|existing| existing != &value);
+            write(data, key, Some(Value::Array(values.clone())))?;
+            Ok(values)
+        })
+    }
+
+    fn insert(&self, value: Value) -> Result<String> {
+        self.call("insert", None, |data| {
+            let key = KeyGenerator::UuidV4.generate();
+            data.insert(key.clone(), value);
+            Ok(key)
+        })
+    }
+
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        self.call("replace", Some(key), |data| {
+            let previous = read(data, key);
+            write(data, key, Some(value))?;
+            Ok(previous)
+        })
+    }
+
+    fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        self.call("delete_many_nested", Some(key), |data| {
+            let (root, path) = split_key(key);
+            let Some(mut value) = data
+                .get(root)
+                .and_then(|value| lookup(value, &path))
+                .cloned()
+            else {
+                return Ok(0);
+            };
+
+            let mut deleted = 0;
+            for field in paths {
+                let (first, rest) = split_key(field);
+                let field_path: Vec<&str> = std::iter::once(first).chain(rest).collect();
+                if remove_path(&mut value, &field_path)? {
+                    deleted += 1;
+                }
+            }
+            write(data, key, Some(value))?;
+            Ok(deleted)
+        })
+    }
+}
+
+/// Returns the error SQLite reports when the database is locked by another
+/// connection.
+#[cfg(feature = "sqlite")]
+pub fn busy_error() -> ChromoeError {
+    ChromoeError::Sqlite(rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+        Some("database is locked".to_string()),
+    ))
+}
+
+/// Returns the error reported when the value stored at a key is not valid JSON.
+///
+/// # Parameters
+/// - `key`: The key of the corrupted value.
+pub fn corrupted_error(key: &str) -> ChromoeError {
+    ChromoeError::Corrupted {
+        key: key.to_string(),
+        source: serde_json::from_str::<Value>("{").unwrap_err(),
+    }
+}
+
+/// Returns the error reported when an operation exceeds its timeout.
+pub fn timeout_error() -> ChromoeError {
+    ChromoeError::Timeout("mock operation timed out".to_string())
+}
+
+/// Locks a mutex, recovering its value if another thread panicked.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Reads the value stored at a possibly nested key.
+fn read(data: &BTreeMap<String, Value>, key: &str) -> Option<Value> {
+    let (root, path) = split_key(key);
+    data.get(root)
+        .and_then(|value| lookup(value, &path))
+        .cloned()
+}
+
+/// Writes the value stored at a possibly nested key, `None` deleting it.
+fn write(data: &mut BTreeMap<String, Value>, key: &str, value: Option<Value>) -> Result<()> {
+    let (root, path) = split_key(key);
+    let mut current = data.get(root).cloned();
+    replace(&mut current, &path, value)?;
+    match current {
+        Some(current) => data.insert(root.to_string(), current),
+        None => data.remove(root),
+    };
+    Ok(())
+}
+
+/// Reads the array stored at a key, treating anything else as empty.
+fn array(data: &BTreeMap<String, Value>, key: &str) -> Vec<Value> {
+    match read(data, key) {
+        Some(Value::Array(values)) => values,
+        _ => Vec::new(),
+    }
+}
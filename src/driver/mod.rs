@@ -1 +1,269 @@
-pub mod sqlite_driver;
\ No newline at end of file
+use serde_json::Value;
+
+use crate::error::{ChromoeError, Result};
+use crate::structure::{DataSet, JsonType, KeyGenerator};
+
+#[cfg(feature = "async")]
+pub mod blocking_driver;
+/// Encrypting the bundles of `SQLiteDriver::export_encrypted`.
+#[cfg(feature = "encryption")]
+pub(crate) mod bundle;
+#[cfg(feature = "couchdb")]
+pub mod couch_driver;
+#[cfg(feature = "d1")]
+pub mod d1_driver;
+pub mod dry_run_driver;
+#[cfg(feature = "etcd")]
+pub mod etcd_driver;
+pub mod failover_driver;
+#[cfg(feature = "http")]
+pub mod http_driver;
+/// Helpers for drivers that edit nested values of a row on the client. The
+/// SQLite driver only removes nested values this way, and shares the splitting
+/// of escaped keys with the HTTP driver.
+#[cfg(any(
+    feature = "sqlite",
+    feature = "http",
+    feature = "libsql",
+    feature = "d1",
+    feature = "rocksdb",
+    feature = "couchdb",
+    feature = "scylla",
+    feature = "etcd",
+    feature = "test-utils"
+))]
+#[cfg_attr(
+    not(any(
+        feature = "libsql",
+        feature = "d1",
+        feature = "rocksdb",
+        feature = "couchdb",
+        feature = "scylla",
+        feature = "etcd",
+        feature = "test-utils"
+    )),
+    allow(dead_code)
+)]
+pub(crate) mod json_path;
+#[cfg(feature = "libsql")]
+pub mod libsql_driver;
+#[cfg(feature = "test-utils")]
+pub mod mock_driver;
+/// Finding nested values within JSON text without parsing the values around them.
+#[cfg(feature = "sqlite")]
+pub(crate) mod raw_json;
+#[cfg(feature = "rocksdb")]
+pub mod rocks_driver;
+pub mod scoped_driver;
+#[cfg(feature = "scylla")]
+pub mod scylla_driver;
+#[cfg(feature = "sqlite")]
+pub mod sharded_sqlite_driver;
+/// Reading and writing the SQL dumps of `SQLiteDriver::export_sql`.
+#[cfg(feature = "sqlite")]
+pub(crate) mod sql_dump;
+/// Logging the statements run by a `SQLiteDriver` to its `sql_log` function.
+#[cfg(feature = "sqlite")]
+pub(crate) mod sql_log;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_driver;
+pub mod throttled_driver;
+
+/// The common interface implemented by every chromoe-db database driver.
+///
+/// `Driver` exposes the key-value operations shared by all backends in terms of
+/// `serde_json::Value`, which keeps the trait object-safe so applications can
+/// select a backend at runtime and hold it as a `Box<dyn Driver>`. Drivers
+/// usually also provide inherent, strongly-typed variants of these methods.
+///
+/// Keys may refer to nested values using dot notation, such as `world.money`.
+pub trait Driver {
+    /// Checks that the backend is reachable and responding.
+    fn ping(&self) -> Result<()>;
+
+    /// Retrieves all data entries as a vector of key and value tuples.
+    fn all(&self) -> Result<Vec<(String, Value)>>;
+
+    /// Retrieves the value stored at `key`, or `None` if it doesn't exist.
+    fn get(&self, key: &str) -> Result<Option<Value>>;
+
+    /// Sets or updates the value stored at `key`.
+    fn set(&self, key: &str, value: Value) -> Result<()>;
+
+    /// Checks if a value is stored at `key`.
+    fn has(&self, key: &str) -> Result<bool>;
+
+    /// Deletes the value stored at `key`, returning whether there was a value
+    /// to delete.
+    fn delete(&self, key: &str) -> Result<bool>;
+
+    /// Deletes all entries.
+    fn delete_all(&self) -> Result<bool>;
+
+    /// Adds `value` to the number stored at `key` and returns the new value.
+    /// A missing value counts as `0`, and a value that is not a number is a
+    /// `TypeMismatch` error, unless the driver's `lenient_numbers` option is set.
+    fn add(&self, key: &str, value: f64) -> Result<f64>;
+
+    /// Subtracts `value` from the number stored at `key` and returns the new value.
+    fn subtract(&self, key: &str, value: f64) -> Result<f64>;
+
+    /// Appends `value` to the array stored at `key` and returns the updated array.
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>>;
+
+    /// Removes every occurrence of `value` from the array stored at `key` and
+    /// returns the updated array.
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>>;
+
+    /// Stores `value` under a newly generated key and returns the key.
+    ///
+    /// The default implementation generates a random UUID with
+    /// [`KeyGenerator::UuidV4`].
+    fn insert(&self, value: Value) -> Result<String> {
+        let key = KeyGenerator::UuidV4.generate();
+        self.set(&key, value)?;
+        Ok(key)
+    }
+
+    /// Sets the value stored at `key` and returns the value it replaced, or
+    /// `None` if it didn't exist.
+    ///
+    /// The default implementation is a `get` followed by a `set`, so another
+    /// writer may change the value in between. Drivers override it to swap the
+    /// value atomically where the backend allows it.
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        let previous = self.get(key)?;
+        self.set(key, value)?;
+        Ok(previous)
+    }
+
+    /// Deletes several nested fields of the value stored at `key`, given as
+    /// paths relative to it, and returns the number of fields deleted.
+    ///
+    /// The default implementation deletes the fields one at a time. Drivers
+    /// override it to delete them with a single write.
+    fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        let mut deleted = 0;
+        for path in paths {
+            if self.delete(&format!("{}.{}", key, path))? {
+                deleted += 1;
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Retrieves the value stored at `key` along with the metadata the driver
+    /// tracks for it, or `None` if it doesn't exist.
+    ///
+    /// The default implementation is built on `get` and carries no metadata.
+    fn entry(&self, key: &str) -> Result<Option<DataSet>> {
+        Ok(self.get(key)?.map(|value| DataSet::new(key, value)))
+    }
+
+    /// Retrieves all data entries along with the metadata the driver tracks for
+    /// them.
+    ///
+    /// The default implementation is built on `all` and carries no metadata.
+    fn entries(&self) -> Result<Vec<DataSet>> {
+        Ok(self
+            .all()?
+            .into_iter()
+            .map(|(id, value)| DataSet::new(id, value))
+            .collect())
+    }
+
+    /// Splits a key the way the driver does into the row key, followed by the
+    /// path of the nested value within it.
+    ///
+    /// The default implementation splits the key at every dot.
+    fn split_key(&self, key: &str) -> Vec<String> {
+        key.split('.').map(String::from).collect()
+    }
+
+    /// Builds the key the driver splits into a path, the reverse of
+    /// `split_key`, or `None` if a part of the path can't be written in a key.
+    ///
+    /// The default implementation joins the parts with dots, so parts holding
+    /// a dot can't be written.
+    fn join_key(&self, path: &[String]) -> Option<String> {
+        if path.iter().any(|part| part.contains('.')) {
+            return None;
+        }
+        Some(path.join("."))
+    }
+}
+
+/// The asynchronous counterpart of [`Driver`], for applications built on an
+/// async runtime.
+///
+/// `AsyncDriver` exposes the same operations as `Driver` and is object-safe, so
+/// asynchronous backends can be held as a `Box<dyn AsyncDriver>`. Natively
+/// asynchronous drivers, such as the `D1Driver`, implement it directly, while
+/// every blocking driver can be used through the
+/// [`BlockingDriver`](blocking_driver::BlockingDriver) adapter, which runs its
+/// operations on Tokio's blocking thread pool.
+///
+/// The futures returned are `Send`, except on `wasm32` targets, which are
+/// single-threaded.
+#[cfg(any(feature = "async", feature = "d1"))]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+pub trait AsyncDriver {
+    /// Checks that the backend is reachable and responding.
+    async fn ping(&self) -> Result<()>;
+
+    /// Retrieves all data entries as a vector of key and value tuples.
+    async fn all(&self) -> Result<Vec<(String, Value)>>;
+
+    /// Retrieves the value stored at `key`, or `None` if it doesn't exist.
+    async fn get(&self, key: &str) -> Result<Option<Value>>;
+
+    /// Sets or updates the value stored at `key`.
+    async fn set(&self, key: &str, value: Value) -> Result<()>;
+
+    /// Checks if a value is stored at `key`.
+    async fn has(&self, key: &str) -> Result<bool>;
+
+    /// Deletes the value stored at `key`.
+    async fn delete(&self, key: &str) -> Result<bool>;
+
+    /// Deletes all entries.
+    async fn delete_all(&self) -> Result<bool>;
+
+    /// Adds `value` to the number stored at `key` and returns the new value.
+    async fn add(&self, key: &str, value: f64) -> Result<f64>;
+
+    /// Subtracts `value` from the number stored at `key` and returns the new value.
+    async fn subtract(&self, key: &str, value: f64) -> Result<f64>;
+
+    /// Appends `value` to the array stored at `key` and returns the updated array.
+    async fn push(&self, key: &str, value: Value) -> Result<Vec<Value>>;
+
+    /// Removes every occurrence of `value` from the array stored at `key` and
+    /// returns the updated array.
+    async fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>>;
+}
+
+/// Reads the number that `add` and `subtract` start from, treating a missing
+/// value as zero.
+///
+/// # Parameters
+/// - `current`: The value stored at the key, if any.
+/// - `lenient`: Whether a value that is not a number counts as zero.
+///
+/// # Returns
+/// A `Result` containing the number, or a `TypeMismatch` error if the value is
+/// not a number and `lenient` is false.
+pub(crate) fn number(current: Option<&Value>, lenient: bool) -> Result<f64> {
+    match current {
+        Some(value) => match value.as_f64() {
+            Some(number) => Ok(number),
+            None if lenient => Ok(0.0),
+            None => Err(ChromoeError::TypeMismatch {
+                expected: "f64".to_string(),
+                found: JsonType::of(value),
+            }),
+        },
+        None => Ok(0.0),
+    }
+}
@@ -0,0 +1,79 @@
+use std::fmt;
+
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde_json::value::RawValue;
+
+/// Returns the JSON text of the nested value at a path within a row's JSON
+/// text, as `lookup` does, without building the values around it.
+pub(crate) fn lookup_raw<'a>(root: &'a RawValue, path: &[&str]) -> Option<&'a RawValue> {
+    path.iter().try_fold(root, |value, part| {
+        let mut deserializer = serde_json::Deserializer::from_str(value.get());
+        Field(part).deserialize(&mut deserializer).ok().flatten()
+    })
+}
+
+/// Finds the value of a field of a JSON object, skipping the other fields.
+/// Like `Value::get`, the last of duplicate fields wins, and values that are
+/// not objects have no fields.
+struct Field<'k>(&'k str);
+
+impl<'de> DeserializeSeed<'de> for Field<'_> {
+    type Value = Option<&'de RawValue>;
+
+    fn deserialize<D>(self, deserializer: D) -> std::result::Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Field<'_> {
+    type Value = Option<&'de RawValue>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a JSON object")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut found = None;
+        while let Some(matches) = map.next_key_seed(FieldName(self.0))? {
+            if matches {
+                found = Some(map.next_value()?);
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(found)
+    }
+}
+
+/// Compares the name of a field of a JSON object with a name, without
+/// allocating it.
+struct FieldName<'k>(&'k str);
+
+impl<'de> DeserializeSeed<'de> for FieldName<'_> {
+    type Value = bool;
+
+    fn deserialize<D>(self, deserializer: D) -> std::result::Result<bool, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl Visitor<'_> for FieldName<'_> {
+    type Value = bool;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a field name")
+    }
+
+    fn visit_str<E>(self, name: &str) -> std::result::Result<bool, E> {
+        Ok(name == self.0)
+    }
+}
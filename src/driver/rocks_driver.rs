@@ -0,0 +1,445 @@
+use std::sync::Mutex;
+
+use rocksdb::{ColumnFamily, Options, WriteBatch, DB};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::driver::json_path::{lookup, remove_path, replace, split_key};
+use crate::driver::{number, Driver};
+use crate::error::{ChromoeError, Result};
+use crate::structure::RocksDriverOptions;
+use crate::uri::is_valid_identifier;
+
+/// Driver storing JSON data in a RocksDB database, suited to write-heavy workloads.
+///
+/// Each table is a column family of the database, holding one entry per key
+/// with the value serialised as JSON. Tables sharing a database are written to
+/// the same write-ahead log, and unlike the SQLite drivers, writers never wait
+/// for each other to commit. Read-modify-write operations, such as `add` and
+/// writes to nested keys, are serialised within the driver, so they are atomic
+/// as long as a single process opens the database, which RocksDB enforces.
+///
+/// # Fields
+///
+/// - `options`: Configuration options for the RocksDB driver.
+///
+/// # Example Usage
+///
+/// ```rust,no_run
+/// use chromoe_db::driver::rocks_driver::RocksDriver;
+///
+/// let driver = RocksDriver::from_uri("rocksdb://data/json.rocksdb?table=economy").unwrap();
+///
+/// driver.set("user_1", serde_json::json!({ "coins": 100 })).unwrap();
+/// assert_eq!(driver.add("user_1.coins", 50.0).unwrap(), 150.0);
+/// assert_eq!(driver.starts_with("user_").unwrap().len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct RocksDriver {
+    /// Configuration options for the RocksDB driver.
+    pub options: RocksDriverOptions,
+    db: DB,
+    write_lock: Mutex<()>,
+}
+
+impl RocksDriver {
+    /// Creates a new instance of the `RocksDriver`, creating the database and
+    /// the table's column family if they don't already exist.
+    ///
+    /// # Parameters
+    /// - `options`: Configuration options for the RocksDB driver.
+    ///
+    /// # Returns
+    /// A `Result` containing either the `RocksDriver` instance or an error if
+    /// the table name is invalid or the database cannot be opened.
+    pub fn new(options: RocksDriverOptions) -> Result<Self> {
+        if !is_valid_identifier(&options.table_name) {
+            return Err(ChromoeError::Config(format!(
+                "`{}` is not a valid table name",
+                options.table_name
+            )));
+        }
+
+        let mut db_options = Options::default();
+        db_options.create_if_missing(true);
+        db_options.create_missing_column_families(true);
+
+        // Every existing column family must be opened along with the table's own.
+        let mut column_families = DB::list_cf(&db_options, &options.path).unwrap_or_default();
+        if !column_families.contains(&options.table_name) {
+            column_families.push(options.table_name.clone());
+        }
+
+        let db = DB::open_cf(&db_options, &options.path, column_families)?;
+        Ok(RocksDriver {
+            options,
+            db,
+            write_lock: Mutex::new(()),
+        })
+    }
+
+    /// Creates a new instance of the `RocksDriver` from a connection string.
+    ///
+    /// # Parameters
+    /// - `uri`: A connection string as accepted by [`RocksDriverOptions::from_uri`].
+    ///
+    /// # Returns
+    /// A `Result` containing the `RocksDriver` instance, or an error if the
+    /// connection string is invalid or the database cannot be opened.
+    pub fn from_uri(uri: &str) -> Result<Self> {
+        Self::new(RocksDriverOptions::from_uri(uri)?)
+    }
+
+    /// Checks that the database is open and the table can be read.
+    ///
+    /// # Returns
+    /// A `Result` which is an error if the table cannot be read.
+    pub fn ping(&self) -> Result<()> {
+        self.db
+            .iterator_cf(self.table(), rocksdb::IteratorMode::Start)
+            .next()
+            .transpose()?;
+        Ok(())
+    }
+
+    /// Adds a value to an existing entry or creates a new entry if it doesn't exist.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry to update.
+    /// - `value`: The value to add to the current entry.
+    ///
+    /// # Returns
+    /// The new value after adding `value` to the existing entry.
+    pub fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.modify(key, |current| {
+            let new_value = number(current, self.options.lenient_numbers)? + value;
+            Ok((Some(Value::from(new_value)), new_value))
+        })
+    }
+
+    /// Retrieves all data entries from the table as a vector of tuples.
+    ///
+    /// # Returns
+    /// A `Result` containing a vector of key and value pairs, in key order.
+    pub fn all(&self) -> Result<Vec<(String, Value)>> {
+        self.starts_with("")
+    }
+
+    /// Retrieves the entries whose key starts with a prefix, using a prefix seek
+    /// so only the matching range of the table is read.
+    ///
+    /// # Parameters
+    /// - `prefix`: The prefix keys must start with, such as `user_`.
+    ///
+    /// # Returns
+    /// A `Result` containing the matching key and value pairs, in key order.
+    pub fn starts_with(&self, prefix: &str) -> Result<Vec<(String, Value)>> {
+        let mut entries = Vec::new();
+        for entry in self.db.prefix_iterator_cf(self.table(), prefix) {
+            let (key, value) = entry?;
+            // Without a prefix extractor, the seek is not bounded to the prefix.
+            if !key.starts_with(prefix.as_bytes()) {
+                break;
+            }
+            entries.push((
+                String::from_utf8_lossy(&key).into_owned(),
+                serde_json::from_slice(&value).unwrap_or(Value::Null),
+            ));
+        }
+        Ok(entries)
+    }
+
+    /// Deletes a specific entry by key, or a nested field within an entry.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to delete.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if a value was deleted, or `false` if there
+    /// was no value at `key`.
+    pub fn delete(&self, key: &str) -> Result<bool> {
+        if !key.contains('.') {
+            let _guard = self.lock();
+            let existed = self.db.get_pinned_cf(self.table(), key)?.is_some();
+            self.db.delete_cf(self.table(), key)?;
+            return Ok(existed);
+        }
+
+        self.modify(key, |current| Ok((None, current.is_some())))
+    }
+
+    /// Deletes several nested fields of the value at a key with a single write.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the value holding the fields, which may itself be nested.
+    /// - `paths`: The paths of the fields to delete, relative to `key`.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of fields deleted, or a `TypeMismatch`
+    /// error if a path leads through a value that is not an object.
+    pub fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        self.modify(key, |current| {
+            let mut value = current.cloned();
+            let mut deleted = 0;
+            if let Some(value) = value.as_mut() {
+                for path in paths {
+                    if remove_path(value, &path.split('.').collect::<Vec<_>>())? {
+                        deleted += 1;
+                    }
+                }
+            }
+            Ok((value, deleted))
+        })
+    }
+
+    /// Deletes all entries in the table.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the deletion was successful.
+    pub fn delete_all(&self) -> Result<bool> {
+        let _guard = self.lock();
+        let mut batch = WriteBatch::default();
+        for entry in self
+            .db
+            .iterator_cf(self.table(), rocksdb::IteratorMode::Start)
+        {
+            let (key, _) = entry?;
+            batch.delete_cf(self.table(), key);
+        }
+        self.db.write(batch)?;
+        Ok(true)
+    }
+
+    /// Retrieves the value for a given key, deserialising it into the specified type.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` of the deserialised value.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned + Default,
+    {
+        let (root, path) = split_key(key);
+        let value = self
+            .read(root)?
+            .and_then(|root| lookup(&root, &path).cloned());
+
+        Ok(value.map(|value| serde_json::from_value(value).unwrap_or_default()))
+    }
+
+    /// Checks if a given key exists.
+    ///
+    /// # Parameters
+    /// - `key`: The key to check.
+    ///
+    /// # Returns
+    /// A `Result` containing a boolean indicating whether the key exists.
+    pub fn has(&self, key: &str) -> Result<bool> {
+        Ok(self.get::<Value>(key)?.is_some())
+    }
+
+    /// Removes a specific value from an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to remove from the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after removal.
+    pub fn pull<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + std::cmp::PartialEq + Clone + Serialize,
+    {
+        self.modify(key, |current| {
+            let mut arr: Vec<T> = current
+                .and_then(|current| serde_json::from_value(current.clone()).ok())
+                .unwrap_or_default();
+            arr.retain(|x| x != &value);
+            Ok((Some(serde_json::to_value(&arr)?), arr))
+        })
+    }
+
+    /// Appends a value to an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to append to the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after the value is appended.
+    pub fn push<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + Clone + Serialize,
+    {
+        self.modify(key, |current| {
+            let mut arr: Vec<T> = current
+                .and_then(|current| serde_json::from_value(current.clone()).ok())
+                .unwrap_or_default();
+            arr.push(value);
+            Ok((Some(serde_json::to_value(&arr)?), arr))
+        })
+    }
+
+    /// Sets the value for a given key and returns the value it replaced, with
+    /// the same guarantees against concurrent writers as `set`.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` containing the previous value, or `None` if the key didn't
+    /// exist, or a `Json` error if the previous value cannot be deserialised
+    /// into the type of `value`.
+    pub fn replace<T>(&self, key: &str, value: T) -> Result<Option<T>>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let value = serde_json::to_value(value)?;
+        let previous = self.modify(key, |current| Ok((Some(value.clone()), current.cloned())))?;
+        Ok(previous.map(serde_json::from_value).transpose()?)
+    }
+
+    /// Sets or updates the value for a given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the value was stored.
+    pub fn set<T>(&self, key: &str, value: T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        if !key.contains('.') {
+            let _guard = self.lock();
+            self.db
+                .put_cf(self.table(), key, serde_json::to_vec(&value)?)?;
+            return Ok(());
+        }
+
+        self.modify(key, |_| Ok((Some(value), ())))
+    }
+
+    /// Subtracts a value from an existing entry. If the entry does not exist,
+    /// it initialises it with the result.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to subtract from.
+    /// - `value`: The value to subtract from the current value.
+    ///
+    /// # Returns
+    /// The new value after subtraction.
+    pub fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.modify(key, |current| {
+            let new_value = number(current, self.options.lenient_numbers)? - value;
+            Ok((Some(Value::from(new_value)), new_value))
+        })
+    }
+
+    /// Replaces the value at a key while holding the driver's write lock.
+    ///
+    /// `update` receives the current value at the key and returns its new value,
+    /// `None` removing it, along with the result of the operation.
+    fn modify<T, F>(&self, key: &str, update: F) -> Result<T>
+    where
+        F: FnOnce(Option<&Value>) -> Result<(Option<Value>, T)>,
+    {
+        let (root, path) = split_key(key);
+        let _guard = self.lock();
+
+        let mut root_value = self.read(root)?;
+        let (value, result) = update(root_value.as_ref().and_then(|root| lookup(root, &path)))?;
+        replace(&mut root_value, &path, value)?;
+
+        match root_value {
+            Some(root_value) => {
+                self.db
+                    .put_cf(self.table(), root, serde_json::to_vec(&root_value)?)?
+            }
+            None => self.db.delete_cf(self.table(), root)?,
+        }
+        Ok(result)
+    }
+
+    /// Reads and deserialises the value stored for a row key.
+    fn read(&self, key: &str) -> Result<Option<Value>> {
+        Ok(self
+            .db
+            .get_pinned_cf(self.table(), key)?
+            .and_then(|value| serde_json::from_slice(&value).ok()))
+    }
+
+    /// Returns the column family holding the table, which is opened with the database.
+    fn table(&self) -> &ColumnFamily {
+        self.db
+            .cf_handle(&self.options.table_name)
+            .expect("the table's column family is opened with the database")
+    }
+
+    /// Acquires the write lock, which stays usable if a writer panicked.
+    fn lock(&self) -> std::sync::MutexGuard<'_, ()> {
+        self.write_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Driver for RocksDriver {
+    fn ping(&self) -> Result<()> {
+        RocksDriver::ping(self)
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        RocksDriver::all(self)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        RocksDriver::get(self, key)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        RocksDriver::set(self, key, value)
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        RocksDriver::has(self, key)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        RocksDriver::delete(self, key)
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        RocksDriver::delete_all(self)
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        RocksDriver::add(self, key, value)
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        RocksDriver::subtract(self, key, value)
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        RocksDriver::push(self, key, value)
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        RocksDriver::pull(self, key, value)
+    }
+
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        RocksDriver::replace(self, key, value)
+    }
+
+    fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        RocksDriver::delete_many_nested(self, key, paths)
+    }
+}
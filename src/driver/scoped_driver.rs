@@ -0,0 +1,500 @@
+use serde_json::{Map, Value};
+
+use crate::driver::Driver;
+use crate::error::{ChromoeError, Result};
+use crate::structure::DataSet;
+
+/// What a [`ScopedDriver`] allows on the keys of a prefix.
+///
+/// # Variants
+///
+/// - `Denied`: The keys can be neither read nor written.
+/// - `ReadOnly`: The keys can be read, but not written.
+/// - `ReadWrite`: The keys can be read and written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Permission {
+    /// Neither reading nor writing is allowed.
+    #[default]
+    Denied,
+    /// Reading is allowed.
+    ReadOnly,
+    /// Reading and writing are allowed.
+    ReadWrite,
+}
+
+impl Permission {
+    /// Returns whether the keys can be read.
+    pub fn can_read(&self) -> bool {
+        matches!(self, Permission::ReadOnly | Permission::ReadWrite)
+    }
+
+    /// Returns whether the keys can be written.
+    pub fn can_write(&self) -> bool {
+        *self == Permission::ReadWrite
+    }
+}
+
+/// A driver wrapper restricting the keys that can be read and written, so
+/// code such as a plugin of a bot can be handed a restricted view of the
+/// store.
+///
+/// The permission of a key is the one of the longest prefix it starts with,
+/// or the default permission, which denies every key unless it is set with
+/// [`ScopedDriver::with_default`]. Keys and prefixes are split into their
+/// parts the way the wrapped driver splits nested keys, with its escapes and
+/// path separator, and compared part by part. A prefix such as `config`
+/// covers the key `config` and the values nested in it, but not
+/// `configuration`, while a prefix ending with the separator, such as
+/// `music.`, only covers the values nested in `music`. The permission of a
+/// key covers the values nested in it that no longer prefix gives another
+/// permission.
+///
+/// Writing or deleting a key is only allowed when every value nested in it
+/// can be written, and reading a key returns its value without the nested
+/// values that can't be read. A key is only denied to reads when neither it
+/// nor any of its nested values can be read.
+///
+/// `all` and `entries` return the parts of the entries the scope can read,
+/// and `delete_all` deletes the parts it can write. `insert` generates a new
+/// key, and is only allowed when the default permission is `ReadWrite`.
+/// Operations that aren't allowed fail with a `PermissionDenied` error.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::scoped_driver::{Permission, ScopedDriver};
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::driver::Driver;
+/// use chromoe_db::error::ChromoeError;
+/// use serde_json::json;
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// driver.set("config", json!({ "prefix": "!" })).unwrap();
+/// driver.set("tokens", json!({ "discord": "secret" })).unwrap();
+///
+/// let plugin = ScopedDriver::new(driver)
+///     .allow("plugins.music.", Permission::ReadWrite)
+///     .allow("config", Permission::ReadOnly);
+///
+/// plugin.set("plugins.music.volume", json!(80)).unwrap();
+/// assert_eq!(plugin.get("config.prefix").unwrap(), Some(json!("!")));
+/// assert!(matches!(plugin.set("config.prefix", json!("?")), Err(ChromoeError::PermissionDenied(_))));
+/// assert!(matches!(plugin.get("tokens.discord"), Err(ChromoeError::PermissionDenied(_))));
+///
+/// // Reading an ancestor of the prefix returns only what the plugin can read.
+/// assert_eq!(plugin.get("plugins").unwrap(), Some(json!({ "music": { "volume": 80 } })));
+/// assert_eq!(plugin.all().unwrap().len(), 2);
+/// assert!(plugin.all().unwrap().contains(&("plugins".to_string(), json!({ "music": { "volume": 80 } }))));
+/// ```
+///
+/// Values nested in a readable and writable key keep their own permissions:
+///
+/// ```rust
+/// use chromoe_db::driver::scoped_driver::{Permission, ScopedDriver};
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::driver::Driver;
+/// use chromoe_db::error::ChromoeError;
+/// use serde_json::json;
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// driver.set("bot", json!({ "name": "chromoe", "token": "secret", "config": { "prefix": "!" } })).unwrap();
+///
+/// let plugin = ScopedDriver::new(driver)
+///     .with_default(Permission::ReadWrite)
+///     .allow("bot.token", Permission::Denied)
+///     .allow("bot.config.", Permission::ReadOnly);
+///
+/// let visible = json!({ "name": "chromoe", "config": { "prefix": "!" } });
+/// assert_eq!(plugin.get("bot").unwrap(), Some(visible.clone()));
+/// assert_eq!(plugin.all().unwrap(), vec![("bot".to_string(), visible)]);
+///
+/// // Writing or deleting `bot` would also change the token and the configuration.
+/// assert!(matches!(plugin.set("bot", json!({})), Err(ChromoeError::PermissionDenied(_))));
+/// assert!(matches!(plugin.set("bot.config", json!({})), Err(ChromoeError::PermissionDenied(_))));
+/// assert!(matches!(plugin.delete("bot"), Err(ChromoeError::PermissionDenied(_))));
+/// assert!(matches!(
+///     plugin.delete_many_nested("bot", &["name", "token"]),
+///     Err(ChromoeError::PermissionDenied(_))
+/// ));
+///
+/// // Deleting everything leaves what the plugin can't write.
+/// plugin.delete_all().unwrap();
+/// assert_eq!(
+///     plugin.inner().get::<serde_json::Value>("bot").unwrap(),
+///     Some(json!({ "token": "secret", "config": { "prefix": "!" } }))
+/// );
+/// ```
+///
+/// Escaped keys and custom path separators refer to the same values as for the
+/// wrapped driver:
+///
+/// ```rust
+/// use chromoe_db::driver::scoped_driver::{Permission, ScopedDriver};
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::driver::Driver;
+/// use chromoe_db::error::ChromoeError;
+/// use chromoe_db::structure::SQLiteDriverOptions;
+/// use serde_json::json;
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// driver.set("bot", json!({ "name": "chromoe", "token": "secret" })).unwrap();
+///
+/// let plugin = ScopedDriver::new(driver)
+///     .with_default(Permission::ReadWrite)
+///     .allow("bot.token", Permission::Denied);
+///
+/// // `to\ken` is the part `token`, so it is denied as well.
+/// assert!(matches!(plugin.get("bot.to\\ken"), Err(ChromoeError::PermissionDenied(_))));
+/// assert_eq!(plugin.get("bot.name").unwrap(), Some(json!("chromoe")));
+///
+/// let driver = SQLiteDriver::new(Some(SQLiteDriverOptions {
+///     file_name: ":memory:".to_string(),
+///     path_separator: '/',
+///     ..Default::default()
+/// }))
+/// .unwrap();
+/// driver.set("bot/token", json!("secret")).unwrap();
+///
+/// let plugin = ScopedDriver::new(driver)
+///     .with_default(Permission::ReadWrite)
+///     .allow("bot/token", Permission::Denied);
+///
+/// assert!(matches!(plugin.get("bot/token"), Err(ChromoeError::PermissionDenied(_))));
+/// assert_eq!(plugin.get("bot").unwrap(), Some(json!({})));
+/// ```
+#[derive(Debug)]
+pub struct ScopedDriver<D> {
+    driver: D,
+    /// The parts of each prefix, ending with an empty part for a prefix ending
+    /// with the separator.
+    rules: Vec<(Vec<String>, Permission)>,
+    default: Permission,
+}
+
+impl<D> ScopedDriver<D>
+where
+    D: Driver,
+{
+    /// Wraps a driver, denying every key until permissions are set.
+    ///
+    /// # Parameters
+    /// - `driver`: The driver to restrict.
+    ///
+    /// # Returns
+    /// The `ScopedDriver` wrapping `driver`.
+    pub fn new(driver: D) -> Self {
+        ScopedDriver {
+            driver,
+            rules: Vec::new(),
+            default: Permission::Denied,
+        }
+    }
+
+    /// Sets the permission of the keys starting with a prefix, replacing the
+    /// one set for the same prefix.
+    ///
+    /// # Parameters
+    /// - `prefix`: The prefix of the keys, such as `plugins.music.`.
+    /// - `permission`: What is allowed on the keys.
+    ///
+    /// # Returns
+    /// The `ScopedDriver` with the permission set.
+    pub fn allow(mut self, prefix: &str, permission: Permission) -> Self {
+        let prefix = self.driver.split_key(prefix);
+        self.rules.retain(|(existing, _)| *existing != prefix);
+        self.rules.push((prefix, permission));
+        self
+    }
+
+    /// Sets the permission of the keys that start with none of the prefixes.
+    ///
+    /// # Parameters
+    /// - `permission`: What is allowed on the keys.
+    ///
+    /// # Returns
+    /// The `ScopedDriver` with the default permission set.
+    pub fn with_default(mut self, permission: Permission) -> Self {
+        self.default = permission;
+        self
+    }
+
+    /// Returns the permission of a key.
+    pub fn permission(&self, key: &str) -> Permission {
+        self.permission_of(&self.driver.split_key(key))
+    }
+
+    /// Returns the wrapped driver, which is not restricted.
+    pub fn inner(&self) -> &D {
+        &self.driver
+    }
+
+    /// Consumes the wrapper, returning the wrapped driver.
+    pub fn into_inner(self) -> D {
+        self.driver
+    }
+
+    /// Returns the permission of the value at a path, given by the longest
+    /// prefix covering it. A prefix ending with the separator is shorter than
+    /// a prefix of as many parts naming a value.
+    fn permission_of(&self, path: &[String]) -> Permission {
+        self.rules
+            .iter()
+            .filter(|(prefix, _)| covers(prefix, path))
+            .max_by_key(|(prefix, _)| {
+                (
+                    prefix.len(),
+                    prefix.last().is_some_and(|part| !part.is_empty()),
+                )
+            })
+            .map_or(self.default, |(_, permission)| *permission)
+    }
+
+    /// Returns the rules of the values nested in a path, such as the rule of
+    /// `plugins.music.` for `plugins`.
+    fn rules_below<'a>(&'a self, path: &'a [String]) -> impl Iterator<Item = Permission> + 'a {
+        self.rules
+            .iter()
+            .filter(move |(prefix, _)| prefix.len() > path.len() && prefix.starts_with(path))
+            .map(|(_, permission)| *permission)
+    }
+
+    /// Returns whether the value at a path and every value nested in it can be
+    /// written.
+    fn can_write(&self, path: &[String]) -> bool {
+        self.permission_of(path).can_write() && self.rules_below(path).all(|rule| rule.can_write())
+    }
+
+    /// Returns the part of the value at a path that can be read, or `None` if
+    /// none of it can.
+    fn visible(&self, path: &[String], value: Value) -> Option<Value> {
+        let readable = self.permission_of(path).can_read();
+        if self
+            .rules_below(path)
+            .all(|rule| rule.can_read() == readable)
+        {
+            return readable.then_some(value);
+        }
+
+        match value {
+            Value::Object(object) => {
+                let fields: Map<String, Value> = object
+                    .into_iter()
+                    .filter_map(|(field, value)| {
+                        self.visible(&child(path, &field), value)
+                            .map(|value| (field, value))
+                    })
+                    .collect();
+                (readable || !fields.is_empty()).then_some(Value::Object(fields))
+            }
+            value => readable.then_some(value),
+        }
+    }
+
+    /// Returns an entry with the part of its value that can be read, or `None`
+    /// if none of it can.
+    fn visible_entry(&self, mut entry: DataSet) -> Option<DataSet> {
+        entry.value = self.visible(&[entry.id.clone()], entry.value)?;
+        Some(entry)
+    }
+
+    /// Deletes the parts of the value at a path that can be written. Parts
+    /// the wrapped driver has no key for are left in place.
+    ///
+    /// # Returns
+    /// A `Result` containing whether anything was deleted.
+    fn delete_writable(&self, path: &[String], value: Value) -> Result<bool> {
+        if self.can_write(path) {
+            return match self.driver.join_key(path) {
+                Some(key) => self.driver.delete(&key),
+                None => Ok(false),
+            };
+        }
+
+        let mut deleted = false;
+        if let Value::Object(object) = value {
+            if self.permission_of(path).can_write()
+                || self.rules_below(path).any(|rule| rule.can_write())
+            {
+                for (field, value) in object {
+                    deleted |= self.delete_writable(&child(path, &field), value)?;
+                }
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Checks that a key, or a value nested in it, can be read.
+    ///
+    /// # Returns
+    /// A `Result` containing the path of the key.
+    fn check_read(&self, key: &str) -> Result<Vec<String>> {
+        let path = self.driver.split_key(key);
+        if !self.permission_of(&path).can_read()
+            && !self.rules_below(&path).any(|rule| rule.can_read())
+        {
+            return Err(ChromoeError::PermissionDenied(format!(
+                "`{}` cannot be read",
+                key
+            )));
+        }
+        Ok(path)
+    }
+
+    /// Checks that a key and every value nested in it can be written.
+    fn check_write(&self, key: &str) -> Result<()> {
+        self.check_write_path(&self.driver.split_key(key), key)
+    }
+
+    /// Checks that the value at a path and every value nested in it can be
+    /// written, naming it `key` in the error.
+    fn check_write_path(&self, path: &[String], key: &str) -> Result<()> {
+        if !self.can_write(path) {
+            return Err(ChromoeError::PermissionDenied(format!(
+                "`{}` cannot be written",
+                key
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Returns whether a prefix covers the value at a path.
+fn covers(prefix: &[String], path: &[String]) -> bool {
+    match prefix.split_last() {
+        Some((last, parents)) if last.is_empty() => {
+            path.len() > parents.len() && path.starts_with(parents)
+        }
+        _ => path.starts_with(prefix),
+    }
+}
+
+/// Returns the path of a field of the value at a path.
+fn child(path: &[String], field: &str) -> Vec<String> {
+    let mut path = path.to_vec();
+    path.push(field.to_string());
+    path
+}
+
+impl<D> Driver for ScopedDriver<D>
+where
+    D: Driver,
+{
+    fn ping(&self) -> Result<()> {
+        self.driver.ping()
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        Ok(self
+            .driver
+            .all()?
+            .into_iter()
+            .filter_map(|(key, value)| {
+                self.visible(std::slice::from_ref(&key), value)
+                    .map(|value| (key, value))
+            })
+            .collect())
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        let path = self.check_read(key)?;
+        Ok(self
+            .driver
+            .get(key)?
+            .and_then(|value| self.visible(&path, value)))
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        self.check_write(key)?;
+        self.driver.set(key, value)
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        let path = self.check_read(key)?;
+        if self.rules_below(&path).next().is_some() {
+            return Ok(self.get(key)?.is_some());
+        }
+        self.driver.has(key)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        self.check_write(key)?;
+        self.driver.delete(key)
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        let mut deleted = false;
+        for (key, value) in self.driver.all()? {
+            deleted |= self.delete_writable(&[key], value)?;
+        }
+        Ok(deleted)
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.check_write(key)?;
+        self.driver.add(key, value)
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.check_write(key)?;
+        self.driver.subtract(key, value)
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        self.check_write(key)?;
+        self.driver.push(key, value)
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        self.check_write(key)?;
+        self.driver.pull(key, value)
+    }
+
+    fn insert(&self, value: Value) -> Result<String> {
+        if !self.default.can_write() {
+            return Err(ChromoeError::PermissionDenied(
+                "inserting under a generated key requires a writable default".to_string(),
+            ));
+        }
+        self.driver.insert(value)
+    }
+
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        self.check_write(key)?;
+        self.driver.replace(key, value)
+    }
+
+    fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        let parent = self.driver.split_key(key);
+        for path in paths {
+            let mut full = parent.clone();
+            full.extend(self.driver.split_key(path));
+            self.check_write_path(&full, &format!("{}.{}", key, path))?;
+        }
+        self.driver.delete_many_nested(key, paths)
+    }
+
+    fn entry(&self, key: &str) -> Result<Option<DataSet>> {
+        let path = self.check_read(key)?;
+        Ok(self.driver.entry(key)?.and_then(|mut entry| {
+            entry.value = self.visible(&path, entry.value)?;
+            Some(entry)
+        }))
+    }
+
+    fn entries(&self) -> Result<Vec<DataSet>> {
+        Ok(self
+            .driver
+            .entries()?
+            .into_iter()
+            .filter_map(|entry| self.visible_entry(entry))
+            .collect())
+    }
+
+    fn split_key(&self, key: &str) -> Vec<String> {
+        self.driver.split_key(key)
+    }
+
+    fn join_key(&self, path: &[String]) -> Option<String> {
+        self.driver.join_key(path)
+    }
+}
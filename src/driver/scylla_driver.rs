@@ -0,0 +1,604 @@
+use futures::TryStreamExt;
+use scylla::client::session::Session;
+use scylla::client::session_builder::SessionBuilder;
+use scylla::errors::{ExecutionError, RequestAttemptError};
+use scylla::response::query_result::QueryResult;
+use scylla::serialize::row::SerializeRow;
+use scylla::statement::prepared::PreparedStatement;
+use scylla::value::{CqlValue, Row};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{from_str, Value};
+use tokio::runtime::Runtime;
+
+use crate::driver::json_path::{lookup, remove_path, replace, split_key};
+use crate::driver::{number, Driver};
+use crate::error::{ChromoeError, Result};
+use crate::structure::ScyllaDriverOptions;
+use crate::uri::is_valid_identifier;
+
+/// The number of times a conflicting write is retried before giving up.
+const MAX_ATTEMPTS: usize = 8;
+
+/// Driver storing JSON data in a ScyllaDB or Cassandra cluster.
+///
+/// Each key is a row of a table with an `id` text primary key and a `json`
+/// text column holding the value, so the table can be queried with `cqlsh` and
+/// spread across any number of nodes. Read-modify-write operations, such as
+/// `add` and writes to nested keys, are written with lightweight transactions
+/// that only apply if the row is unchanged since it was read, and retry
+/// otherwise, which keeps them atomic across clients. Requests that fail
+/// because no connection to the cluster is available are retried with the
+/// backoff of the `reconnect` option.
+///
+/// The `scylla` crate is asynchronous, so the driver runs its own Tokio runtime
+/// and blocks on every operation. Its methods must not be called from within
+/// an asynchronous task; use `tokio::task::spawn_blocking` there instead.
+///
+/// # Fields
+///
+/// - `options`: Configuration options for the Scylla driver.
+///
+/// # Example Usage
+///
+/// ```rust,no_run
+/// use chromoe_db::driver::scylla_driver::ScyllaDriver;
+///
+/// let driver =
+///     ScyllaDriver::from_uri("scylla://10.0.0.1:9042,10.0.0.2:9042?keyspace=bots&table=economy")
+///         .unwrap();
+///
+/// driver.set("coins", 100).unwrap();
+/// assert_eq!(driver.add("coins", 50.0).unwrap(), 150.0);
+/// ```
+pub struct ScyllaDriver {
+    /// Configuration options for the Scylla driver.
+    pub options: ScyllaDriverOptions,
+    session: Session,
+    statements: Statements,
+    runtime: Runtime,
+}
+
+/// The statements used by the driver, prepared once when it is created.
+struct Statements {
+    all: PreparedStatement,
+    get: PreparedStatement,
+    upsert: PreparedStatement,
+    delete: PreparedStatement,
+    insert_if_absent: PreparedStatement,
+    update_if: PreparedStatement,
+    delete_if: PreparedStatement,
+}
+
+impl ScyllaDriver {
+    /// Creates a new instance of the `ScyllaDriver`, connecting to the cluster
+    /// and creating the keyspace and table if they don't already exist.
+    ///
+    /// # Parameters
+    /// - `options`: Configuration options for the Scylla driver.
+    ///
+    /// # Returns
+    /// A `Result` containing either the `ScyllaDriver` instance or an error if
+    /// the keyspace or table name is invalid or the cluster cannot be reached.
+    pub fn new(options: ScyllaDriverOptions) -> Result<Self> {
+        for name in [&options.keyspace, &options.table_name] {
+            if !is_valid_identifier(name) {
+                return Err(ChromoeError::Config(format!(
+                    "`{}` is not a valid keyspace or table name",
+                    name
+                )));
+            }
+        }
+
+        // The session's connections are serviced in the background between operations.
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()?;
+
+        let (session, statements) = runtime.block_on(async {
+            let mut builder = SessionBuilder::new()
+                .known_nodes(&options.nodes)
+                .connection_timeout(options.timeout);
+            if let (Some(username), Some(password)) = (&options.username, &options.password) {
+                builder = builder.user(username, password);
+            }
+            let session = builder.build().await.map_err(scylla_error)?;
+
+            session
+                .query_unpaged(
+                    format!(
+                        "CREATE KEYSPACE IF NOT EXISTS {} WITH replication = \
+                         {{'class': 'SimpleStrategy', 'replication_factor': {}}}",
+                        options.keyspace, options.replication_factor
+                    ),
+                    (),
+                )
+                .await
+                .map_err(scylla_error)?;
+
+            let table = format!("{}.{}", options.keyspace, options.table_name);
+            session
+                .query_unpaged(
+                    format!(
+                        "CREATE TABLE IF NOT EXISTS {} (id text PRIMARY KEY, json text)",
+                        table
+                    ),
+                    (),
+                )
+                .await
+                .map_err(scylla_error)?;
+
+            let prepare = |cql: String| {
+                let session = &session;
+                async move { session.prepare(cql).await.map_err(scylla_error) }
+            };
+            let statements = Statements {
+                all: prepare(format!("SELECT id, json FROM {}", table)).await?,
+                get: prepare(format!("SELECT json FROM {} WHERE id = ?", table)).await?,
+                upsert: prepare(format!("INSERT INTO {} (id, json) VALUES (?, ?)", table)).await?,
+                delete: prepare(format!("DELETE FROM {} WHERE id = ? IF EXISTS", table)).await?,
+                insert_if_absent: prepare(format!(
+                    "INSERT INTO {} (id, json) VALUES (?, ?) IF NOT EXISTS",
+                    table
+                ))
+                .await?,
+                update_if: prepare(format!(
+                    "UPDATE {} SET json = ? WHERE id = ? IF json = ?",
+                    table
+                ))
+                .await?,
+                delete_if: prepare(format!("DELETE FROM {} WHERE id = ? IF json = ?", table))
+                    .await?,
+            };
+            Ok::<_, ChromoeError>((session, statements))
+        })?;
+
+        Ok(ScyllaDriver {
+            options,
+            session,
+            statements,
+            runtime,
+        })
+    }
+
+    /// Creates a new instance of the `ScyllaDriver` from a connection string.
+    ///
+    /// # Parameters
+    /// - `uri`: A connection string as accepted by [`ScyllaDriverOptions::from_uri`].
+    ///
+    /// # Returns
+    /// A `Result` containing the `ScyllaDriver` instance, or an error if the
+    /// connection string is invalid or the cluster cannot be reached.
+    pub fn from_uri(uri: &str) -> Result<Self> {
+        Self::new(ScyllaDriverOptions::from_uri(uri)?)
+    }
+
+    /// Checks that the cluster is reachable and the table can be read.
+    ///
+    /// # Returns
+    /// A `Result` which is an error if the table cannot be read.
+    pub fn ping(&self) -> Result<()> {
+        self.retry(|| {
+            self.runtime.block_on(async {
+                self.session
+                    .query_unpaged(
+                        format!(
+                            "SELECT id FROM {}.{} LIMIT 1",
+                            self.options.keyspace, self.options.table_name
+                        ),
+                        (),
+                    )
+                    .await
+                    .map_err(scylla_error)
+            })
+        })?;
+        Ok(())
+    }
+
+    /// Adds a value to an existing entry or creates a new entry if it doesn't exist.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry to update.
+    /// - `value`: The value to add to the current entry.
+    ///
+    /// # Returns
+    /// The new value after adding `value` to the existing entry.
+    pub fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.modify(key, |current| {
+            let new_value = number(current, self.options.lenient_numbers)? + value;
+            Ok((Some(Value::from(new_value)), new_value))
+        })
+    }
+
+    /// Retrieves all data entries from the table as a vector of tuples. The
+    /// table is read page by page.
+    ///
+    /// # Returns
+    /// A `Result` containing a vector of key and value pairs.
+    pub fn all(&self) -> Result<Vec<(String, Value)>> {
+        let rows: Vec<(String, Option<String>)> = self.retry(|| {
+            self.runtime.block_on(async {
+                self.session
+                    .execute_iter(self.statements.all.clone(), ())
+                    .await
+                    .map_err(scylla_error)?
+                    .rows_stream::<(String, Option<String>)>()
+                    .map_err(scylla_error)?
+                    .try_collect()
+                    .await
+                    .map_err(scylla_error)
+            })
+        })?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(id, json)| {
+                let json = json
+                    .and_then(|json| from_str(&json).ok())
+                    .unwrap_or(Value::Null);
+                (id, json)
+            })
+            .collect())
+    }
+
+    /// Deletes a specific entry by key, or a nested field within an entry.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to delete.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if a value was deleted, or `false` if there
+    /// was no value at `key`.
+    pub fn delete(&self, key: &str) -> Result<bool> {
+        if !key.contains('.') {
+            return applied(self.execute(&self.statements.delete, (key,))?);
+        }
+
+        self.modify(key, |current| Ok((None, current.is_some())))
+    }
+
+    /// Deletes several nested fields of the value at a key with a single write.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the value holding the fields, which may itself be nested.
+    /// - `paths`: The paths of the fields to delete, relative to `key`.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of fields deleted, or a `TypeMismatch`
+    /// error if a path leads through a value that is not an object.
+    pub fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        self.modify(key, |current| {
+            let mut value = current.cloned();
+            let mut deleted = 0;
+            if let Some(value) = value.as_mut() {
+                for path in paths {
+                    if remove_path(value, &path.split('.').collect::<Vec<_>>())? {
+                        deleted += 1;
+                    }
+                }
+            }
+            Ok((value, deleted))
+        })
+    }
+
+    /// Deletes all entries in the table.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the deletion was successful.
+    pub fn delete_all(&self) -> Result<bool> {
+        self.retry(|| {
+            self.runtime.block_on(async {
+                self.session
+                    .query_unpaged(
+                        format!(
+                            "TRUNCATE {}.{}",
+                            self.options.keyspace, self.options.table_name
+                        ),
+                        (),
+                    )
+                    .await
+                    .map_err(scylla_error)
+            })
+        })?;
+        Ok(true)
+    }
+
+    /// Retrieves the value for a given key, deserialising it into the specified type.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` of the deserialised value.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned + Default,
+    {
+        let (root, path) = split_key(key);
+        let value = self
+            .read_row(root)?
+            .and_then(|json| from_str::<Value>(&json).ok())
+            .and_then(|root| lookup(&root, &path).cloned());
+
+        Ok(value.map(|value| serde_json::from_value(value).unwrap_or_default()))
+    }
+
+    /// Checks if a given key exists.
+    ///
+    /// # Parameters
+    /// - `key`: The key to check.
+    ///
+    /// # Returns
+    /// A `Result` containing a boolean indicating whether the key exists.
+    pub fn has(&self, key: &str) -> Result<bool> {
+        Ok(self.get::<Value>(key)?.is_some())
+    }
+
+    /// Removes a specific value from an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to remove from the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after removal.
+    pub fn pull<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + std::cmp::PartialEq + Clone + Serialize,
+    {
+        self.modify(key, |current| {
+            let mut arr: Vec<T> = current
+                .and_then(|current| serde_json::from_value(current.clone()).ok())
+                .unwrap_or_default();
+            arr.retain(|x| x != &value);
+            Ok((Some(serde_json::to_value(&arr)?), arr))
+        })
+    }
+
+    /// Appends a value to an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to append to the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after the value is appended.
+    pub fn push<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + Clone + Serialize,
+    {
+        self.modify(key, |current| {
+            let mut arr: Vec<T> = current
+                .and_then(|current| serde_json::from_value(current.clone()).ok())
+                .unwrap_or_default();
+            arr.push(value.clone());
+            Ok((Some(serde_json::to_value(&arr)?), arr))
+        })
+    }
+
+    /// Sets the value for a given key and returns the value it replaced, with
+    /// the same guarantees against concurrent writers as `set`.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` containing the previous value, or `None` if the key didn't
+    /// exist, or a `Json` error if the previous value cannot be deserialised
+    /// into the type of `value`.
+    pub fn replace<T>(&self, key: &str, value: T) -> Result<Option<T>>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        let value = serde_json::to_value(value)?;
+        let previous = self.modify(key, |current| Ok((Some(value.clone()), current.cloned())))?;
+        Ok(previous.map(serde_json::from_value).transpose()?)
+    }
+
+    /// Sets or updates the value for a given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the value was stored.
+    pub fn set<T>(&self, key: &str, value: T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        if !key.contains('.') {
+            self.execute(&self.statements.upsert, (key, value.to_string()))?;
+            return Ok(());
+        }
+
+        self.modify(key, |_| Ok((Some(value.clone()), ())))
+    }
+
+    /// Subtracts a value from an existing entry. If the entry does not exist,
+    /// it initialises it with the result.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to subtract from.
+    /// - `value`: The value to subtract from the current value.
+    ///
+    /// # Returns
+    /// The new value after subtraction.
+    pub fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.modify(key, |current| {
+            let new_value = number(current, self.options.lenient_numbers)? - value;
+            Ok((Some(Value::from(new_value)), new_value))
+        })
+    }
+
+    /// Replaces the value at a key, retrying if the row changes between reading
+    /// and writing it.
+    ///
+    /// `update` receives the current value at the key and returns its new value,
+    /// `None` removing it, along with the result of the operation.
+    fn modify<T, F>(&self, key: &str, mut update: F) -> Result<T>
+    where
+        F: FnMut(Option<&Value>) -> Result<(Option<Value>, T)>,
+    {
+        let (root, path) = split_key(key);
+
+        for _ in 0..MAX_ATTEMPTS {
+            let current = self.read_row(root)?;
+            let mut root_value = current.as_deref().and_then(|json| from_str(json).ok());
+
+            let (value, result) = update(root_value.as_ref().and_then(|root| lookup(root, &path)))?;
+            replace(&mut root_value, &path, value)?;
+
+            let response = match (&current, &root_value) {
+                (None, None) => return Ok(result),
+                (None, Some(new)) => {
+                    self.execute(&self.statements.insert_if_absent, (root, new.to_string()))?
+                }
+                (Some(old), Some(new)) => {
+                    self.execute(&self.statements.update_if, (new.to_string(), root, old))?
+                }
+                (Some(old), None) => self.execute(&self.statements.delete_if, (root, old))?,
+            };
+
+            if applied(response)? {
+                return Ok(result);
+            }
+        }
+
+        Err(ChromoeError::Scylla(
+            format!("`{}` kept changing while it was being updated", root).into(),
+        ))
+    }
+
+    /// Reads the serialised value of a row.
+    fn read_row(&self, key: &str) -> Result<Option<String>> {
+        let response = self.execute(&self.statements.get, (key,))?;
+        let row = response
+            .into_rows_result()
+            .map_err(scylla_error)?
+            .maybe_first_row::<(Option<String>,)>()
+            .map_err(scylla_error)?;
+        Ok(row.and_then(|(json,)| json))
+    }
+
+    /// Executes a prepared statement with the given values.
+    fn execute(
+        &self,
+        statement: &PreparedStatement,
+        values: impl SerializeRow,
+    ) -> Result<QueryResult> {
+        self.retry(|| {
+            self.runtime.block_on(async {
+                self.session
+                    .execute_unpaged(statement, &values)
+                    .await
+                    .map_err(scylla_error)
+            })
+        })
+    }
+
+    /// Runs an operation, retrying it as configured by the `reconnect` option
+    /// when no connection to the cluster is available. The session reconnects
+    /// to the nodes in the background meanwhile.
+    fn retry<T, F>(&self, operation: F) -> Result<T>
+    where
+        F: FnMut() -> Result<T>,
+    {
+        self.options
+            .reconnect
+            .run(operation, is_connection_error, || Ok(()))
+    }
+}
+
+impl Driver for ScyllaDriver {
+    fn ping(&self) -> Result<()> {
+        ScyllaDriver::ping(self)
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        ScyllaDriver::all(self)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        ScyllaDriver::get(self, key)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        ScyllaDriver::set(self, key, value)
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        ScyllaDriver::has(self, key)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        ScyllaDriver::delete(self, key)
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        ScyllaDriver::delete_all(self)
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        ScyllaDriver::add(self, key, value)
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        ScyllaDriver::subtract(self, key, value)
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        ScyllaDriver::push(self, key, value)
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        ScyllaDriver::pull(self, key, value)
+    }
+
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        ScyllaDriver::replace(self, key, value)
+    }
+
+    fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        ScyllaDriver::delete_many_nested(self, key, paths)
+    }
+}
+
+/// Wraps any of the `scylla` crate's error types in a `ChromoeError`.
+fn scylla_error<E>(err: E) -> ChromoeError
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    ChromoeError::Scylla(Box::new(err))
+}
+
+/// Returns whether an error means no connection to the cluster was available,
+/// so the request was never sent. Requests that timed out or whose connection
+/// broke may have been applied, and are not retried.
+fn is_connection_error(err: &ChromoeError) -> bool {
+    let ChromoeError::Scylla(err) = err else {
+        return false;
+    };
+    matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(
+            ExecutionError::ConnectionPoolError(_)
+                | ExecutionError::LastAttemptError(RequestAttemptError::UnableToAllocStreamId)
+        )
+    )
+}
+
+/// Returns whether a conditional write was applied, as reported by the first
+/// column, `[applied]`, of the row it returns.
+fn applied(response: QueryResult) -> Result<bool> {
+    let applied = response
+        .into_rows_result()
+        .map_err(scylla_error)?
+        .maybe_first_row::<Row>()
+        .map_err(scylla_error)?
+        .and_then(|row| row.columns.into_iter().next().flatten());
+    Ok(matches!(applied, Some(CqlValue::Boolean(true))))
+}
@@ -0,0 +1,334 @@
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::driver::Driver;
+use crate::error::{ChromoeError, Result};
+use crate::structure::{SQLiteDriverOptions, ShardedSQLiteDriverOptions};
+
+/// SQLite driver spreading its entries across several database files.
+///
+/// The `ShardedSQLiteDriver` hashes the root of every key (the part before the
+/// first `.`) to pick one of `shard_count` SQLite files, so nested keys always
+/// live in the same shard as their parent. Writes to different shards don't
+/// contend for the same file lock, which lifts the main scaling limit of a
+/// single SQLite file. It exposes the same API as the `SQLiteDriver`.
+///
+/// The shard of a key depends on the shard count, so the count must not change
+/// once data has been written.
+///
+/// # Fields
+///
+/// - `options`: Configuration options for the sharded driver.
+/// - `shards`: The drivers for every shard, in shard order.
+#[derive(Debug)]
+pub struct ShardedSQLiteDriver {
+    /// Configuration options for the sharded driver.
+    pub options: ShardedSQLiteDriverOptions,
+    /// The drivers for every shard, in shard order.
+    pub shards: Vec<SQLiteDriver>,
+}
+
+impl ShardedSQLiteDriver {
+    /// Creates a new instance of the `ShardedSQLiteDriver`, creating the shard
+    /// directory and database files if they don't exist.
+    ///
+    /// # Parameters
+    /// - `options`: Configuration options for the sharded driver.
+    ///
+    /// # Returns
+    /// A `Result` containing either the `ShardedSQLiteDriver` instance or an error
+    /// if the shard count is zero or a shard cannot be opened.
+    pub fn new(options: ShardedSQLiteDriverOptions) -> Result<Self> {
+        if options.shard_count == 0 {
+            return Err(ChromoeError::Config(
+                "the shard count must be at least one".to_string(),
+            ));
+        }
+
+        std::fs::create_dir_all(&options.directory)?;
+
+        let shards = (0..options.shard_count)
+            .map(|shard| {
+                let file_name = Path::new(&options.directory)
+                    .join(format!("shard-{}.sqlite", shard))
+                    .to_string_lossy()
+                    .into_owned();
+                SQLiteDriver::new(Some(SQLiteDriverOptions {
+                    file_name,
+                    table_name: options.table_name.clone(),
+                    wal: options.wal,
+                    ..Default::default()
+                }))
+            })
+            .collect::<Result<Vec<SQLiteDriver>>>()?;
+
+        Ok(ShardedSQLiteDriver { options, shards })
+    }
+
+    /// Returns the index of the shard storing the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key, which may refer to a nested value.
+    ///
+    /// # Returns
+    /// The index of the shard in `shards`.
+    pub fn shard_index(&self, key: &str) -> usize {
+        let root = self.shards[0].split_path(key).swap_remove(0);
+
+        // FNV-1a keeps the placement stable across processes and Rust versions.
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        for byte in root.bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        }
+
+        (hash % self.shards.len() as u64) as usize
+    }
+
+    /// Returns the driver of the shard storing the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key, which may refer to a nested value.
+    ///
+    /// # Returns
+    /// The `SQLiteDriver` for the key's shard.
+    pub fn shard_for(&self, key: &str) -> &SQLiteDriver {
+        &self.shards[self.shard_index(key)]
+    }
+
+    /// Adds a value to an existing entry or creates a new entry if it doesn't exist.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry to update.
+    /// - `value`: The value to add to the current entry.
+    ///
+    /// # Returns
+    /// The new value after adding `value` to the existing entry.
+    pub fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.shard_for(key).add(key, value)
+    }
+
+    /// Retrieves all data entries from every shard as a vector of tuples.
+    ///
+    /// # Returns
+    /// A `Result` containing the entries of all shards, in shard order.
+    pub fn all(&self) -> Result<Vec<(String, Value)>> {
+        let mut data = Vec::new();
+        for shard in &self.shards {
+            data.extend(shard.all()?);
+        }
+        Ok(data)
+    }
+
+    /// Closes every shard, making sure everything written is in the shard
+    /// files before returning. See `SQLiteDriver::close`.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether every shard was closed, or the first
+    /// error encountered. The remaining shards are still closed when dropped.
+    pub fn close(self) -> Result<()> {
+        for shard in self.shards {
+            shard.close()?;
+        }
+        Ok(())
+    }
+
+    /// Deletes a specific entry by key, or a nested field within an entry.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to delete.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if a value was deleted, or `false` if there
+    /// was no value at `key`.
+    pub fn delete(&self, key: &str) -> Result<bool> {
+        self.shard_for(key).delete(key)
+    }
+
+    /// Deletes all entries in every shard.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the deletion was successful.
+    pub fn delete_all(&self) -> Result<bool> {
+        for shard in &self.shards {
+            shard.delete_all()?;
+        }
+        Ok(true)
+    }
+
+    /// Retrieves the value for a given key, deserialising it into the specified type.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` of the deserialised value.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned + Default,
+    {
+        self.shard_for(key).get(key)
+    }
+
+    /// Checks if a given key exists.
+    ///
+    /// # Parameters
+    /// - `key`: The key to check.
+    ///
+    /// # Returns
+    /// A `Result` containing a boolean indicating whether the key exists.
+    pub fn has(&self, key: &str) -> Result<bool> {
+        self.shard_for(key).has(key)
+    }
+
+    /// Removes a specific value from an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to remove from the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after removal.
+    pub fn pull<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + std::cmp::PartialEq + Clone + Serialize,
+    {
+        self.shard_for(key).pull(key, value)
+    }
+
+    /// Removes the first occurrence of a value from an array stored at the
+    /// given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to remove from the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after removal.
+    pub fn pull_first<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + std::cmp::PartialEq + Serialize,
+    {
+        self.shard_for(key).pull_first(key, value)
+    }
+
+    /// Removes every occurrence of several values from an array stored at the
+    /// given key, in a single write.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `values`: The values to remove from the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after removal.
+    pub fn pull_many<T>(&self, key: &str, values: &[T]) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + std::cmp::PartialEq + Serialize,
+    {
+        self.shard_for(key).pull_many(key, values)
+    }
+
+    /// Appends a value to an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to append to the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after the value is appended.
+    pub fn push<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + Clone + Serialize,
+    {
+        self.shard_for(key).push(key, value)
+    }
+
+    /// Sets or updates the value for a given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the value was stored.
+    pub fn set<T>(&self, key: &str, value: T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        self.shard_for(key).set(key, value)
+    }
+
+    /// Subtracts a value from an existing entry. If the entry does not exist,
+    /// it initialises it with the result.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to subtract from.
+    /// - `value`: The value to subtract from the current value.
+    ///
+    /// # Returns
+    /// The new value after subtraction.
+    pub fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.shard_for(key).subtract(key, value)
+    }
+}
+
+impl Driver for ShardedSQLiteDriver {
+    fn ping(&self) -> Result<()> {
+        for shard in &self.shards {
+            shard.ping()?;
+        }
+        Ok(())
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        ShardedSQLiteDriver::all(self)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        ShardedSQLiteDriver::get(self, key)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        ShardedSQLiteDriver::set(self, key, value)
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        ShardedSQLiteDriver::has(self, key)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        ShardedSQLiteDriver::delete(self, key)
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        ShardedSQLiteDriver::delete_all(self)
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        ShardedSQLiteDriver::add(self, key, value)
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        ShardedSQLiteDriver::subtract(self, key, value)
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        ShardedSQLiteDriver::push(self, key, value)
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        ShardedSQLiteDriver::pull(self, key, value)
+    }
+
+    fn split_key(&self, key: &str) -> Vec<String> {
+        Driver::split_key(&self.shards[0], key)
+    }
+
+    fn join_key(&self, path: &[String]) -> Option<String> {
+        Driver::join_key(&self.shards[0], path)
+    }
+}
@@ -0,0 +1,188 @@
+use crate::error::{ChromoeError, Result};
+
+/// A token of a SQL dump.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// A keyword or identifier, with the quotes of a quoted identifier removed.
+    Word(String),
+    /// A string literal, with its quotes removed and its escapes resolved.
+    Text(String),
+    /// Any other character, such as `(`, `,` or `;`.
+    Symbol(char),
+}
+
+/// Quotes text as a SQL string literal.
+pub(crate) fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', "''"))
+}
+
+/// Reads the rows inserted into a table by a SQL dump, such as the output of
+/// `SQLiteDriver::export_sql` or the `.dump` command of the sqlite3 CLI.
+///
+/// Transaction, `CREATE` and `PRAGMA` statements are skipped, as are the rows
+/// inserted into other tables, so a dump of a whole database can be read.
+///
+/// # Parameters
+/// - `sql`: The statements of the dump.
+/// - `table`: The name of the table whose rows are read, compared without
+///   regard to case.
+///
+/// # Returns
+/// A `Result` containing the key and JSON text of every row, in order, or a
+/// `Config` error if the dump has another kind of statement or an `INSERT`
+/// statement that doesn't insert a key and a JSON text.
+pub(crate) fn parse_inserts(sql: &str, table: &str) -> Result<Vec<(String, String)>> {
+    let tokens = tokenize(sql)?;
+    let mut rows = Vec::new();
+    let mut rest = tokens.as_slice();
+    while !rest.is_empty() {
+        let (statement, tail) = next_statement(rest);
+        rest = tail;
+        let Some(Token::Word(keyword)) = statement.first() else {
+            if statement.is_empty() {
+                continue;
+            }
+            return Err(malformed("a statement must start with a keyword"));
+        };
+
+        match keyword.to_ascii_uppercase().as_str() {
+            "BEGIN" | "COMMIT" | "END" | "CREATE" | "PRAGMA" => {}
+            "INSERT" => parse_insert(&statement[1..], table, &mut rows)?,
+            other => {
+                return Err(malformed(&format!("unsupported `{}` statement", other)));
+            }
+        }
+    }
+    Ok(rows)
+}
+
+/// Splits the first statement, without its `;`, from the tokens following it.
+///
+/// The statements within the body of a `CREATE TRIGGER` statement are kept in
+/// it, up to the `END` closing the body.
+fn next_statement(tokens: &[Token]) -> (&[Token], &[Token]) {
+    let is_word = |token: &Token, word: &str| matches!(token, Token::Word(text) if text.eq_ignore_ascii_case(word));
+    let trigger = tokens.first().is_some_and(|token| is_word(token, "CREATE"))
+        && tokens.iter().take(3).any(|token| is_word(token, "TRIGGER"));
+
+    let end = (0..tokens.len()).find(|&i| {
+        tokens[i] == Token::Symbol(';') && (!trigger || (i > 0 && is_word(&tokens[i - 1], "END")))
+    });
+    match end {
+        Some(end) => (&tokens[..end], &tokens[end + 1..]),
+        None => (tokens, &[]),
+    }
+}
+
+/// Reads the rows of an `INSERT` statement following its keyword, if it
+/// inserts into `table`.
+fn parse_insert(mut tokens: &[Token], table: &str, rows: &mut Vec<(String, String)>) -> Result<()> {
+    if let [Token::Word(or), Token::Word(_), rest @ ..] = tokens {
+        if or.eq_ignore_ascii_case("OR") {
+            tokens = rest;
+        }
+    }
+    let [Token::Word(into), Token::Word(name), rest @ ..] = tokens else {
+        return Err(malformed("expected `INSERT INTO <table>`"));
+    };
+    if !into.eq_ignore_ascii_case("INTO") {
+        return Err(malformed("expected `INSERT INTO <table>`"));
+    }
+    let mut name = name;
+    tokens = rest;
+    if let [Token::Symbol('.'), Token::Word(bare), rest @ ..] = tokens {
+        name = bare;
+        tokens = rest;
+    }
+    if !name.eq_ignore_ascii_case(table) {
+        return Ok(());
+    }
+
+    if let [Token::Symbol('('), rest @ ..] = tokens {
+        let end = rest
+            .iter()
+            .position(|token| *token == Token::Symbol(')'))
+            .ok_or_else(|| malformed("unterminated column list"))?;
+        tokens = &rest[end + 1..];
+    }
+    let [Token::Word(values), rest @ ..] = tokens else {
+        return Err(malformed("expected `VALUES`"));
+    };
+    if !values.eq_ignore_ascii_case("VALUES") {
+        return Err(malformed("expected `VALUES`"));
+    }
+
+    let mut rest = rest;
+    loop {
+        let [Token::Symbol('('), Token::Text(key), Token::Symbol(','), Token::Text(json), Token::Symbol(')'), tail @ ..] =
+            rest
+        else {
+            return Err(malformed("expected rows of a key and a JSON text"));
+        };
+        rows.push((key.clone(), json.clone()));
+        match tail {
+            [] => return Ok(()),
+            [Token::Symbol(','), tail @ ..] => rest = tail,
+            _ => return Err(malformed("expected `,` or `;` after a row")),
+        }
+    }
+}
+
+/// Splits a SQL dump into tokens, skipping whitespace and comments.
+fn tokenize(sql: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = sql.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '-' if chars.peek() == Some(&'-') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '\'' | '"' | '`' | '[' => {
+                let close = if c == '[' { ']' } else { c };
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == close => {
+                            if close != ']' && chars.peek() == Some(&close) {
+                                chars.next();
+                                text.push(close);
+                            } else {
+                                break;
+                            }
+                        }
+                        Some(next) => text.push(next),
+                        None => return Err(malformed("unterminated quoted text")),
+                    }
+                }
+                tokens.push(if c == '\'' {
+                    Token::Text(text)
+                } else {
+                    Token::Word(text)
+                });
+            }
+            c if c.is_alphanumeric() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+            c => tokens.push(Token::Symbol(c)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Builds the error returned for a dump that can't be read.
+fn malformed(reason: &str) -> ChromoeError {
+    ChromoeError::Config(format!("invalid SQL dump: {}", reason))
+}
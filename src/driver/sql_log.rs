@@ -0,0 +1,144 @@
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+use std::panic::catch_unwind;
+use std::ptr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use rusqlite::{ffi, Connection};
+
+use crate::structure::{SqlLogFn, SqlStatement};
+
+/// The SQL log of the driver running an operation on a thread.
+struct Scope {
+    /// The function called with every statement.
+    on_statement: Arc<SqlLogFn>,
+    /// The statements started but not finished yet, identified by their
+    /// address, with their text and when they started.
+    started: Vec<(usize, String, Instant)>,
+    /// Whether `on_statement` is running, so the statements it runs itself
+    /// are not logged.
+    logging: bool,
+}
+
+thread_local! {
+    /// The trace callback is shared by every connection, so the SQL log of
+    /// the running operation is found through the thread running it.
+    static SCOPE: RefCell<Option<Scope>> = const { RefCell::new(None) };
+}
+
+/// Traces the statements run on a connection, so the statements run within
+/// a [`scope`] are logged.
+pub(crate) fn install(database: &Connection) {
+    let mask = (ffi::SQLITE_TRACE_STMT | ffi::SQLITE_TRACE_PROFILE) as c_uint;
+    // SAFETY: the handle is valid while the connection is open, and the
+    // callback doesn't use a context pointer. rusqlite only wraps the legacy
+    // trace and profile callbacks, which can't be installed together.
+    unsafe {
+        ffi::sqlite3_trace_v2(database.handle(), mask, Some(callback), ptr::null_mut());
+    }
+}
+
+/// Runs an operation, logging the statements it runs on connections traced
+/// with [`install`] to `on_statement`.
+pub(crate) fn scope<T, F>(on_statement: Arc<SqlLogFn>, run: F) -> T
+where
+    F: FnOnce() -> T,
+{
+    let outer = SCOPE.with(|scope| {
+        scope.borrow_mut().replace(Scope {
+            on_statement,
+            started: Vec::new(),
+            logging: false,
+        })
+    });
+    let value = run();
+    SCOPE.with(|scope| *scope.borrow_mut() = outer);
+    value
+}
+
+/// Receives the start and the end of every statement run on a traced
+/// connection.
+unsafe extern "C" fn callback(
+    event: c_uint,
+    _context: *mut c_void,
+    statement: *mut c_void,
+    sql: *mut c_void,
+) -> c_int {
+    let logged = SCOPE.with(|scope| scope.borrow().as_ref().is_some_and(|scope| !scope.logging));
+    if !logged {
+        return 0;
+    }
+
+    let id = statement as usize;
+    if event == ffi::SQLITE_TRACE_STMT as c_uint {
+        // SAFETY: SQLite gives the statement and its text, which stay valid
+        // during the callback.
+        let sql = unsafe { CStr::from_ptr(sql as *const c_char) };
+        // The statements run by triggers are given as comments naming them.
+        if sql.to_bytes().starts_with(b"--") {
+            return 0;
+        }
+        let sql = unsafe { expanded_sql(statement as *mut ffi::sqlite3_stmt, sql) };
+        let _ = catch_unwind(|| started(id, sql));
+    } else if event == ffi::SQLITE_TRACE_PROFILE as c_uint {
+        let _ = catch_unwind(|| finished(id));
+    }
+    0
+}
+
+/// Returns the text of a statement with the values bound to its parameters
+/// written in place of them.
+///
+/// # Safety
+/// `statement` must be a valid statement, whose text is `sql`.
+unsafe fn expanded_sql(statement: *mut ffi::sqlite3_stmt, sql: &CStr) -> String {
+    let expanded = unsafe { ffi::sqlite3_expanded_sql(statement) };
+    if expanded.is_null() {
+        return sql.to_string_lossy().into_owned();
+    }
+    let text = unsafe { CStr::from_ptr(expanded) }
+        .to_string_lossy()
+        .into_owned();
+    unsafe { ffi::sqlite3_free(expanded as *mut c_void) };
+    text
+}
+
+/// Records the start of a statement.
+fn started(id: usize, sql: String) {
+    SCOPE.with(|scope| {
+        if let Some(scope) = scope.borrow_mut().as_mut() {
+            scope.started.retain(|(started, ..)| *started != id);
+            scope.started.push((id, sql, Instant::now()));
+        }
+    });
+}
+
+/// Logs a finished statement.
+fn finished(id: usize) {
+    let found = SCOPE.with(|scope| {
+        let mut scope = scope.borrow_mut();
+        let scope = scope.as_mut()?;
+        let position = scope
+            .started
+            .iter()
+            .position(|(started, ..)| *started == id)?;
+        let (_, sql, since) = scope.started.remove(position);
+        scope.logging = true;
+        Some((scope.on_statement.clone(), sql, since))
+    });
+    let Some((on_statement, sql, since)) = found else {
+        return;
+    };
+
+    on_statement(&SqlStatement {
+        sql: &sql,
+        duration: since.elapsed(),
+    });
+    SCOPE.with(|scope| {
+        if let Some(scope) = scope.borrow_mut().as_mut() {
+            scope.logging = false;
+        }
+    });
+}
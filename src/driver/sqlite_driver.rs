@@ -1,9 +1,54 @@
-use rusqlite::{params, Connection, Error as RusqliteError, OptionalExtension, Result};
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::{Read, Write};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use rusqlite::functions::FunctionFlags;
+use rusqlite::types::{Value as SqlValue, ValueRef};
+use rusqlite::{
+    params, params_from_iter, Connection, Error as RusqliteError, ErrorCode, OpenFlags,
+    OptionalExtension, Params, Row, Transaction, TransactionBehavior,
+};
+#[cfg(feature = "decimal")]
+use rust_decimal::Decimal;
 use serde::de::DeserializeOwned;
-use serde::Serialize;
-use serde_json::{from_str, json, to_string, Error as SerdeJsonError, Value};
+use serde::{Deserialize, Serialize};
+use serde_json::error::Category;
+use serde_json::value::RawValue;
+use serde_json::{from_str, json, to_string, Error as SerdeJsonError, Map, Value};
 
-use crate::structure::SQLiteDriverOptions;
+use crate::batch::Batch;
+use crate::counter::Counter;
+use crate::driver::json_path::{join_escaped, lookup, remove_path, replace, split_escaped};
+use crate::driver::raw_json::lookup_raw;
+use crate::driver::sql_dump;
+use crate::driver::sql_log;
+use crate::driver::Driver;
+use crate::error::{ChromoeError, Result};
+use crate::metrics::{InMemoryMetrics, MetricsSink, OperationEvent, OperationMetrics};
+use crate::migrations::Migrations;
+use crate::pubsub::Subscriber;
+use crate::queue::Queue;
+use crate::replication::ChangeLog;
+use crate::snapshot::Snapshot;
+use crate::sorted::Sorted;
+use crate::structure::{
+    Aggregate, BulkProgress, BulkUpdateOptions, Change, CheckpointMode, CheckpointReport, Cooldown,
+    CorruptedRow, Cursor, DataSet, EvictionPolicy, ExpiryOptions, IntegrityReport, Job, JsonType,
+    KeyStats, ListOptions, Lock, Message, Namespace, Order, Page, QueryPlanStep, QuotaMode,
+    QuotaOptions, RawRow, RepairStrategy, RetentionOptions, RetentionTimestamp,
+    SQLiteDriverOptions, SQLiteOpenMode, SeedMode, StorageStats, TableOptions, TransactionMode,
+    TriggerDefinition, TriggerEvent, TriggerTiming,
+};
+use crate::transaction::TransactionScope;
+use crate::uri::is_valid_identifier;
 
 /// SQLite database driver for storing and managing JSON data.
 ///
@@ -21,7 +66,6 @@ use crate::structure::SQLiteDriverOptions;
 ///   database file name and table name.
 /// - `table`: The name of the table in the SQLite database to operate on.
 /// - `database`: The connection to the SQLite database.
-#[derive(Debug)]
 pub struct SQLiteDriver {
     /// The name of the SQLite database file.
     pub name: String,
@@ -32,6 +76,186 @@ pub struct SQLiteDriver {
     pub table: String,
     /// The connection to the SQLite database.
     pub database: Connection,
+    /// How many instrumented operations are currently running, so that only the
+    /// outermost operation is reported when one operation is built on others.
+    depth: Cell<usize>,
+    /// The built-in recorder queried through `metrics()`.
+    metrics: InMemoryMetrics,
+    /// Additional sinks notified of every completed operation.
+    metrics_sinks: Vec<Arc<dyn MetricsSink>>,
+    /// User-defined SQL functions, re-registered when reconnecting.
+    functions: Vec<UserFunction>,
+    /// User-defined collations, re-registered when reconnecting.
+    collations: Vec<(String, Arc<CollationFn>)>,
+    /// Attached databases as path and alias, re-attached when reconnecting.
+    attachments: Vec<(String, String)>,
+    /// Writes waiting to be flushed when write buffering is enabled.
+    write_buffer: RefCell<WriteBuffer>,
+    /// The timeout set by `with_timeout`, overriding `options.timeout`.
+    timeout_override: Cell<Option<Duration>>,
+    /// The database file created by `temp`, deleted when the driver is dropped.
+    temp_file: Option<PathBuf>,
+    /// The background thread deleting expired entries, stopped when the driver
+    /// is dropped.
+    sweeper: Option<Sweeper>,
+    /// The background thread enforcing the retention policy, stopped when the
+    /// driver is dropped.
+    retention: Option<Sweeper>,
+    /// Whether the statements run by the driver are given to the `sql_log`
+    /// function.
+    sql_logging: Cell<bool>,
+}
+
+/// A background thread deleting entries of a table with its own connection,
+/// such as expired entries, until it is dropped.
+struct Sweeper {
+    stop: Option<mpsc::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for Sweeper {
+    fn drop(&mut self) {
+        // Dropping the sender wakes the thread up, which then exits.
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The version of the layout of the tables and triggers the driver keeps in a
+/// database, recorded in the database's `chromoe_meta` table.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The migrations upgrading a database to [`SCHEMA_VERSION`], where the one at
+/// index `i` upgrades the database of the given schema from version `i`.
+const MIGRATIONS: &[fn(&Connection, &str) -> Result<()>] = &[migrate_table_options];
+
+/// How long SQLite waits for a lock when no timeout is configured, matching the
+/// default set by `rusqlite` when a connection is opened.
+const DEFAULT_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How many SQLite virtual machine instructions run between deadline checks.
+const DEADLINE_CHECK_INTERVAL: i32 = 1000;
+
+/// The longest timeout SQLite accepts, which takes lock waits in milliseconds
+/// as an `i32`.
+const MAX_TIMEOUT: Duration = Duration::from_millis(i32::MAX as u64);
+
+/// Operations that only touch individual keys, and can therefore read through
+/// the write buffer instead of flushing it first.
+const KEYED_OPERATIONS: &[&str] = &[
+    "add",
+    "add_decimal",
+    "delete",
+    "delete_many_nested",
+    "flush",
+    "get",
+    "get_field",
+    "get_json",
+    "get_path",
+    "get_raw",
+    "get_with",
+    "has",
+    "insert",
+    "pull",
+    "pull_first",
+    "pull_many",
+    "push",
+    "replace",
+    "sadd",
+    "set",
+    "set_raw",
+    "sismember",
+    "smembers",
+    "srem",
+    "subtract",
+    "subtract_decimal",
+];
+
+/// Writes buffered by a driver with write buffering enabled.
+#[derive(Debug, Default)]
+struct WriteBuffer {
+    /// The latest serialised value of every written key, `None` for deletions.
+    entries: BTreeMap<String, Option<String>>,
+    /// The number of writes buffered since the last flush.
+    operations: usize,
+    /// When the oldest buffered write was made.
+    since: Option<Instant>,
+}
+
+impl WriteBuffer {
+    /// Discards every buffered write.
+    fn clear(&mut self) {
+        *self = WriteBuffer::default();
+    }
+}
+
+impl Drop for SQLiteDriver {
+    fn drop(&mut self) {
+        // Errors cannot be reported from `drop`; call `close` to handle them.
+        let _ = self.flush_buffer();
+        self.stop_background_threads();
+        // A passive checkpoint never waits for other connections, so dropping
+        // a driver doesn't block while the database is busy.
+        if self.temp_file.is_none() {
+            let _ = self.checkpoint_wal(CheckpointMode::Passive);
+        }
+
+        if let Some(path) = self.temp_file.take() {
+            // The file is closed first, since open files cannot be deleted on Windows.
+            if let Ok(memory) = Connection::open_in_memory() {
+                drop(std::mem::replace(&mut self.database, memory));
+            }
+            for suffix in ["", "-wal", "-shm", "-journal"] {
+                let mut file = path.clone().into_os_string();
+                file.push(suffix);
+                let _ = std::fs::remove_file(file);
+            }
+        }
+    }
+}
+
+/// The implementation of a user-defined scalar SQL function.
+type ScalarFn = dyn Fn(&[Value]) -> Result<Value> + Send + Sync;
+
+/// The implementation of a user-defined collation.
+type CollationFn = dyn Fn(&str, &str) -> std::cmp::Ordering + Send + Sync;
+
+/// The metadata of an entry, derived from the change log.
+struct ChangeMetadata {
+    created_at: SystemTime,
+    updated_at: SystemTime,
+    version: u64,
+}
+
+impl ChangeMetadata {
+    /// Fills in the metadata fields of an entry.
+    fn apply<T>(self, entry: &mut DataSet<T>) {
+        entry.created_at = Some(self.created_at);
+        entry.updated_at = Some(self.updated_at);
+        entry.version = Some(self.version);
+    }
+}
+
+/// A scalar SQL function registered with `register_function`.
+#[derive(Clone)]
+struct UserFunction {
+    name: String,
+    n_args: i32,
+    deterministic: bool,
+    run: Arc<ScalarFn>,
+}
+
+impl fmt::Debug for SQLiteDriver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SQLiteDriver")
+            .field("name", &self.name)
+            .field("options", &self.options)
+            .field("table", &self.table)
+            .field("database", &self.database)
+            .finish_non_exhaustive()
+    }
 }
 
 impl SQLiteDriver {
@@ -45,307 +269,6396 @@ impl SQLiteDriver {
     /// # Returns
     /// A `Result` containing either the `SQLiteDriver` instance or an error.
     pub fn new(options: Option<SQLiteDriverOptions>) -> Result<Self> {
-        let options = options.unwrap_or_else(|| SQLiteDriverOptions {
-            file_name: "json.sqlite".to_string(),
-            table_name: "json".to_string(),
-        });
+        let options = options.unwrap_or_default();
 
-        let database = Connection::open(&options.file_name)?;
+        let database = Self::open(&options)?;
 
-        let driver = SQLiteDriver {
+        let mut driver = SQLiteDriver {
             name: options.file_name.clone(),
             options: options.clone(),
             table: options.table_name.clone(),
             database,
+            depth: Cell::new(0),
+            metrics: InMemoryMetrics::default(),
+            metrics_sinks: Vec::new(),
+            functions: Vec::new(),
+            collations: Vec::new(),
+            attachments: Vec::new(),
+            write_buffer: RefCell::new(WriteBuffer::default()),
+            timeout_override: Cell::new(None),
+            temp_file: None,
+            sweeper: None,
+            retention: None,
+            sql_logging: Cell::new(options.sql_log.as_ref().is_some_and(|log| log.enabled)),
         };
 
-        driver.prepare(&options.table_name)?;
+        driver.initialize()?;
+        driver.sweeper = driver.spawn_sweeper()?;
+        driver.retention = driver.spawn_retention()?;
 
         Ok(driver)
     }
 
-    /// Prepares the SQLite database by creating the table if it doesn't already exist.
+    /// Creates a driver for a new database in a uniquely named file of the
+    /// system's temporary directory, which is deleted when the driver is
+    /// dropped. Intended for tests, which then don't leave database files in
+    /// the working directory or share data with each other.
+    ///
+    /// # Returns
+    /// A `Result` containing either the `SQLiteDriver` instance or an error.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::temp().unwrap();
+    /// driver.set("user.coins", 100).unwrap();
+    ///
+    /// let path = std::path::PathBuf::from(&driver.name);
+    /// assert!(path.exists());
+    ///
+    /// drop(driver);
+    /// assert!(!path.exists());
+    /// ```
+    pub fn temp() -> Result<Self> {
+        Self::temp_with(SQLiteDriverOptions::default())
+    }
+
+    /// Creates a driver for a new database in a temporary file, as
+    /// [`SQLiteDriver::temp`] does, with the given options.
     ///
     /// # Parameters
-    /// - `table`: The name of the table to create.
+    /// - `options`: The options of the driver, whose file name and open mode
+    ///   are replaced.
     ///
     /// # Returns
-    /// A `Result` indicating success or failure.
-    pub fn prepare(&self, table: &str) -> Result<()> {
-        self.database.execute(
-            &format!(
-                "CREATE TABLE IF NOT EXISTS {} (ID TEXT PRIMARY KEY, JSON TEXT)",
-                table
-            ),
-            [],
-        )?;
-        Ok(())
+    /// A `Result` containing either the `SQLiteDriver` instance or an error.
+    pub fn temp_with(options: SQLiteDriverOptions) -> Result<Self> {
+        let path = std::env::temp_dir().join(format!("chromoe-db-{}.sqlite", lock_token()));
+        let mut driver = Self::new(Some(SQLiteDriverOptions {
+            file_name: path.to_string_lossy().into_owned(),
+            open_mode: SQLiteOpenMode::ReadWriteCreate,
+            ..options
+        }))?;
+        driver.temp_file = Some(path);
+        Ok(driver)
     }
 
-    /// Adds a value to an existing entry or creates a new entry if it doesn't exist.
-    /// The value is added to the current value of the entry (if it exists).
+    /// Creates a new instance of the `SQLiteDriver` from a connection string,
+    /// such as `sqlite://data/json.sqlite?table=economy&mode=rwc&wal=true`.
+    ///
+    /// See [`SQLiteDriverOptions::from_uri`] for the supported parameters.
     ///
     /// # Parameters
-    /// - `key`: The key for the entry to update.
-    /// - `value`: The value to add to the current entry.
+    /// - `uri`: The connection string describing the database.
     ///
     /// # Returns
-    /// The new value after adding `value` to the existing entry, or an error if
-    /// the value is not finite (e.g., NaN or infinity).
-    pub fn add(&self, key: &str, value: f64) -> Result<f64> {
-        let current_value: f64 = self.get(key)?.unwrap_or(0.0);
-
-        if !current_value.is_finite() {
-            return Err(RusqliteError::ToSqlConversionFailure(Box::new(
-                std::io::Error::new(std::io::ErrorKind::InvalidData, "Non-finite value"),
-            )));
-        }
-
-        let new_value = current_value + value;
-        self.set(key, new_value)?;
-        Ok(new_value)
+    /// A `Result` containing either the `SQLiteDriver` instance or an error if the
+    /// connection string is invalid or the database cannot be opened.
+    pub fn from_uri(uri: &str) -> Result<Self> {
+        Self::new(Some(SQLiteDriverOptions::from_uri(uri)?))
     }
 
-    /// Retrieves all data entries from the database as a vector of tuples.
+    /// Opens a connection to the database described by `options`.
+    ///
+    /// # Parameters
+    /// - `options`: The options describing the database file and open mode.
     ///
     /// # Returns
-    /// A `Result` containing a vector of tuples where each tuple consists of
-    /// a key (`String`) and a corresponding value (`serde_json::Value`).
-    pub fn all(&self) -> Result<Vec<(String, Value)>> {
-        let mut stmt = self
-            .database
-            .prepare(&format!("SELECT * FROM {}", self.table))?;
-        let rows = stmt.query_map([], |row| {
-            let id: String = row.get(0)?;
-            let json_str: String = row.get(1)?;
-            let json: Value = from_str(&json_str).unwrap_or(Value::Null);
-            Ok((id, json))
-        })?;
+    /// A `Result` containing the opened connection.
+    fn open(options: &SQLiteDriverOptions) -> Result<Connection> {
+        let database = match options.open_mode {
+            SQLiteOpenMode::Memory => Connection::open_in_memory()?,
+            SQLiteOpenMode::ReadOnly => Connection::open_with_flags(
+                &options.file_name,
+                OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )?,
+            SQLiteOpenMode::ReadWrite => Connection::open_with_flags(
+                &options.file_name,
+                OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+            )?,
+            SQLiteOpenMode::ReadWriteCreate => Connection::open(&options.file_name)?,
+        };
 
-        let mut data = Vec::new();
-        for row in rows {
-            let (id, value) = row?;
-            data.push((id, value));
+        if let Some(timeout) = options.timeout {
+            if timeout > MAX_TIMEOUT {
+                return Err(ChromoeError::Config(format!(
+                    "the timeout must be at most {:?}, found {:?}",
+                    MAX_TIMEOUT, timeout
+                )));
+            }
+            database.busy_timeout(timeout)?;
+        }
+
+        if options.wal {
+            database.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+        }
+
+        if let Some(pages) = options.auto_checkpoint {
+            database.query_row(
+                &format!("PRAGMA wal_autocheckpoint = {}", pages),
+                [],
+                |_| Ok(()),
+            )?;
+        }
+
+        // The checksum triggers of a table call this function, so it is
+        // installed on every connection, whether it verifies checksums or not.
+        database.create_scalar_function(
+            "chromoe_checksum",
+            1,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            |ctx| Ok(ctx.get::<Option<String>>(0)?.map(|json| checksum(&json))),
+        )?;
+
+        if options.sql_log.is_some() {
+            sql_log::install(&database);
         }
 
-        Ok(data)
+        Ok(database)
     }
 
-    /// Deletes a specific entry by key. If the key refers to a nested value,
-    /// it will remove the nested field within the JSON data.
+    /// Runs an operation, reporting its name, key, duration and the number of
+    /// rows it changed to the metrics sinks and, when the `tracing` feature is
+    /// enabled, as a tracing span and event.
+    ///
+    /// Operations built on other operations (such as `add`, which reads and then
+    /// sets a value) are reported once, under the outermost operation's name.
     ///
     /// # Parameters
-    /// - `key`: The key of the entry to delete.
+    /// - `operation`: The name of the operation.
+    /// - `key`: The key the operation acts on, if any.
+    /// - `run`: The operation itself.
     ///
     /// # Returns
-    /// A `Result` indicating whether the deletion was successful.
-    pub fn delete(&self, key: &str) -> Result<bool> {
-        if key.contains('.') {
-            let split: Vec<&str> = key.split('.').collect();
-            let mut obj: Value = self.get(split[0])?.unwrap_or(Value::Null);
-            obj.as_object_mut().map(|obj| obj.remove(split[1]));
-            self.set(split[0], obj)?;
-            return Ok(true);
+    /// The result of `run`.
+    fn instrument<T, F>(&self, operation: &'static str, key: Option<&str>, run: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        let depth = self.depth.get();
+        if depth > 0 {
+            return run();
         }
 
-        self.delete_row_key(key)?;
-        Ok(true)
+        let timeout = self.timeout();
+        if let Some(timeout) = timeout {
+            self.start_deadline(timeout)?;
+        }
+        let result = self.instrument_inner(operation, key, run);
+        if timeout.is_some() {
+            self.clear_deadline()?;
+        }
+
+        result.map_err(|err| match (timeout, err) {
+            (Some(timeout), ChromoeError::Sqlite(err)) if is_timeout(&err) => {
+                ChromoeError::Timeout(format!(
+                    "`{}` did not complete within {:?}",
+                    operation, timeout
+                ))
+            }
+            (_, err) => err,
+        })
     }
 
-    /// Deletes all entries in the database.
+    /// Runs an operation for `instrument` once its deadline is set, logging
+    /// its statements if the `sql_log` option is enabled.
+    fn instrument_inner<T, F>(
+        &self,
+        operation: &'static str,
+        key: Option<&str>,
+        run: F,
+    ) -> Result<T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        if let Some(log) = self
+            .options
+            .sql_log
+            .as_ref()
+            .filter(|_| self.sql_logging.get())
+        {
+            return sql_log::scope(log.on_statement.clone(), || {
+                self.report(operation, key, run)
+            });
+        }
+        self.report(operation, key, run)
+    }
+
+    /// Runs an operation for `instrument_inner` once its statements are
+    /// logged, flushing the write buffer and reporting the operation.
+    fn report<T, F>(&self, operation: &'static str, key: Option<&str>, run: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        if self.options.write_buffer.is_some() {
+            // Operations that scan the table must observe buffered writes, while
+            // operations on individual keys read through the buffer.
+            if KEYED_OPERATIONS.contains(&operation) {
+                self.flush_if_due()?;
+            } else {
+                self.flush_buffer()?;
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        let span =
+            tracing::debug_span!("chromoe_db", operation, key, table = %self.table).entered();
+        let (started, changes) = (Instant::now(), self.database.total_changes());
+
+        let result = {
+            let _depth = DepthGuard::enter(&self.depth);
+            run()
+        };
+
+        let event = OperationEvent {
+            operation,
+            table: &self.table,
+            key,
+            duration: started.elapsed(),
+            rows_affected: self.database.total_changes() - changes,
+            success: result.is_ok(),
+        };
+
+        self.metrics.record(&event);
+        for sink in &self.metrics_sinks {
+            sink.record(&event);
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            let elapsed_ms = event.duration.as_secs_f64() * 1000.0;
+            let rows_affected = event.rows_affected;
+
+            match &result {
+                Ok(_) => tracing::debug!(elapsed_ms, rows_affected, "operation completed"),
+                Err(err) => {
+                    tracing::debug!(elapsed_ms, rows_affected, error = %err, "operation failed")
+                }
+            }
+
+            if let Some(threshold) = self.options.slow_operation_threshold {
+                if event.duration >= threshold {
+                    tracing::warn!(
+                        elapsed_ms,
+                        rows_affected,
+                        threshold_ms = threshold.as_secs_f64() * 1000.0,
+                        "slow operation"
+                    );
+                }
+            }
+
+            drop(span);
+        }
+
+        result
+    }
+
+    /// Returns the timeout applying to the next operation, if any.
+    fn timeout(&self) -> Option<Duration> {
+        self.timeout_override.get().or(self.options.timeout)
+    }
+
+    /// Bounds lock waits by `timeout` and interrupts statements still running
+    /// once it has elapsed.
     ///
-    /// # Returns
-    /// A `Result` indicating whether the deletion was successful.
-    pub fn delete_all(&self) -> Result<bool> {
-        self.delete_rows()
+    /// Timeouts longer than SQLite accepts wait for locks as long as it allows,
+    /// and a deadline too far in the future to represent is never reached.
+    fn start_deadline(&self, timeout: Duration) -> Result<()> {
+        self.database.busy_timeout(timeout.min(MAX_TIMEOUT))?;
+
+        if let Some(deadline) = Instant::now().checked_add(timeout) {
+            self.database.progress_handler(
+                DEADLINE_CHECK_INTERVAL,
+                Some(move || Instant::now() >= deadline),
+            );
+        }
+        Ok(())
     }
 
-    /// Deletes a specific row from the table by key.
+    /// Removes the deadline set by `start_deadline`, restoring the configured
+    /// lock wait.
+    fn clear_deadline(&self) -> Result<()> {
+        self.database.progress_handler(0, None::<fn() -> bool>);
+        self.database
+            .busy_timeout(self.options.timeout.unwrap_or(DEFAULT_BUSY_TIMEOUT))?;
+        Ok(())
+    }
+
+    /// Returns a handle to the driver whose operations time out after `timeout`,
+    /// overriding the `timeout` option.
+    ///
+    /// Operations exceeding the timeout, including while waiting for another
+    /// connection to release a lock, are interrupted and return a
+    /// `ChromoeError::Timeout`. Interrupted writes are rolled back. The override
+    /// ends when the handle is dropped, so it can be applied to a single call.
+    /// Lock waits are capped at `i32::MAX` milliseconds, the longest SQLite
+    /// accepts.
     ///
     /// # Parameters
-    /// - `key`: The key of the entry to delete.
+    /// - `timeout`: How long each operation may take.
     ///
     /// # Returns
-    /// A `Result` indicating whether the deletion was successful.
-    fn delete_row_key(&self, key: &str) -> Result<bool> {
-        self.database
-            .prepare(&format!("DELETE FROM {} WHERE ID = ?", self.table))?
-            .execute(params![key])?;
-        Ok(true)
+    /// A handle dereferencing to the driver.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::{SQLiteDriverOptions, SQLiteOpenMode};
+    ///
+    /// let driver = SQLiteDriver::new(Some(SQLiteDriverOptions {
+    ///     open_mode: SQLiteOpenMode::Memory,
+    ///     ..Default::default()
+    /// }))
+    /// .unwrap();
+    ///
+    /// driver.with_timeout(Duration::from_millis(250)).set("coins", 100).unwrap();
+    /// let coins: Option<u64> = driver.with_timeout(Duration::from_millis(250)).get("coins").unwrap();
+    /// assert_eq!(coins, Some(100));
+    /// ```
+    pub fn with_timeout(&self, timeout: Duration) -> WithTimeout<'_> {
+        WithTimeout {
+            driver: self,
+            previous: self.timeout_override.replace(Some(timeout)),
+        }
     }
 
-    /// Deletes all rows from the table.
+    /// Switches the logging of the SQL statements run by the driver on or off,
+    /// such as to log the statements of a single command while debugging it.
+    /// Without the `sql_log` option this does nothing.
+    ///
+    /// # Parameters
+    /// - `enabled`: Whether statements are logged.
+    pub fn set_sql_logging(&self, enabled: bool) {
+        self.sql_logging.set(enabled);
+    }
+
+    /// Returns whether the SQL statements run by the driver are logged.
+    pub fn sql_logging(&self) -> bool {
+        self.options.sql_log.is_some() && self.sql_logging.get()
+    }
+
+    /// Returns the operation counters and latency histograms recorded by this
+    /// driver's built-in in-memory recorder, keyed by operation name.
     ///
     /// # Returns
-    /// A `Result` indicating whether the deletion was successful.
-    fn delete_rows(&self) -> Result<bool> {
-        self.database
-            .prepare(&format!("DELETE FROM {}", self.table))?
-            .execute([])?;
-        Ok(true)
+    /// A snapshot of the metrics recorded since the driver was created or the
+    /// metrics were last reset.
+    pub fn metrics(&self) -> BTreeMap<String, OperationMetrics> {
+        self.metrics.snapshot()
     }
 
-    /// Retrieves the value for a given key, potentially deserialising it into the specified type.
+    /// Discards the metrics recorded by the built-in in-memory recorder.
+    pub fn reset_metrics(&self) {
+        self.metrics.reset();
+    }
+
+    /// Registers a sink that is called for every completed operation, in
+    /// addition to the built-in in-memory recorder.
     ///
     /// # Parameters
-    /// - `key`: The key of the entry to retrieve.
+    /// - `sink`: The sink to notify, such as an exporter for Prometheus.
+    pub fn add_metrics_sink(&mut self, sink: Arc<dyn MetricsSink>) {
+        self.metrics_sinks.push(sink);
+    }
+
+    /// Executes a raw SQL statement against the database, such as a schema change
+    /// or a bulk update the driver doesn't provide.
+    ///
+    /// Occurrences of `{table}` in `sql` are replaced with the driver's table name.
+    /// Values should always be passed through `params` rather than formatted into
+    /// the statement.
+    ///
+    /// # Parameters
+    /// - `sql`: The SQL statement to execute.
+    /// - `params`: The values bound to the statement's placeholders.
     ///
     /// # Returns
-    /// A `Result` containing an `Option` of the deserialised value, or an error if the
-    /// deserialisation fails.
-    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    /// A `Result` containing the number of rows changed by the statement.
+    pub fn execute_raw<P>(&self, sql: &str, params: P) -> Result<usize>
     where
-        T: DeserializeOwned + Default,
+        P: Params,
     {
-        if key.contains('.') {
-            let split: Vec<&str> = key.split('.').collect();
-            let val: Value = self.get_row_key(split[0])?.unwrap_or_default();
-            let nested_value = val.pointer(&format!("/{}", split[1])).cloned();
-            Ok(nested_value.map(|v| from_str(&v.to_string()).unwrap_or_default()))
-        } else {
-            self.get_row_key(key)
-        }
+        self.instrument("execute_raw", None, || {
+            Ok(self
+                .database
+                .execute(&sql.replace("{table}", &self.table), params)?)
+        })
     }
 
-    /// Retrieves a value for a key, directly from the row.
+    /// Runs a raw SQL query against the database and returns every resulting row.
+    ///
+    /// Occurrences of `{table}` in `sql` are replaced with the driver's table name.
+    /// Column values are converted to JSON: `NULL` becomes `null`, integers and
+    /// reals become numbers, text becomes a string and blobs become arrays of bytes.
     ///
     /// # Parameters
-    /// - `key`: The key of the entry to retrieve.
+    /// - `sql`: The SQL query to run.
+    /// - `params`: The values bound to the query's placeholders.
     ///
     /// # Returns
-    /// A `Result` containing the deserialised value, or `None` if the key doesn't exist.
-    fn get_row_key<T>(&self, key: &str) -> Result<Option<T>>
+    /// A `Result` containing the rows produced by the query.
+    pub fn query_raw<P>(&self, sql: &str, params: P) -> Result<Vec<RawRow>>
     where
-        T: DeserializeOwned,
+        P: Params,
     {
-        let mut stmt = self
-            .database
-            .prepare(&format!("SELECT JSON FROM {} WHERE ID = ?", self.table))?;
+        self.instrument("query_raw", None, || {
+            let mut stmt = self
+                .database
+                .prepare(&sql.replace("{table}", &self.table))?;
+            let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
 
-        let row = stmt
-            .query_row(params![key], |row| row.get::<_, String>(0))
-            .optional()?;
+            let rows = stmt.query_map(params, |row| {
+                let mut values = Vec::with_capacity(columns.len());
+                for i in 0..columns.len() {
+                    values.push(sql_to_json(row.get_ref(i)?));
+                }
+                Ok(RawRow {
+                    columns: columns.clone(),
+                    values,
+                })
+            })?;
 
-        if let Some(json_str) = row {
-            let json: Option<T> = from_str(&json_str).ok();
-            Ok(json)
-        } else {
-            Ok(None)
-        }
+            Ok(rows.collect::<rusqlite::Result<Vec<RawRow>>>()?)
+        })
     }
 
-    /// Checks if a given key exists in the database.
+    /// Returns the plan SQLite chooses for a raw SQL query, as given by
+    /// `EXPLAIN QUERY PLAN`, such as to check that a query uses an index on a
+    /// JSON field.
+    ///
+    /// Occurrences of `{table}` in `sql` are replaced with the driver's table
+    /// name. The query itself is not run.
     ///
     /// # Parameters
-    /// - `key`: The key to check.
+    /// - `sql`: The SQL query to explain.
+    /// - `params`: The values bound to the query's placeholders.
     ///
     /// # Returns
-    /// A `Result` containing a boolean indicating whether the key exists.
-    pub fn has(&self, key: &str) -> Result<bool> {
-        Ok(self.get::<Value>(key)?.is_some())
+    /// A `Result` containing the steps of the plan, in order.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver
+    ///     .execute_raw("CREATE INDEX json_level ON {table} (json_extract(JSON, '$.level'))", [])
+    ///     .unwrap();
+    ///
+    /// let plan = driver
+    ///     .explain("SELECT ID FROM {table} WHERE json_extract(JSON, '$.level') > ?", [10])
+    ///     .unwrap();
+    /// assert!(plan[0].detail.contains("USING INDEX json_level"));
+    /// ```
+    pub fn explain<P>(&self, sql: &str, params: P) -> Result<Vec<QueryPlanStep>>
+    where
+        P: Params,
+    {
+        self.instrument("explain", None, || {
+            let mut stmt = self.database.prepare(&format!(
+                "EXPLAIN QUERY PLAN {}",
+                sql.replace("{table}", &self.table)
+            ))?;
+            let rows = stmt.query_map(params, |row| {
+                Ok(QueryPlanStep {
+                    id: row.get(0)?,
+                    parent: row.get(1)?,
+                    detail: row.get(3)?,
+                })
+            })?;
+
+            Ok(rows.collect::<rusqlite::Result<Vec<QueryPlanStep>>>()?)
+        })
     }
 
-    /// Removes a specific value from an array stored at the given key.
+    /// Runs a closure with direct access to the underlying SQLite connection,
+    /// for advanced uses such as registering hooks or preparing custom statements.
+    ///
+    /// Prefer this over accessing `database` directly, as it keeps working when
+    /// the driver manages its connections differently.
     ///
     /// # Parameters
-    /// - `key`: The key of the entry where the array is stored.
-    /// - `value`: The value to remove from the array.
+    /// - `run`: The closure receiving the connection.
     ///
     /// # Returns
-    /// A `Result` containing the updated array after removal.
-    pub fn pull<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    /// A `Result` containing the closure's return value.
+    pub fn with_connection<T, F>(&self, run: F) -> Result<T>
     where
-        T: DeserializeOwned + std::cmp::PartialEq + Clone + Serialize,
+        F: FnOnce(&Connection) -> Result<T>,
     {
-        let mut arr: Vec<T> = self.get(key)?.unwrap_or_default();
-
-        arr.retain(|x| x != &value);
-
-        self.set(key, arr.clone())?;
-
-        Ok(arr)
+        self.instrument("with_connection", None, || run(&self.database))
     }
 
-    /// Appends a value to an array stored at the given key.
+    /// Runs `run` inside an immediate transaction, so that the reads and writes
+    /// it performs cannot interleave with writers on other connections. When a
+    /// transaction is already open, `run` simply joins it.
     ///
     /// # Parameters
-    /// - `key`: The key of the entry where the array is stored.
-    /// - `value`: The value to append to the array.
+    /// - `run`: The operations to perform atomically.
     ///
     /// # Returns
-    /// A `Result` containing the updated array after the value is appended.
-    pub fn push<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    /// The result of `run`. The transaction is committed if `run` succeeds and
+    /// rolled back otherwise.
+    pub(crate) fn atomic<T, F>(&self, run: F) -> Result<T>
     where
-        T: DeserializeOwned + Clone + Serialize,
+        F: FnOnce() -> Result<T>,
     {
-        let mut arr: Vec<T> = self.get(key)?.unwrap_or_default();
+        self.atomic_with(TransactionMode::Immediate, run)
+    }
 
-        arr.push(value);
+    /// Runs operations in a transaction started in the given mode, unless a
+    /// transaction is already open.
+    fn atomic_with<T, F>(&self, mode: TransactionMode, run: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        if !self.database.is_autocommit() {
+            return run();
+        }
 
-        self.set(key, arr.clone())?;
+        // Writes made in the transaction bypass the buffer, so they are rolled
+        // back with it.
+        self.flush_buffer()?;
+        let behavior = match mode {
+            TransactionMode::Deferred => TransactionBehavior::Deferred,
+            TransactionMode::Immediate => TransactionBehavior::Immediate,
+            TransactionMode::Exclusive => TransactionBehavior::Exclusive,
+        };
+        let tx = Transaction::new_unchecked(&self.database, behavior)?;
+        let value = run()?;
+        tx.commit()?;
+        Ok(value)
+    }
 
-        Ok(arr)
+    /// Runs operations in a savepoint, rolling back to it if they fail.
+    pub(crate) fn savepoint<T, F>(&self, run: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        self.database.execute_batch("SAVEPOINT chromoe_db")?;
+        match run() {
+            Ok(value) => {
+                self.database.execute_batch("RELEASE chromoe_db")?;
+                Ok(value)
+            }
+            Err(err) => {
+                self.database
+                    .execute_batch("ROLLBACK TO chromoe_db; RELEASE chromoe_db")?;
+                Err(err)
+            }
+        }
     }
 
-    /// Sets or updates the value for a given key in the database.
+    /// Runs operations in a transaction, committed if they succeed and rolled
+    /// back if they fail.
+    ///
+    /// The operations run on the [`TransactionScope`] passed to `run`, which
+    /// dereferences to this driver and can run parts of the transaction in
+    /// savepoints. Called within a transaction, the operations run in a
+    /// savepoint of it. Writes made in a transaction are not buffered.
     ///
     /// # Parameters
-    /// - `key`: The key for the entry.
-    /// - `value`: The value to store, which will be serialised into JSON.
+    /// - `run`: The operations to run in the transaction.
     ///
     /// # Returns
-    /// A `Result` containing the value that was set.
-    pub fn set<T>(&self, key: &str, value: T) -> Result<()>
+    /// A `Result` containing the value returned by `run`, or its error once
+    /// the transaction is rolled back.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("alice.coins", 100).unwrap();
+    ///
+    /// driver
+    ///     .with_transaction(|tx| {
+    ///         tx.subtract("alice.coins", 30.0)?;
+    ///         tx.add("bob.coins", 30.0)?;
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(driver.get::<f64>("bob.coins").unwrap(), Some(30.0));
+    /// ```
+    pub fn with_transaction<T, F>(&self, run: F) -> Result<T>
     where
-        T: Serialize,
+        F: FnOnce(&TransactionScope<'_>) -> Result<T>,
     {
-        let parts: Vec<&str> = key.split('.').collect();
-        let root_key = parts[0];
-
-        let mut root_value: Value = self.get(root_key)?.unwrap_or_else(|| json!({}));
-
-        let mut current = &mut root_value;
-        for part in &parts[1..] {
-            current = current
-                .as_object_mut()
-                .unwrap()
-                .entry(part.to_string())
-                .or_insert(json!({}));
-        }
-        *current = json!(value);
-
-        let json_string = to_string(&root_value)
-            .map_err(|e: SerdeJsonError| RusqliteError::ToSqlConversionFailure(Box::new(e)))?;
-        self.database
-            .prepare(&format!(
-                "INSERT INTO {} (ID, JSON) VALUES (?, ?) ON CONFLICT(ID) DO UPDATE SET JSON = ?",
-                self.table
-            ))?
-            .execute(params![root_key, json_string, json_string])?;
-
-        Ok(())
+        self.with_transaction_mode(TransactionMode::Immediate, run)
     }
 
-    /// Subtracts a value from an existing entry. If the entry does not exist,
-    /// it initialises it with the result.
+    /// Runs operations in a transaction started in the given mode, as
+    /// described for [`SQLiteDriver::with_transaction`], which starts
+    /// transactions in `TransactionMode::Immediate`.
+    ///
+    /// Called within a transaction, the operations run in a savepoint of it
+    /// and the mode is ignored.
     ///
     /// # Parameters
-    /// - `key`: The key of the entry to subtract from.
-    /// - `value`: The value to subtract from the current value.
+    /// - `mode`: How the transaction locks the database.
+    /// - `run`: The operations to run in the transaction.
     ///
     /// # Returns
-    /// The new value after subtraction.
-    pub fn subtract(&self, key: &str, value: f64) -> Result<f64> {
-        let current_value: f64 = self.get(key)?.unwrap_or(0.0);
-
-        if !current_value.is_finite() {
-            return Err(RusqliteError::ToSqlConversionFailure(Box::new(
-                std::io::Error::new(std::io::ErrorKind::InvalidData, "Non-finite value"),
-            )));
+    /// A `Result` containing the value returned by `run`, or its error once
+    /// the transaction is rolled back.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::TransactionMode;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("a", 1).unwrap();
+    /// driver.set("b", 2).unwrap();
+    ///
+    /// let total = driver
+    ///     .with_transaction_mode(TransactionMode::Deferred, |tx| {
+    ///         Ok(tx.get::<i64>("a")?.unwrap_or(0) + tx.get::<i64>("b")?.unwrap_or(0))
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(total, 3);
+    /// ```
+    pub fn with_transaction_mode<T, F>(&self, mode: TransactionMode, run: F) -> Result<T>
+    where
+        F: FnOnce(&TransactionScope<'_>) -> Result<T>,
+    {
+        let scope = TransactionScope::new(self);
+        if self.database.is_autocommit() {
+            self.atomic_with(mode, || run(&scope))
+        } else {
+            self.savepoint(|| run(&scope))
         }
+    }
+
+    /// Checks that the database connection is alive and the table is readable.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the database responded.
+    pub fn ping(&self) -> Result<()> {
+        self.database.query_row(
+            &format!("SELECT COUNT(*) FROM (SELECT 1 FROM {} LIMIT 1)", self.table),
+            [],
+            |_| Ok(()),
+        )?;
+        Ok(())
+    }
+
+    /// Closes the current connection and opens a new one using the driver's options.
+    ///
+    /// This is useful when the database file was replaced or the connection was
+    /// left in an unusable state. In-memory databases start empty again.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the connection was re-established.
+    pub fn reconnect(&mut self) -> Result<()> {
+        self.database = Self::open(&self.options)?;
+        self.restore_connection_state()?;
+        self.initialize()
+    }
+
+    /// Applies the options stored for the table, then creates the table and
+    /// the tables and triggers of the enabled options if they don't exist.
+    fn initialize(&mut self) -> Result<()> {
+        self.migrate()?;
+        if let Some(options) = self.read_table_options()? {
+            self.apply_table_options(&options);
+        }
+        if self.options.open_mode != SQLiteOpenMode::ReadOnly {
+            self.prepare(&self.table)?;
+            if self.options.change_log {
+                self.prepare_change_log()?;
+            }
+            if self.options.expiry.is_some() {
+                self.prepare_expiry()?;
+            }
+            if self.options.checksums.is_some() {
+                self.prepare_checksums()?;
+            }
+            if self.tracks_access() {
+                self.prepare_access_times()?;
+            }
+            for trigger in self.read_triggers()? {
+                self.create_trigger_sql(&trigger, true)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the schema and the name of the driver's table.
+    fn schema_and_table(&self) -> (&str, &str) {
+        self.table
+            .split_once('.')
+            .unwrap_or(("main", self.table.as_str()))
+    }
+
+    /// Upgrades the database holding the table to [`SCHEMA_VERSION`], creating
+    /// its `chromoe_meta` table if it doesn't exist.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the database is up to date, or a `Config`
+    /// error if it was written by a newer version of the crate.
+    fn migrate(&self) -> Result<()> {
+        let (schema, _) = self.schema_and_table();
+        let read_only = self.options.open_mode == SQLiteOpenMode::ReadOnly;
+        if !read_only {
+            self.database.execute_batch(&format!(
+                "CREATE TABLE IF NOT EXISTS {}.chromoe_meta \
+                 (KEY TEXT PRIMARY KEY, VALUE TEXT NOT NULL)",
+                schema
+            ))?;
+        }
+
+        let check = |version: u32| {
+            if version > SCHEMA_VERSION {
+                return Err(ChromoeError::Config(format!(
+                    "the database uses schema version {}, newer than the supported version {}",
+                    version, SCHEMA_VERSION
+                )));
+            }
+            Ok(version == SCHEMA_VERSION || read_only)
+        };
+        if check(self.read_schema_version()?)? {
+            return Ok(());
+        }
+
+        // Checked again in the transaction, in case another connection
+        // migrated the database in the meantime.
+        self.atomic(|| {
+            let version = self.read_schema_version()?;
+            if check(version)? {
+                return Ok(());
+            }
+            for migration in &MIGRATIONS[version as usize..] {
+                migration(&self.database, schema)?;
+            }
+            self.write_meta("schema_version", &SCHEMA_VERSION.to_string())
+        })
+    }
+
+    /// Returns the version of the layout of the database holding the table.
+    ///
+    /// # Returns
+    /// A `Result` containing the schema version, which is [`SCHEMA_VERSION`]
+    /// once the database was opened for writing by this version of the crate.
+    pub fn schema_version(&self) -> Result<u32> {
+        self.instrument("schema_version", None, || self.read_schema_version())
+    }
+
+    /// Reads the schema version of the database holding the table, which is
+    /// `0` for databases that never had one recorded.
+    fn read_schema_version(&self) -> Result<u32> {
+        match self.read_meta("schema_version")? {
+            Some(version) => version
+                .parse()
+                .map_err(|_| ChromoeError::Config(format!("invalid schema version `{}`", version))),
+            None => Ok(0),
+        }
+    }
+
+    /// Reads a value of the `chromoe_meta` table of the database holding the
+    /// table, which may not exist in read-only databases.
+    pub(crate) fn read_meta(&self, key: &str) -> Result<Option<String>> {
+        let (schema, _) = self.schema_and_table();
+        match self.database.query_row(
+            &format!("SELECT VALUE FROM {}.chromoe_meta WHERE KEY = ?1", schema),
+            params![key],
+            |row| row.get(0),
+        ) {
+            Ok(value) => Ok(Some(value)),
+            Err(RusqliteError::QueryReturnedNoRows) => Ok(None),
+            Err(RusqliteError::SqliteFailure(_, Some(message)))
+                if message.starts_with("no such table") =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes a value of the `chromoe_meta` table of the database holding the
+    /// table.
+    pub(crate) fn write_meta(&self, key: &str, value: &str) -> Result<()> {
+        let (schema, _) = self.schema_and_table();
+        self.database.execute(
+            &format!(
+                "INSERT INTO {}.chromoe_meta (KEY, VALUE) VALUES (?1, ?2) \
+                 ON CONFLICT(KEY) DO UPDATE SET VALUE = excluded.VALUE",
+                schema
+            ),
+            params![key, value],
+        )?;
+        Ok(())
+    }
+
+    /// Reads the options stored for the table, if any.
+    fn read_table_options(&self) -> Result<Option<TableOptions>> {
+        let (_, table) = self.schema_and_table();
+        match self.read_meta(&format!("table_options:{}", table))? {
+            Some(stored) => from_str(&stored)
+                .map(Some)
+                .map_err(|source| ChromoeError::Corrupted {
+                    key: table.to_string(),
+                    source,
+                }),
+            None => Ok(None),
+        }
+    }
+
+    /// Overrides the driver's options with the options stored for the table.
+    fn apply_table_options(&mut self, options: &TableOptions) {
+        if options.change_log {
+            self.options.change_log = true;
+        }
+        if let Some(separator) = options.path_separator {
+            self.options.path_separator = separator;
+        }
+        if let Some(ttl) = options.default_ttl {
+            self.options
+                .expiry
+                .get_or_insert_with(ExpiryOptions::default)
+                .default_ttl = Some(ttl);
+        }
+    }
+
+    /// Returns the options stored for the driver's table with
+    /// [`SQLiteDriver::set_table_options`].
+    ///
+    /// # Returns
+    /// A `Result` containing the stored options, which are the defaults if
+    /// none were stored.
+    pub fn table_options(&self) -> Result<TableOptions> {
+        self.instrument("table_options", None, || {
+            Ok(self.read_table_options()?.unwrap_or_default())
+        })
+    }
+
+    /// Stores options for the driver's table in the `chromoe_meta` table of
+    /// its database, and applies them to this driver.
+    ///
+    /// The stored options override the options of every driver later opened
+    /// on the table, including handles opened with [`SQLiteDriver::table`],
+    /// so each table of a database can be configured once and keep its
+    /// configuration across restarts.
+    ///
+    /// # Parameters
+    /// - `options`: The options of the table, replacing the stored ones.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the options were stored.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::TableOptions;
+    ///
+    /// let driver = SQLiteDriver::temp().unwrap();
+    /// let mut sessions = driver.table("sessions").unwrap();
+    ///
+    /// sessions
+    ///     .set_table_options(TableOptions {
+    ///         default_ttl: Some(Duration::from_secs(3600)),
+    ///         ..Default::default()
+    ///     })
+    ///     .unwrap();
+    /// sessions.set("abc", "user_1").unwrap();
+    /// assert!(sessions.ttl("abc").unwrap().is_some());
+    ///
+    /// // Handles opened later carry the stored options.
+    /// let reopened = driver.table("sessions").unwrap();
+    /// assert_eq!(reopened.table_options().unwrap().default_ttl, Some(Duration::from_secs(3600)));
+    /// assert!(driver.table_options().unwrap().default_ttl.is_none());
+    /// ```
+    pub fn set_table_options(&mut self, options: TableOptions) -> Result<()> {
+        let (_, table) = self.schema_and_table();
+        self.write_meta(&format!("table_options:{}", table), &to_string(&options)?)?;
+
+        if let Some(expiry) = &mut self.options.expiry {
+            expiry.default_ttl = None;
+        }
+        self.apply_table_options(&options);
+        self.initialize()
+    }
+
+    /// Defines a trigger reacting to the changes of the rows of the driver's
+    /// table, such as to mirror them to a history table or to reject invalid
+    /// values.
+    ///
+    /// The definition is recorded in the `chromoe_meta` table, and triggers
+    /// that are missing, such as after the table was restored from a dump,
+    /// are created again by [`SQLiteDriver::restore_triggers`] and whenever a
+    /// driver is opened on the table. Defining a trigger that exists replaces
+    /// it.
+    ///
+    /// # Parameters
+    /// - `trigger`: The definition of the trigger.
+    ///
+    /// # Returns
+    /// A `Result` which is a `Config` error if the name is not a valid
+    /// identifier, the body is empty or the condition or body end the trigger
+    /// and add statements after it, or the error SQLite reports for an invalid
+    /// condition or body.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::{TriggerDefinition, TriggerEvent, TriggerTiming};
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver
+    ///     .execute_raw("CREATE TABLE history (ID TEXT, JSON TEXT)", [])
+    ///     .unwrap();
+    ///
+    /// driver
+    ///     .create_trigger(&TriggerDefinition {
+    ///         name: "history".to_string(),
+    ///         event: TriggerEvent::Update,
+    ///         body: "INSERT INTO history VALUES (OLD.ID, OLD.JSON);".to_string(),
+    ///         ..Default::default()
+    ///     })
+    ///     .unwrap();
+    /// driver
+    ///     .create_trigger(&TriggerDefinition {
+    ///         name: "no_negative_coins".to_string(),
+    ///         timing: TriggerTiming::Before,
+    ///         event: TriggerEvent::Update,
+    ///         condition: Some("json_extract(NEW.JSON, '$.coins') < 0".to_string()),
+    ///         body: "SELECT RAISE(ABORT, 'coins cannot be negative');".to_string(),
+    ///     })
+    ///     .unwrap();
+    ///
+    /// driver.set("alice", json!({ "coins": 10 })).unwrap();
+    /// driver.set("alice", json!({ "coins": 5 })).unwrap();
+    /// assert!(driver.set("alice", json!({ "coins": -5 })).is_err());
+    ///
+    /// // A body can't end the trigger to run statements of its own.
+    /// assert!(driver
+    ///     .create_trigger(&TriggerDefinition {
+    ///         name: "sneaky".to_string(),
+    ///         body: "SELECT 1; END; DROP TABLE history; SELECT 1".to_string(),
+    ///         ..Default::default()
+    ///     })
+    ///     .is_err());
+    /// assert_eq!(driver.triggers().unwrap().len(), 2);
+    /// ```
+    pub fn create_trigger(&self, trigger: &TriggerDefinition) -> Result<()> {
+        if !is_valid_identifier(&trigger.name) {
+            return Err(ChromoeError::Config(format!(
+                "`{}` is not a valid trigger name",
+                trigger.name
+            )));
+        }
+        if trigger.body.trim().is_empty() {
+            return Err(ChromoeError::Config(format!(
+                "the body of the trigger `{}` is empty",
+                trigger.name
+            )));
+        }
+
+        self.instrument("create_trigger", None, || {
+            let (_, table) = self.schema_and_table();
+            self.atomic(|| {
+                self.drop_trigger_sql(&trigger.name)?;
+                self.create_trigger_sql(trigger, false)?;
+                self.write_meta(
+                    &format!("trigger:{}:{}", table, trigger.name),
+                    &to_string(trigger)?,
+                )
+            })
+        })
+    }
+
+    /// Drops a trigger defined with [`SQLiteDriver::create_trigger`], and its
+    /// recorded definition.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the trigger.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if the trigger was defined.
+    pub fn drop_trigger(&self, name: &str) -> Result<bool> {
+        self.instrument("drop_trigger", None, || {
+            let (schema, table) = self.schema_and_table();
+            self.atomic(|| {
+                self.drop_trigger_sql(name)?;
+                let deleted = self.database.execute(
+                    &format!("DELETE FROM {}.chromoe_meta WHERE KEY = ?1", schema),
+                    params![format!("trigger:{}:{}", table, name)],
+                )?;
+                Ok(deleted > 0)
+            })
+        })
+    }
+
+    /// Returns the triggers defined on the driver's table with
+    /// [`SQLiteDriver::create_trigger`].
+    ///
+    /// # Returns
+    /// A `Result` containing the recorded definitions, ordered by name.
+    pub fn triggers(&self) -> Result<Vec<TriggerDefinition>> {
+        self.instrument("triggers", None, || self.read_triggers())
+    }
+
+    /// Creates the triggers recorded for the driver's table that don't exist,
+    /// such as after the table was restored from a dump.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of recorded triggers.
+    pub fn restore_triggers(&self) -> Result<usize> {
+        self.instrument("restore_triggers", None, || {
+            let triggers = self.read_triggers()?;
+            self.atomic(|| {
+                for trigger in &triggers {
+                    self.create_trigger_sql(trigger, true)?;
+                }
+                Ok(triggers.len())
+            })
+        })
+    }
+
+    /// Reads the definitions of the triggers recorded for the driver's table.
+    fn read_triggers(&self) -> Result<Vec<TriggerDefinition>> {
+        let (schema, table) = self.schema_and_table();
+        let mut stmt = match self.database.prepare(&format!(
+            "SELECT KEY, VALUE FROM {}.chromoe_meta WHERE substr(KEY, 1, ?2) = ?1 ORDER BY KEY",
+            schema
+        )) {
+            Ok(stmt) => stmt,
+            // Read-only databases may have no metadata table.
+            Err(RusqliteError::SqliteFailure(_, Some(message)))
+                if message.starts_with("no such table") =>
+            {
+                return Ok(Vec::new());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        let prefix = format!("trigger:{}:", table);
+        let rows = stmt.query_map(params![prefix, prefix.len() as i64], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+
+        let mut triggers = Vec::new();
+        for row in rows {
+            let (key, definition) = row?;
+            triggers.push(
+                from_str(&definition).map_err(|source| ChromoeError::Corrupted { key, source })?,
+            );
+        }
+        Ok(triggers)
+    }
+
+    /// Creates the SQL trigger of a definition, named after the table and
+    /// the definition so it can't clash with the triggers of other tables.
+    fn create_trigger_sql(&self, trigger: &TriggerDefinition, if_not_exists: bool) -> Result<()> {
+        // Triggers may only name tables of their own database, so a table of an
+        // attached database is referred to without its alias in the trigger body.
+        let (schema, table) = self.schema_and_table();
+        let timing = match trigger.timing {
+            TriggerTiming::Before => "BEFORE",
+            TriggerTiming::After => "AFTER",
+        };
+        let event = match trigger.event {
+            TriggerEvent::Insert => "INSERT",
+            TriggerEvent::Update => "UPDATE",
+            TriggerEvent::Delete => "DELETE",
+        };
+        let condition = trigger
+            .condition
+            .as_ref()
+            .map(|condition| format!(" WHEN {}", condition))
+            .unwrap_or_default();
+        let body = trigger.body.trim();
+        let separator = if body.ends_with(';') { "" } else { ";" };
+
+        let sql = format!(
+            "CREATE TRIGGER {exists}{schema}.{table}_trigger_{name} {timing} {event} ON {table}{condition} \
+             BEGIN {body}{separator} END;",
+            exists = if if_not_exists { "IF NOT EXISTS " } else { "" },
+            name = trigger.name,
+        );
+
+        // The definition is replayed whenever a driver is opened on the table,
+        // so a condition or body closing the trigger early must not smuggle in
+        // statements of its own.
+        let mut statements = rusqlite::Batch::new(&self.database, &sql);
+        let statement = statements.next()?;
+        match (statement, statements.next()?) {
+            (Some(mut statement), None) => {
+                statement.execute([])?;
+                Ok(())
+            }
+            _ => Err(ChromoeError::Config(format!(
+                "the condition and body of the trigger `{}` must stay within the trigger",
+                trigger.name
+            ))),
+        }
+    }
+
+    /// Drops the SQL trigger of a definition, if it exists.
+    fn drop_trigger_sql(&self, name: &str) -> Result<()> {
+        let (schema, table) = self.schema_and_table();
+        self.database.execute_batch(&format!(
+            "DROP TRIGGER IF EXISTS {}.{}_trigger_{}",
+            schema, table, name
+        ))?;
+        Ok(())
+    }
+
+    /// Re-applies attached databases, functions and collations registered on this
+    /// driver to its current connection.
+    fn restore_connection_state(&self) -> Result<()> {
+        for (path, alias) in &self.attachments {
+            self.database
+                .execute(&format!("ATTACH DATABASE ?1 AS {}", alias), params![path])?;
+        }
+        for function in &self.functions {
+            self.install_function(function)?;
+        }
+        for (name, compare) in &self.collations {
+            let compare = Arc::clone(compare);
+            self.database
+                .create_collation(name, move |a, b| compare(a, b))?;
+        }
+        Ok(())
+    }
+
+    /// Attaches another SQLite database file to the connection under an alias.
+    ///
+    /// Tables in the attached database can then be used by table handles named
+    /// `alias.table` (see `table`) and copied with `copy_table_between`. This
+    /// allows, for example, one database file per guild while still doing
+    /// cross-file operations. Attachments survive `reconnect`.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the database file to attach, created if it doesn't exist.
+    /// - `alias`: The schema name under which the database is attached.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the database was attached.
+    pub fn attach(&mut self, path: &str, alias: &str) -> Result<()> {
+        if !is_valid_identifier(alias) {
+            return Err(ChromoeError::Config(format!(
+                "`{}` is not a valid database alias",
+                alias
+            )));
+        }
+
+        self.database
+            .execute(&format!("ATTACH DATABASE ?1 AS {}", alias), params![path])?;
+        self.attachments.push((path.to_string(), alias.to_string()));
+        Ok(())
+    }
+
+    /// Detaches a database previously attached with `attach`.
+    ///
+    /// # Parameters
+    /// - `alias`: The alias the database was attached under.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the database was detached.
+    pub fn detach(&mut self, alias: &str) -> Result<()> {
+        if !is_valid_identifier(alias) {
+            return Err(ChromoeError::Config(format!(
+                "`{}` is not a valid database alias",
+                alias
+            )));
+        }
+
+        self.database
+            .execute(&format!("DETACH DATABASE {}", alias), [])?;
+        self.attachments.retain(|(_, existing)| existing != alias);
+        Ok(())
+    }
+
+    /// Opens a handle to another table of the same database.
+    ///
+    /// The handle is a separate driver with its own connection to the database
+    /// file, carrying over this driver's options, attached databases, functions,
+    /// collations and metrics sinks. The table is created if it doesn't exist.
+    /// Tables of attached databases are referenced as `alias.table`.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the table, optionally qualified with a database alias.
+    ///
+    /// # Returns
+    /// A `Result` containing the driver for the table, or an error if the name is
+    /// invalid or the database is in-memory, since in-memory databases cannot be
+    /// shared between connections.
+    pub fn table(&self, name: &str) -> Result<SQLiteDriver> {
+        let valid = match name.split_once('.') {
+            Some((alias, table)) => is_valid_identifier(alias) && is_valid_identifier(table),
+            None => is_valid_identifier(name),
+        };
+        if !valid {
+            return Err(ChromoeError::Config(format!(
+                "`{}` is not a valid table name",
+                name
+            )));
+        }
+        if self.options.open_mode == SQLiteOpenMode::Memory || self.name == ":memory:" {
+            return Err(ChromoeError::Config(
+                "table handles require a file-backed database".to_string(),
+            ));
+        }
+
+        let options = SQLiteDriverOptions {
+            table_name: name.to_string(),
+            ..self.options.clone()
+        };
+
+        let mut driver = SQLiteDriver {
+            name: options.file_name.clone(),
+            table: options.table_name.clone(),
+            database: Self::open(&options)?,
+            options,
+            depth: Cell::new(0),
+            metrics: InMemoryMetrics::default(),
+            metrics_sinks: self.metrics_sinks.clone(),
+            functions: self.functions.clone(),
+            collations: self.collations.clone(),
+            attachments: self.attachments.clone(),
+            write_buffer: RefCell::new(WriteBuffer::default()),
+            timeout_override: Cell::new(None),
+            temp_file: None,
+            sweeper: None,
+            retention: None,
+            sql_logging: Cell::new(self.sql_logging.get()),
+        };
+
+        driver.restore_connection_state()?;
+        driver.initialize()?;
+        driver.sweeper = driver.spawn_sweeper()?;
+        driver.retention = driver.spawn_retention()?;
+
+        Ok(driver)
+    }
+
+    /// Copies every entry of this driver's table from one database to another,
+    /// replacing entries with the same key in the destination.
+    ///
+    /// The table name is taken from this driver without its database alias, and
+    /// the destination table is created if it doesn't exist. Use `main` to refer
+    /// to the database the driver was opened with.
+    ///
+    /// # Parameters
+    /// - `from`: The alias of the source database.
+    /// - `to`: The alias of the destination database.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries copied.
+    pub fn copy_table_between(&self, from: &str, to: &str) -> Result<usize> {
+        self.instrument("copy_table_between", None, || {
+            for alias in [from, to] {
+                if !is_valid_identifier(alias) {
+                    return Err(ChromoeError::Config(format!(
+                        "`{}` is not a valid database alias",
+                        alias
+                    )));
+                }
+            }
+
+            let table = self
+                .table
+                .rsplit_once('.')
+                .map_or(self.table.as_str(), |(_, table)| table);
+
+            self.atomic(|| {
+                self.prepare(&format!("{}.{}", to, table))?;
+                Ok(self.database.execute(
+                    &format!(
+                        "INSERT OR REPLACE INTO {to}.{table} (ID, JSON) SELECT ID, JSON FROM {from}.{table}",
+                        to = to,
+                        from = from,
+                        table = table
+                    ),
+                    [],
+                )?)
+            })
+        })
+    }
+
+    /// Registers a scalar SQL function on the connection, which can then be
+    /// called from queries such as `query_raw`, `execute_raw` or JSON1
+    /// expressions.
+    ///
+    /// Arguments are converted to JSON the same way as `query_raw` converts
+    /// column values. The returned JSON value is converted back to SQL: `null`
+    /// becomes `NULL`, booleans and integers become integers, other numbers become
+    /// reals, strings become text, and arrays and objects become JSON text.
+    /// Registered functions survive `reconnect`.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the function in SQL.
+    /// - `n_args`: The number of arguments the function takes, or `-1` for any number.
+    /// - `deterministic`: Whether the function always returns the same result for
+    ///   the same arguments, which allows SQLite to use it in indexes.
+    /// - `function`: The implementation of the function.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the function was registered.
+    pub fn register_function<F>(
+        &mut self,
+        name: &str,
+        n_args: i32,
+        deterministic: bool,
+        function: F,
+    ) -> Result<()>
+    where
+        F: Fn(&[Value]) -> Result<Value> + Send + Sync + 'static,
+    {
+        let function = UserFunction {
+            name: name.to_string(),
+            n_args,
+            deterministic,
+            run: Arc::new(function),
+        };
+        self.install_function(&function)?;
+        self.functions.retain(|existing| {
+            !(existing.name.eq_ignore_ascii_case(name) && existing.n_args == n_args)
+        });
+        self.functions.push(function);
+        Ok(())
+    }
+
+    /// Registers a collation on the connection, which can then be used in
+    /// `ORDER BY ... COLLATE name` clauses of raw queries. Registered collations
+    /// survive `reconnect`.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the collation in SQL.
+    /// - `compare`: Compares two strings.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the collation was registered.
+    pub fn register_collation<F>(&mut self, name: &str, compare: F) -> Result<()>
+    where
+        F: Fn(&str, &str) -> std::cmp::Ordering + Send + Sync + 'static,
+    {
+        let compare: Arc<CollationFn> = Arc::new(compare);
+        let registered = Arc::clone(&compare);
+        self.database
+            .create_collation(name, move |a, b| registered(a, b))?;
+        self.collations
+            .retain(|(existing, _)| !existing.eq_ignore_ascii_case(name));
+        self.collations.push((name.to_string(), compare));
+        Ok(())
+    }
+
+    /// Installs a user-defined function on the current connection.
+    fn install_function(&self, function: &UserFunction) -> Result<()> {
+        let mut flags = FunctionFlags::SQLITE_UTF8;
+        if function.deterministic {
+            flags |= FunctionFlags::SQLITE_DETERMINISTIC;
+        }
+
+        let run = Arc::clone(&function.run);
+        self.database.create_scalar_function(
+            &function.name,
+            function.n_args,
+            flags,
+            move |ctx| {
+                let args: Vec<Value> = (0..ctx.len())
+                    .map(|i| sql_to_json(ctx.get_raw(i)))
+                    .collect();
+                let result =
+                    run(&args).map_err(|e| RusqliteError::UserFunctionError(Box::new(e)))?;
+                Ok(json_to_sql(result))
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Rebuilds the database file, reclaiming the space left behind by deleted
+    /// entries and defragmenting the stored data.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the database was vacuumed.
+    pub fn vacuum(&self) -> Result<()> {
+        self.instrument("vacuum", None, || {
+            self.database.execute_batch("VACUUM")?;
+            Ok(())
+        })
+    }
+
+    /// Runs `PRAGMA optimize`, letting SQLite refresh the statistics it considers
+    /// stale. This is cheap and intended to be run periodically or before closing.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the optimisation succeeded.
+    pub fn optimize(&self) -> Result<()> {
+        self.instrument("optimize", None, || {
+            self.database.execute_batch("PRAGMA optimize")?;
+            Ok(())
+        })
+    }
+
+    /// Gathers statistics about the tables and indices used by the query planner.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the analysis succeeded.
+    pub fn analyze(&self) -> Result<()> {
+        self.instrument("analyze", None, || {
+            self.database.execute_batch("ANALYZE")?;
+            Ok(())
+        })
+    }
+
+    /// Checks the database file for corruption using `PRAGMA integrity_check`.
+    ///
+    /// # Returns
+    /// A `Result` containing an `IntegrityReport` listing every problem found.
+    pub fn integrity_check(&self) -> Result<IntegrityReport> {
+        self.instrument("integrity_check", None, || {
+            let mut stmt = self.database.prepare("PRAGMA integrity_check")?;
+            let messages = stmt
+                .query_map([], |row| row.get::<_, String>(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()?;
+
+            let problems: Vec<String> = messages.into_iter().filter(|m| m != "ok").collect();
+
+            Ok(IntegrityReport {
+                ok: problems.is_empty(),
+                problems,
+            })
+        })
+    }
+
+    /// Finds the rows whose stored JSON can no longer be parsed, which reads
+    /// report as `Corrupted` errors, or, when the `checksums` option is set,
+    /// doesn't match its checksum.
+    ///
+    /// # Returns
+    /// A `Result` containing every corrupted row, ordered by key.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::RepairStrategy;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("healthy", json!({ "coins": 5 })).unwrap();
+    /// driver.execute_raw("INSERT INTO json (ID, JSON) VALUES ('damaged', '{\"coins\": ')", []).unwrap();
+    ///
+    /// let corrupted = driver.scan_corrupted().unwrap();
+    /// assert_eq!(corrupted.len(), 1);
+    /// assert_eq!(corrupted[0].key, "damaged");
+    ///
+    /// assert!(driver.repair("damaged", RepairStrategy::Replace(json!({ "coins": 0 }))).unwrap());
+    /// assert_eq!(driver.get("damaged.coins").unwrap(), Some(json!(0)));
+    /// assert!(driver.scan_corrupted().unwrap().is_empty());
+    /// ```
+    pub fn scan_corrupted(&self) -> Result<Vec<CorruptedRow>> {
+        self.instrument("scan_corrupted", None, || {
+            let mut stmt = self.database.prepare(&format!(
+                "SELECT ID, JSON, {} FROM {} ORDER BY ID",
+                self.checksum_column(),
+                self.table
+            ))?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get(2)?,
+                ))
+            })?;
+
+            let mut corrupted = Vec::new();
+            for row in rows {
+                let (key, json, stored) = row?;
+                if let Some(error) = self.row_problem(&json, stored) {
+                    corrupted.push(CorruptedRow { key, json, error });
+                }
+            }
+            Ok(corrupted)
+        })
+    }
+
+    /// Repairs a row whose stored JSON can no longer be parsed, or doesn't
+    /// match its checksum.
+    ///
+    /// Rows holding intact JSON are left untouched, so a row found by
+    /// [`SQLiteDriver::scan_corrupted`] can be repaired safely after it was
+    /// overwritten in the meantime.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the corrupted row.
+    /// - `strategy`: Whether to delete the row, replace it with a value, or
+    ///   move it to the `<table>_quarantine` table.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if the row was corrupted and has been
+    /// repaired, or `false` if it doesn't exist or holds intact JSON.
+    pub fn repair(&self, key: &str, strategy: RepairStrategy) -> Result<bool> {
+        self.instrument("repair", Some(key), || {
+            self.atomic(|| {
+                let row: Option<(String, Option<i64>)> = self
+                    .database
+                    .query_row(
+                        &format!(
+                            "SELECT JSON, {} FROM {} WHERE ID = ?",
+                            self.checksum_column(),
+                            self.table
+                        ),
+                        params![key],
+                        |row| Ok((row.get(0)?, row.get(1)?)),
+                    )
+                    .optional()?;
+                let Some((json, err)) = row.and_then(|(json, stored)| {
+                    let err = self.row_problem(&json, stored)?;
+                    Some((json, err))
+                }) else {
+                    return Ok(false);
+                };
+
+                // The row is deleted directly, since buffered deletes read the
+                // corrupted value first.
+                let delete = format!("DELETE FROM {} WHERE ID = ?", self.table);
+                match strategy {
+                    RepairStrategy::Delete => {
+                        self.database.execute(&delete, params![key])?;
+                    }
+                    RepairStrategy::Replace(value) => {
+                        self.upsert_row_key(key, &to_string(&value)?)?;
+                    }
+                    RepairStrategy::Quarantine => {
+                        self.database.execute(
+                            &format!(
+                                "CREATE TABLE IF NOT EXISTS {}_quarantine \
+                                 (ID TEXT NOT NULL, JSON TEXT, ERROR TEXT NOT NULL, \
+                                 QUARANTINED_AT INTEGER NOT NULL)",
+                                self.table
+                            ),
+                            [],
+                        )?;
+                        self.database.execute(
+                            &format!(
+                                "INSERT INTO {}_quarantine (ID, JSON, ERROR, QUARANTINED_AT) \
+                                 VALUES (?1, ?2, ?3, ?4)",
+                                self.table
+                            ),
+                            params![key, json, err, unix_millis(SystemTime::now())],
+                        )?;
+                        self.database.execute(&delete, params![key])?;
+                    }
+                }
+                Ok(true)
+            })
+        })
+    }
+
+    /// Collects storage statistics for the database, reporting the ten largest keys.
+    ///
+    /// # Returns
+    /// A `Result` containing the collected `StorageStats`.
+    pub fn stats(&self) -> Result<StorageStats> {
+        self.stats_with_largest(10)
+    }
+
+    /// Collects storage statistics for the database, reporting the `n` largest keys.
+    ///
+    /// # Parameters
+    /// - `n`: The number of largest keys to include in the report.
+    ///
+    /// # Returns
+    /// A `Result` containing the collected `StorageStats`.
+    pub fn stats_with_largest(&self, n: usize) -> Result<StorageStats> {
+        let (row_count, total_value_bytes): (u64, u64) = self.database.query_row(
+            &format!(
+                "SELECT COUNT(*), COALESCE(SUM(LENGTH(CAST(JSON AS BLOB))), 0) FROM {}",
+                self.table
+            ),
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+
+        let page_count: u64 = self
+            .database
+            .query_row("PRAGMA page_count", [], |row| row.get(0))?;
+        let page_size: u64 = self
+            .database
+            .query_row("PRAGMA page_size", [], |row| row.get(0))?;
+
+        let mut stmt = self.database.prepare(
+            "SELECT name, SUM(pgsize) FROM dbstat WHERE aggregate = FALSE GROUP BY name ORDER BY 2 DESC",
+        )?;
+        let table_sizes = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<(String, u64)>>>()?;
+
+        let mut stmt = self.database.prepare(&format!(
+            "SELECT ID, LENGTH(CAST(JSON AS BLOB)) FROM {} ORDER BY 2 DESC LIMIT ?",
+            self.table
+        ))?;
+        let largest_keys = stmt
+            .query_map(params![n as i64], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<(String, u64)>>>()?;
+
+        Ok(StorageStats {
+            row_count,
+            file_size: page_count * page_size,
+            table_sizes,
+            average_value_size: if row_count == 0 {
+                0.0
+            } else {
+                total_value_bytes as f64 / row_count as f64
+            },
+            largest_keys,
+        })
+    }
+
+    /// Prepares the SQLite database by creating the table if it doesn't already exist.
+    ///
+    /// # Parameters
+    /// - `table`: The name of the table to create.
+    ///
+    /// # Returns
+    /// A `Result` indicating success or failure.
+    pub fn prepare(&self, table: &str) -> Result<()> {
+        self.database.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS {} (ID TEXT PRIMARY KEY, JSON TEXT)",
+                table
+            ),
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Creates the change log of the driver's table, along with the triggers
+    /// recording every insert, update and delete into it, if they don't exist.
+    fn prepare_change_log(&self) -> Result<()> {
+        // Triggers may only name tables of their own database, so a table of an
+        // attached database is referred to without its alias in the trigger body.
+        let (schema, table) = match self.table.split_once('.') {
+            Some((alias, table)) => (format!("{}.", alias), table),
+            None => (String::new(), self.table.as_str()),
+        };
+        let timestamp = "CAST(unixepoch('subsec') * 1000 AS INTEGER)";
+
+        self.database.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS {schema}{table}_changes \
+             (SEQ INTEGER PRIMARY KEY AUTOINCREMENT, ID TEXT NOT NULL, JSON TEXT, \
+             TIMESTAMP INTEGER NOT NULL);
+             CREATE TRIGGER IF NOT EXISTS {schema}{table}_changes_insert AFTER INSERT ON {table} \
+             BEGIN INSERT INTO {table}_changes (ID, JSON, TIMESTAMP) \
+             VALUES (NEW.ID, NEW.JSON, {timestamp}); END;
+             CREATE TRIGGER IF NOT EXISTS {schema}{table}_changes_update AFTER UPDATE ON {table} \
+             BEGIN INSERT INTO {table}_changes (ID, JSON, TIMESTAMP) \
+             VALUES (NEW.ID, NEW.JSON, {timestamp}); END;
+             CREATE TRIGGER IF NOT EXISTS {schema}{table}_changes_delete AFTER DELETE ON {table} \
+             BEGIN INSERT INTO {table}_changes (ID, JSON, TIMESTAMP) \
+             VALUES (OLD.ID, NULL, {timestamp}); END;",
+        ))?;
+        Ok(())
+    }
+
+    /// Creates the table holding when entries expire, along with the triggers
+    /// keeping it in line with the driver's table, if they don't exist.
+    fn prepare_expiry(&self) -> Result<()> {
+        let (schema, table) = match self.table.split_once('.') {
+            Some((alias, table)) => (format!("{}.", alias), table),
+            None => (String::new(), self.table.as_str()),
+        };
+        let now = "CAST(unixepoch('subsec') * 1000 AS INTEGER)";
+
+        // Deleting an entry removes its expiry, and overwriting an expired
+        // entry that wasn't swept yet gives the new value no expiry.
+        self.database.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS {schema}{table}_expiry \
+             (ID TEXT PRIMARY KEY, EXPIRES_AT INTEGER NOT NULL);
+             CREATE INDEX IF NOT EXISTS {schema}{table}_expiry_expires_at \
+             ON {table}_expiry (EXPIRES_AT);
+             CREATE TRIGGER IF NOT EXISTS {schema}{table}_expiry_delete AFTER DELETE ON {table} \
+             BEGIN DELETE FROM {table}_expiry WHERE ID = OLD.ID; END;
+             CREATE TRIGGER IF NOT EXISTS {schema}{table}_expiry_update AFTER UPDATE ON {table} \
+             BEGIN DELETE FROM {table}_expiry WHERE ID = NEW.ID AND EXPIRES_AT <= {now}; END;
+             DROP TRIGGER IF EXISTS {schema}{table}_expiry_default;",
+        ))?;
+
+        // New entries are given the default time to live, whichever
+        // connection creates them.
+        let default_ttl = self
+            .options
+            .expiry
+            .as_ref()
+            .and_then(|expiry| expiry.default_ttl);
+        if let Some(ttl) = default_ttl {
+            let ttl = ttl.as_millis().min(i64::MAX as u128);
+            self.database.execute_batch(&format!(
+                "CREATE TRIGGER {schema}{table}_expiry_default AFTER INSERT ON {table} \
+                 BEGIN INSERT OR IGNORE INTO {table}_expiry (ID, EXPIRES_AT) \
+                 VALUES (NEW.ID, {now} + {ttl}); END;",
+            ))?;
+        }
+        Ok(())
+    }
+
+    /// Creates the table holding the checksums of the values, along with the
+    /// triggers keeping it in line with the driver's table, if they don't
+    /// exist, and computes the checksums of the values stored without one.
+    fn prepare_checksums(&self) -> Result<()> {
+        let (schema, table) = match self.table.split_once('.') {
+            Some((alias, table)) => (format!("{}.", alias), table),
+            None => (String::new(), self.table.as_str()),
+        };
+
+        self.database.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS {schema}{table}_checksums \
+             (ID TEXT PRIMARY KEY, CHECKSUM INTEGER NOT NULL);
+             CREATE TRIGGER IF NOT EXISTS {schema}{table}_checksums_insert AFTER INSERT ON {table} \
+             BEGIN INSERT OR REPLACE INTO {table}_checksums (ID, CHECKSUM) \
+             VALUES (NEW.ID, chromoe_checksum(NEW.JSON)); END;
+             CREATE TRIGGER IF NOT EXISTS {schema}{table}_checksums_update AFTER UPDATE ON {table} \
+             BEGIN DELETE FROM {table}_checksums WHERE ID = OLD.ID; \
+             INSERT OR REPLACE INTO {table}_checksums (ID, CHECKSUM) \
+             VALUES (NEW.ID, chromoe_checksum(NEW.JSON)); END;
+             CREATE TRIGGER IF NOT EXISTS {schema}{table}_checksums_delete AFTER DELETE ON {table} \
+             BEGIN DELETE FROM {table}_checksums WHERE ID = OLD.ID; END;
+             INSERT OR IGNORE INTO {schema}{table}_checksums (ID, CHECKSUM) \
+             SELECT ID, chromoe_checksum(JSON) FROM {schema}{table};",
+        ))?;
+        Ok(())
+    }
+
+    /// Returns the SQL expression reading the stored checksum of the value of
+    /// a row of the driver's table, which is `NULL` unless checksums are
+    /// verified.
+    fn checksum_column(&self) -> String {
+        match self.options.checksums {
+            Some(_) => format!(
+                "(SELECT CHECKSUM FROM {0}_checksums AS C WHERE C.ID = {0}.ID)",
+                self.table
+            ),
+            None => "NULL".to_string(),
+        }
+    }
+
+    /// Compares the checksum of a value read from the driver's table with its
+    /// stored checksum, calling the `on_mismatch` function if they differ.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the value is intact, or a
+    /// `ChecksumMismatch` error.
+    fn verify_checksum(&self, key: &str, json: &str, stored: Option<i64>) -> Result<()> {
+        let (Some(options), Some(stored)) = (&self.options.checksums, stored) else {
+            return Ok(());
+        };
+        if checksum(json) == stored {
+            return Ok(());
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::error!(key, table = %self.table, "checksum mismatch");
+        if let Some(on_mismatch) = &options.on_mismatch {
+            on_mismatch(key, json);
+        }
+        Err(ChromoeError::ChecksumMismatch {
+            key: key.to_string(),
+        })
+    }
+
+    /// Describes why the value of a row can't be read, if it can't.
+    ///
+    /// # Returns
+    /// The error parsing the value, or a checksum mismatch, or `None` if the
+    /// value is intact.
+    fn row_problem(&self, json: &str, stored: Option<i64>) -> Option<String> {
+        if let Err(err) = from_str::<Value>(json) {
+            return Some(err.to_string());
+        }
+        let verified = self.options.checksums.is_some() && stored.is_some();
+        (verified && stored != Some(checksum(json))).then(|| "checksum mismatch".to_string())
+    }
+
+    /// Verifies and parses the value of a row read from the driver's table.
+    ///
+    /// # Returns
+    /// A `Result` containing the key and value, a `ChecksumMismatch` error,
+    /// or a `Corrupted` error if the value is not valid JSON.
+    fn parse_row(&self, key: String, json: &str, stored: Option<i64>) -> Result<(String, Value)> {
+        self.verify_checksum(&key, json, stored)?;
+        match from_str(json) {
+            Ok(value) => Ok((key, value)),
+            Err(source) => Err(ChromoeError::Corrupted { key, source }),
+        }
+    }
+
+    /// Returns whether the driver records when and how often entries are
+    /// used, which access statistics, caches and quotas evicting entries need.
+    fn tracks_access(&self) -> bool {
+        self.options.access_stats
+            || self.options.cache.is_some()
+            || self
+                .options
+                .quotas
+                .iter()
+                .any(|quota| quota.mode == QuotaMode::Evict)
+    }
+
+    /// Creates the table holding when and how often entries were used and
+    /// written, along with the triggers recording every write into it, if they
+    /// don't exist.
+    fn prepare_access_times(&self) -> Result<()> {
+        let (schema_name, _) = self.schema_and_table();
+        let (schema, table) = match self.table.split_once('.') {
+            Some((alias, table)) => (format!("{}.", alias), table),
+            None => (String::new(), self.table.as_str()),
+        };
+        let now = "CAST(unixepoch('subsec') * 1000 AS INTEGER)";
+
+        // `SEQ` orders the last uses of entries, even within the same millisecond.
+        self.database.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS {schema}{table}_access \
+             (ID TEXT PRIMARY KEY, ACCESSED_AT INTEGER NOT NULL, HITS INTEGER NOT NULL DEFAULT 0, \
+             WRITES INTEGER NOT NULL DEFAULT 0, SEQ INTEGER NOT NULL DEFAULT 0);"
+        ))?;
+
+        // Tables created before the number of uses and writes were recorded
+        // are given the columns, and their triggers are replaced with ones
+        // counting them.
+        let mut outdated = false;
+        for column in ["HITS", "WRITES", "SEQ"] {
+            let exists = self
+                .database
+                .prepare(&format!(
+                    "SELECT 1 FROM pragma_table_info('{table}_access', '{schema_name}') WHERE name = ?"
+                ))?
+                .exists(params![column])?;
+            if !exists {
+                self.database.execute_batch(&format!(
+                    "ALTER TABLE {schema}{table}_access ADD COLUMN {column} INTEGER NOT NULL DEFAULT 0;"
+                ))?;
+                outdated = true;
+            }
+        }
+        if outdated {
+            self.database.execute_batch(&format!(
+                "DROP TRIGGER IF EXISTS {schema}{table}_access_insert;
+                 DROP TRIGGER IF EXISTS {schema}{table}_access_update;"
+            ))?;
+        }
+
+        let record = format!(
+            "INSERT INTO {table}_access (ID, ACCESSED_AT, HITS, WRITES, SEQ) \
+             VALUES (NEW.ID, {now}, 1, 1, (SELECT COALESCE(MAX(SEQ), 0) + 1 FROM {table}_access)) \
+             ON CONFLICT(ID) DO UPDATE SET ACCESSED_AT = excluded.ACCESSED_AT, HITS = HITS + 1, \
+             WRITES = WRITES + 1, SEQ = excluded.SEQ;"
+        );
+        self.database.execute_batch(&format!(
+            "CREATE INDEX IF NOT EXISTS {schema}{table}_access_seq ON {table}_access (SEQ);
+             CREATE TRIGGER IF NOT EXISTS {schema}{table}_access_insert AFTER INSERT ON {table} \
+             BEGIN {record} END;
+             CREATE TRIGGER IF NOT EXISTS {schema}{table}_access_update AFTER UPDATE ON {table} \
+             BEGIN {record} END;
+             CREATE TRIGGER IF NOT EXISTS {schema}{table}_access_delete AFTER DELETE ON {table} \
+             BEGIN DELETE FROM {table}_access WHERE ID = OLD.ID; END;",
+        ))?;
+        Ok(())
+    }
+
+    /// Records that an entry was read, if the driver records when entries
+    /// were used.
+    fn touch(&self, key: &str) -> Result<()> {
+        if !self.tracks_access() || self.options.open_mode == SQLiteOpenMode::ReadOnly {
+            return Ok(());
+        }
+
+        self.database
+            .prepare_cached(&format!(
+                "INSERT INTO {0}_access (ID, ACCESSED_AT, HITS, SEQ) \
+                 VALUES (?1, ?2, 1, (SELECT COALESCE(MAX(SEQ), 0) + 1 FROM {0}_access)) \
+                 ON CONFLICT(ID) DO UPDATE SET ACCESSED_AT = excluded.ACCESSED_AT, HITS = HITS + 1, \
+                 SEQ = excluded.SEQ",
+                self.table
+            ))?
+            .execute(params![key, unix_millis(SystemTime::now())])?;
+        Ok(())
+    }
+
+    /// Makes room for a write within the capacity of the cache and the quotas
+    /// of the namespaces of its key, evicting entries where they allow it.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry written.
+    /// - `json`: The JSON text written.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the write is within the limits, or a
+    /// `QuotaExceeded` error.
+    fn enforce_quotas(&self, key: &str, json: &str) -> Result<()> {
+        if let Some(cache) = &self.options.cache {
+            let quota = QuotaOptions {
+                namespace: Namespace::Prefix(String::new()),
+                max_keys: Some(cache.capacity),
+                max_bytes: None,
+                mode: QuotaMode::Evict,
+                on_evict: cache.on_evict.clone(),
+            };
+            self.make_room(&quota, cache.policy, key, json)?;
+        }
+        for quota in &self.options.quotas {
+            self.make_room(quota, EvictionPolicy::LeastRecentlyUsed, key, json)?;
+        }
+        Ok(())
+    }
+
+    /// Makes room for a write within a quota, evicting entries in the order
+    /// of `policy` if the quota allows it.
+    fn make_room(
+        &self,
+        quota: &QuotaOptions,
+        policy: EvictionPolicy,
+        key: &str,
+        json: &str,
+    ) -> Result<()> {
+        let Some(prefix) = quota.namespace.prefix_of(key) else {
+            return Ok(());
+        };
+        let size = key.len() + json.len();
+
+        // The entry being overwritten, if any, makes room for its new value.
+        let (count, bytes): (i64, i64) = self
+            .database
+            .prepare_cached(&format!(
+                "SELECT COUNT(*), COALESCE(SUM(length(CAST(ID AS BLOB)) + length(CAST(JSON AS BLOB))), 0) \
+                 FROM {} WHERE ID >= ?1 AND substr(ID, 1, length(?1)) = ?1 AND ID != ?2",
+                self.table
+            ))?
+            .query_row(params![prefix, key], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        let excess_keys = (count as usize + 1).saturating_sub(quota.max_keys.unwrap_or(usize::MAX));
+        let excess_bytes =
+            (bytes as usize + size).saturating_sub(quota.max_bytes.unwrap_or(usize::MAX));
+        if excess_keys == 0 && excess_bytes == 0 {
+            return Ok(());
+        }
+
+        let exceeded = || {
+            let limit = if excess_keys > 0 {
+                format!("{} keys", quota.max_keys.unwrap_or_default())
+            } else {
+                format!("{} bytes", quota.max_bytes.unwrap_or_default())
+            };
+            let holder = match prefix.as_str() {
+                "" => "the table".to_string(),
+                prefix => format!("`{}`", prefix),
+            };
+            Err(ChromoeError::QuotaExceeded(format!(
+                "{} may hold at most {}",
+                holder, limit
+            )))
+        };
+        let fits_alone = quota.max_keys != Some(0) && quota.max_bytes.is_none_or(|max| size <= max);
+        if quota.mode == QuotaMode::Reject || !fits_alone {
+            return exceeded();
+        }
+
+        let order = match policy {
+            EvictionPolicy::LeastRecentlyUsed => "COALESCE(A.SEQ, 0), T.ID",
+            EvictionPolicy::LeastFrequentlyUsed => "COALESCE(A.HITS, 0), COALESCE(A.SEQ, 0), T.ID",
+        };
+        let mut victims = Vec::new();
+        let (mut freed_keys, mut freed_bytes) = (0, 0);
+        {
+            let mut stmt = self.database.prepare_cached(&format!(
+                "SELECT T.ID, length(CAST(T.ID AS BLOB)) + length(CAST(T.JSON AS BLOB)) \
+                 FROM {0} AS T LEFT JOIN {0}_access AS A ON A.ID = T.ID \
+                 WHERE T.ID >= ?1 AND substr(T.ID, 1, length(?1)) = ?1 AND T.ID != ?2 \
+                 ORDER BY {1}",
+                self.table, order
+            ))?;
+            let mut rows = stmt.query(params![prefix, key])?;
+            while freed_keys < excess_keys || freed_bytes < excess_bytes {
+                let Some(row) = rows.next()? else {
+                    return exceeded();
+                };
+                victims.push(row.get::<_, String>(0)?);
+                freed_keys += 1;
+                freed_bytes += row.get::<_, i64>(1)? as usize;
+            }
+        }
+
+        let mut delete = self
+            .database
+            .prepare_cached(&format!("DELETE FROM {} WHERE ID = ?", self.table))?;
+        for victim in &victims {
+            delete.execute(params![victim])?;
+            #[cfg(feature = "tracing")]
+            tracing::debug!(key = %victim, table = %self.table, "evicted entry");
+            if let Some(on_evict) = &quota.on_evict {
+                on_evict(victim);
+            }
+        }
+        Ok(())
+    }
+
+    /// Starts the thread sweeping expired entries, if the `expiry` option sets
+    /// a sweep interval.
+    ///
+    /// # Returns
+    /// A `Result` containing the sweeper, or a `Config` error if the database
+    /// is in-memory, since the sweeper needs a connection of its own.
+    fn spawn_sweeper(&self) -> Result<Option<Sweeper>> {
+        let Some(expiry) = &self.options.expiry else {
+            return Ok(None);
+        };
+        let Some(interval) = expiry.sweep_interval else {
+            return Ok(None);
+        };
+        if self.options.open_mode == SQLiteOpenMode::Memory || self.name == ":memory:" {
+            return Err(ChromoeError::Config(
+                "sweeping expired entries requires a file-backed database".to_string(),
+            ));
+        }
+
+        let table = self.table.clone();
+        let expiry = expiry.clone();
+        self.spawn_worker(interval, move |database| {
+            if let Err(_err) = sweep(database, &table, &expiry) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %_err, table = %table, "failed to sweep expired entries");
+            }
+        })
+        .map(Some)
+    }
+
+    /// Starts the thread enforcing the retention policy, if the `retention`
+    /// option sets an interval.
+    ///
+    /// # Returns
+    /// A `Result` containing the thread, or a `Config` error if the database
+    /// is in-memory, since the thread needs a connection of its own, or if the
+    /// policy is invalid.
+    fn spawn_retention(&self) -> Result<Option<Sweeper>> {
+        let Some(retention) = &self.options.retention else {
+            return Ok(None);
+        };
+        let Some(interval) = retention.interval else {
+            return Ok(None);
+        };
+        if self.options.open_mode == SQLiteOpenMode::Memory || self.name == ":memory:" {
+            return Err(ChromoeError::Config(
+                "enforcing retention in the background requires a file-backed database".to_string(),
+            ));
+        }
+        self.retention_options()?;
+
+        let table = self.table.clone();
+        let retention = retention.clone();
+        self.spawn_worker(interval, move |database| {
+            if let Err(_err) = enforce_retention(database, &table, &retention) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %_err, table = %table, "failed to enforce retention");
+            }
+        })
+        .map(Some)
+    }
+
+    /// Starts a thread running a task on a connection of its own every
+    /// `interval`, until the returned handle is dropped.
+    fn spawn_worker<F>(&self, interval: Duration, mut task: F) -> Result<Sweeper>
+    where
+        F: FnMut(&Connection) + Send + 'static,
+    {
+        let database = Self::open(&self.options)?;
+        let (stop, stopped) = mpsc::channel::<()>();
+
+        let thread = std::thread::spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(interval) {
+                task(&database);
+            }
+        });
+
+        Ok(Sweeper {
+            stop: Some(stop),
+            thread: Some(thread),
+        })
+    }
+
+    /// Returns the expiry options, or a `Config` error if expiry is disabled.
+    fn expiry_options(&self) -> Result<&ExpiryOptions> {
+        self.options
+            .expiry
+            .as_ref()
+            .ok_or_else(|| ChromoeError::Config("the `expiry` option is not set".to_string()))
+    }
+
+    /// Sets an entry to expire after a time to live, replacing any expiry it
+    /// had. The expiry belongs to the entry, so it is kept when the entry is
+    /// overwritten until it expires, and removed when the entry is deleted.
+    ///
+    /// Requires the `expiry` option. Expired entries read as missing, and are
+    /// deleted when they are swept.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry. For nested keys, the entry holding the
+    ///   nested value expires.
+    /// - `ttl`: How long the entry lives from now.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if the expiry was set, or `false` if the
+    /// entry doesn't exist.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::{ExpiryOptions, SQLiteDriverOptions};
+    ///
+    /// let driver = SQLiteDriver::temp_with(SQLiteDriverOptions {
+    ///     expiry: Some(ExpiryOptions::default()),
+    ///     ..Default::default()
+    /// })
+    /// .unwrap();
+    ///
+    /// driver.set("session", "token").unwrap();
+    /// assert!(driver.expire("session", Duration::from_millis(20)).unwrap());
+    /// assert!(driver.ttl("session").unwrap().is_some());
+    ///
+    /// std::thread::sleep(Duration::from_millis(30));
+    /// assert_eq!(driver.get::<String>("session").unwrap(), None);
+    /// assert_eq!(driver.sweep_expired().unwrap(), vec!["session".to_string()]);
+    /// ```
+    pub fn expire(&self, key: &str, ttl: Duration) -> Result<bool> {
+        self.expiry_options()?;
+        self.instrument("expire", Some(key), || {
+            self.set_expiry(key, SystemTime::now() + ttl)
+        })
+    }
+
+    /// Sets an entry to expire at a point in time, replacing any expiry it
+    /// had. A point in time that has already passed deletes the entry right
+    /// away, like Redis' `EXPIREAT`.
+    ///
+    /// Requires the `expiry` option.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry. For nested keys, the entry holding the
+    ///   nested value expires.
+    /// - `at`: When the entry expires.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if the expiry was set or the entry was
+    /// deleted, or `false` if the entry doesn't exist.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use std::time::{Duration, SystemTime};
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::{ExpiryOptions, SQLiteDriverOptions};
+    ///
+    /// let driver = SQLiteDriver::temp_with(SQLiteDriverOptions {
+    ///     expiry: Some(ExpiryOptions::default()),
+    ///     ..Default::default()
+    /// })
+    /// .unwrap();
+    ///
+    /// driver.set("event", "launch").unwrap();
+    /// let at = SystemTime::now() + Duration::from_secs(3600);
+    /// assert!(driver.expire_at("event", at).unwrap());
+    /// assert!(driver.ttl("event").unwrap().unwrap() > Duration::from_secs(3500));
+    ///
+    /// assert!(driver.persist("event").unwrap());
+    /// assert_eq!(driver.ttl("event").unwrap(), None);
+    ///
+    /// assert!(driver.expire_at("event", SystemTime::UNIX_EPOCH).unwrap());
+    /// assert!(!driver.has("event").unwrap());
+    /// ```
+    pub fn expire_at(&self, key: &str, at: SystemTime) -> Result<bool> {
+        self.expiry_options()?;
+        self.instrument("expire_at", Some(key), || self.set_expiry(key, at))
+    }
+
+    /// Sets the expiry of the entry holding a key, deleting the entry if the
+    /// expiry has already passed.
+    fn set_expiry(&self, key: &str, at: SystemTime) -> Result<bool> {
+        self.atomic(|| {
+            let root = self.split_path(key).swap_remove(0);
+            if self.get_row_key::<Value>(&root)?.is_none() {
+                return Ok(false);
+            }
+
+            if at <= SystemTime::now() {
+                self.database.execute(
+                    &format!("DELETE FROM {} WHERE ID = ?", self.table),
+                    params![root],
+                )?;
+                return Ok(true);
+            }
+
+            self.database.execute(
+                &format!(
+                    "INSERT INTO {}_expiry (ID, EXPIRES_AT) VALUES (?1, ?2) \
+                     ON CONFLICT(ID) DO UPDATE SET EXPIRES_AT = excluded.EXPIRES_AT",
+                    self.table
+                ),
+                params![root, unix_millis(at)],
+            )?;
+            Ok(true)
+        })
+    }
+
+    /// Returns how long an entry has left before it expires.
+    ///
+    /// Requires the `expiry` option.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry.
+    ///
+    /// # Returns
+    /// A `Result` containing the time left, or `None` if the entry doesn't
+    /// expire, has expired or doesn't exist.
+    pub fn ttl(&self, key: &str) -> Result<Option<Duration>> {
+        self.expiry_options()?;
+        self.instrument("ttl", Some(key), || {
+            let root = self.split_path(key).swap_remove(0);
+            let now = unix_millis(SystemTime::now());
+            let expires_at: Option<i64> = self
+                .database
+                .query_row(
+                    &format!(
+                        "SELECT EXPIRES_AT FROM {}_expiry WHERE ID = ?1 AND EXPIRES_AT > ?2",
+                        self.table
+                    ),
+                    params![root, now],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(expires_at.map(|expires_at| Duration::from_millis((expires_at - now) as u64)))
+        })
+    }
+
+    /// Removes the expiry of an entry, so it lives until it is deleted.
+    ///
+    /// Requires the `expiry` option.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if the entry had an expiry that hadn't
+    /// passed yet.
+    pub fn persist(&self, key: &str) -> Result<bool> {
+        self.expiry_options()?;
+        self.instrument("persist", Some(key), || {
+            let root = self.split_path(key).swap_remove(0);
+            let removed = self.database.execute(
+                &format!(
+                    "DELETE FROM {}_expiry WHERE ID = ?1 AND EXPIRES_AT > ?2",
+                    self.table
+                ),
+                params![root, unix_millis(SystemTime::now())],
+            )?;
+            Ok(removed > 0)
+        })
+    }
+
+    /// Deletes every expired entry, in transactions of `batch_size` entries,
+    /// calling the `on_evict` function of the expiry options for each.
+    ///
+    /// Requires the `expiry` option. The background sweeper does the same
+    /// every `sweep_interval` when it is set.
+    ///
+    /// # Returns
+    /// A `Result` containing the keys of the deleted entries.
+    pub fn sweep_expired(&self) -> Result<Vec<String>> {
+        let expiry = self.expiry_options()?;
+        self.instrument("sweep_expired", None, || {
+            sweep(&self.database, &self.table, expiry)
+        })
+    }
+
+    /// Returns the retention policy, after checking it can be enforced.
+    ///
+    /// # Returns
+    /// A `Result` containing the policy, or a `Config` error if the `retention`
+    /// option is not set or it reads the update time without the `change_log`
+    /// option, or an `InvalidPath` error if its timestamp path is invalid.
+    fn retention_options(&self) -> Result<&RetentionOptions> {
+        let retention =
+            self.options.retention.as_ref().ok_or_else(|| {
+                ChromoeError::Config("the `retention` option is not set".to_string())
+            })?;
+        match &retention.timestamp {
+            RetentionTimestamp::UpdatedAt if !self.options.change_log => {
+                return Err(ChromoeError::Config(
+                    "retention by update time requires the `change_log` option".to_string(),
+                ));
+            }
+            RetentionTimestamp::UpdatedAt => {}
+            RetentionTimestamp::Path(path) => {
+                field_path(path)?;
+            }
+        }
+        Ok(retention)
+    }
+
+    /// Deletes every entry older than the `max_age` of the retention policy,
+    /// in transactions of `batch_size` entries.
+    ///
+    /// Requires the `retention` option. The background thread does the same
+    /// every `interval` when it is set.
+    ///
+    /// # Returns
+    /// A `Result` containing the keys of the deleted entries.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::{RetentionOptions, RetentionTimestamp, SQLiteDriverOptions, SQLiteOpenMode};
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::new(Some(SQLiteDriverOptions {
+    ///     open_mode: SQLiteOpenMode::Memory,
+    ///     retention: Some(RetentionOptions {
+    ///         max_age: Duration::from_secs(90 * 24 * 60 * 60),
+    ///         timestamp: RetentionTimestamp::Path("$.created_at".to_string()),
+    ///         ..Default::default()
+    ///     }),
+    ///     ..Default::default()
+    /// }))
+    /// .unwrap();
+    ///
+    /// let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() as u64;
+    /// driver.set("case_1", json!({ "action": "ban", "created_at": "2020-01-01T00:00:00Z" })).unwrap();
+    /// driver.set("case_2", json!({ "action": "warn", "created_at": now })).unwrap();
+    ///
+    /// assert_eq!(driver.enforce_retention().unwrap(), ["case_1"]);
+    /// assert!(driver.has("case_2").unwrap());
+    /// ```
+    pub fn enforce_retention(&self) -> Result<Vec<String>> {
+        let retention = self.retention_options()?;
+        self.instrument("enforce_retention", None, || {
+            enforce_retention(&self.database, &self.table, retention)
+        })
+    }
+
+    /// Reads the changes recorded in the table's change log after a sequence
+    /// number, in the order they were made.
+    ///
+    /// Requires the `change_log` option. Buffered writes are flushed first, so
+    /// they appear in the log.
+    ///
+    /// # Parameters
+    /// - `seq`: The sequence number of the last change already read, or `0` to
+    ///   read the log from the start.
+    ///
+    /// # Returns
+    /// A `Result` containing the changes with a sequence number greater than `seq`.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::{SQLiteDriverOptions, SQLiteOpenMode};
+    ///
+    /// let driver = SQLiteDriver::new(Some(SQLiteDriverOptions {
+    ///     open_mode: SQLiteOpenMode::Memory,
+    ///     change_log: true,
+    ///     ..Default::default()
+    /// }))
+    /// .unwrap();
+    ///
+    /// driver.set("coins", 100).unwrap();
+    /// driver.delete("coins").unwrap();
+    ///
+    /// let changes = driver.changes_since(0).unwrap();
+    /// assert_eq!(changes.len(), 2);
+    /// assert_eq!(changes[1].value, None);
+    ///
+    /// driver.set("gems", 5).unwrap();
+    /// let tail = driver.changes_since(changes[1].seq).unwrap();
+    /// assert_eq!(tail[0].key, "gems");
+    /// ```
+    pub fn changes_since(&self, seq: u64) -> Result<Vec<Change>> {
+        self.instrument("changes_since", None, || {
+            let mut stmt = self.database.prepare(&format!(
+                "SELECT SEQ, ID, JSON, TIMESTAMP FROM {}_changes WHERE SEQ > ?1 ORDER BY SEQ",
+                self.table
+            ))?;
+            let rows = stmt.query_map(params![seq.min(i64::MAX as u64) as i64], |row| {
+                let json: Option<String> = row.get(2)?;
+                let timestamp: i64 = row.get(3)?;
+                Ok(Change {
+                    seq: row.get::<_, i64>(0)? as u64,
+                    key: row.get(1)?,
+                    value: json.map(|json| from_str(&json).unwrap_or(Value::Null)),
+                    timestamp: UNIX_EPOCH + Duration::from_millis(timestamp.max(0) as u64),
+                })
+            })?;
+
+            Ok(rows.collect::<std::result::Result<_, _>>()?)
+        })
+    }
+
+    /// Returns the most used entries of the table, with how often they were
+    /// read and written, to find out what is worth caching or sharding.
+    ///
+    /// Writes from every connection are counted, as are reads with `get`
+    /// through drivers recording statistics, including the reads of
+    /// operations modifying a value, such as `add`. Listings such as `all`
+    /// are not counted.
+    ///
+    /// # Parameters
+    /// - `n`: The number of entries to return.
+    ///
+    /// # Returns
+    /// A `Result` containing the statistics of the `n` entries read and
+    /// written the most times, the most used first, or a `Config` error if
+    /// the `access_stats` option is not enabled.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::{SQLiteDriverOptions, SQLiteOpenMode};
+    ///
+    /// let driver = SQLiteDriver::new(Some(SQLiteDriverOptions {
+    ///     file_name: ":memory:".to_string(),
+    ///     open_mode: SQLiteOpenMode::Memory,
+    ///     access_stats: true,
+    ///     ..Default::default()
+    /// }))
+    /// .unwrap();
+    ///
+    /// driver.set("prefix", "!").unwrap();
+    /// driver.set("locale", "en").unwrap();
+    /// for _ in 0..3 {
+    ///     driver.get::<String>("prefix").unwrap();
+    /// }
+    ///
+    /// let hot = driver.hot_keys(1).unwrap();
+    /// assert_eq!(hot[0].key, "prefix");
+    /// assert_eq!((hot[0].reads, hot[0].writes), (3, 1));
+    /// ```
+    pub fn hot_keys(&self, n: usize) -> Result<Vec<KeyStats>> {
+        self.instrument("hot_keys", None, || {
+            if !self.options.access_stats {
+                return Err(ChromoeError::Config(
+                    "access statistics require the `access_stats` option".to_string(),
+                ));
+            }
+
+            let mut stmt = self.database.prepare(&format!(
+                "SELECT ID, HITS, WRITES, ACCESSED_AT FROM {}_access \
+                 ORDER BY HITS DESC, SEQ DESC LIMIT ?",
+                self.table
+            ))?;
+            let rows = stmt.query_map(params![n.min(i64::MAX as usize) as i64], |row| {
+                let hits: i64 = row.get(1)?;
+                let writes: i64 = row.get(2)?;
+                let accessed_at: i64 = row.get(3)?;
+                Ok(KeyStats {
+                    key: row.get(0)?,
+                    reads: (hits - writes).max(0) as u64,
+                    writes: writes.max(0) as u64,
+                    last_access: UNIX_EPOCH + Duration::from_millis(accessed_at.max(0) as u64),
+                })
+            })?;
+
+            Ok(rows.collect::<std::result::Result<_, _>>()?)
+        })
+    }
+
+    /// Retrieves the value for a given key along with its metadata, deserialising
+    /// the value into the specified type.
+    ///
+    /// With the `change_log` option, the creation and last update times and the
+    /// version of the entry are read from the change log, counting from the last
+    /// time the key was deleted. Otherwise the entry carries no metadata.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve. For nested keys, the metadata
+    ///   is that of the entry holding the nested value.
+    ///
+    /// # Returns
+    /// A `Result` containing the entry, or `None` if the key doesn't exist.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:?changes=true").unwrap();
+    ///
+    /// driver.set("coins", 100).unwrap();
+    /// driver.add("coins", 50.0).unwrap();
+    ///
+    /// let entry = driver.entry::<f64>("coins").unwrap().unwrap();
+    /// assert_eq!(entry.value, 150.0);
+    /// assert_eq!(entry.version, Some(2));
+    /// assert!(entry.created_at <= entry.updated_at);
+    /// ```
+    pub fn entry<T>(&self, key: &str) -> Result<Option<DataSet<T>>>
+    where
+        T: DeserializeOwned + Default,
+    {
+        self.instrument("entry", Some(key), || {
+            let Some(value) = self.get::<T>(key)? else {
+                return Ok(None);
+            };
+
+            let mut entry = DataSet::new(key, value);
+            if self.options.change_log {
+                // Buffered writes are not in the change log until they are flushed.
+                self.flush_buffer()?;
+                let root = self.split_path(key).swap_remove(0);
+                let mut metadata = self.change_metadata(Some(&root))?;
+                if let Some((_, metadata)) = metadata.pop() {
+                    metadata.apply(&mut entry);
+                }
+            }
+            if self.options.expiry.is_some() {
+                entry.ttl = self.ttl(key)?;
+            }
+            Ok(Some(entry))
+        })
+    }
+
+    /// Retrieves all data entries from the database along with their metadata,
+    /// as described for [`SQLiteDriver::entry`].
+    ///
+    /// # Returns
+    /// A `Result` containing every entry of the table.
+    pub fn entries(&self) -> Result<Vec<DataSet>> {
+        self.instrument("entries", None, || {
+            let mut metadata: BTreeMap<String, _> = if self.options.change_log {
+                self.change_metadata(None)?.into_iter().collect()
+            } else {
+                BTreeMap::new()
+            };
+
+            Ok(self
+                .all()?
+                .into_iter()
+                .map(|(id, value)| {
+                    let mut entry = DataSet::new(id, value);
+                    if let Some(metadata) = metadata.remove(&entry.id) {
+                        metadata.apply(&mut entry);
+                    }
+                    entry
+                })
+                .collect())
+        })
+    }
+
+    /// Reads the creation time, last update time and version of one key, or of
+    /// every key, from the change log, ignoring changes made before a key was
+    /// last deleted.
+    fn change_metadata(&self, key: Option<&str>) -> Result<Vec<(String, ChangeMetadata)>> {
+        let mut stmt = self.database.prepare(&format!(
+            "SELECT ID, MIN(TIMESTAMP), MAX(TIMESTAMP), COUNT(*) FROM {table}_changes AS c \
+             WHERE (?1 IS NULL OR ID = ?1) AND SEQ > COALESCE((SELECT MAX(SEQ) FROM {table}_changes \
+             WHERE ID = c.ID AND JSON IS NULL), 0) GROUP BY ID",
+            table = self.table
+        ))?;
+        let time = |millis: i64| UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64);
+        let rows = stmt.query_map(params![key], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                ChangeMetadata {
+                    created_at: time(row.get(1)?),
+                    updated_at: time(row.get(2)?),
+                    version: row.get::<_, i64>(3)? as u64,
+                },
+            ))
+        })?;
+
+        Ok(rows.collect::<std::result::Result<_, _>>()?)
+    }
+
+    /// Adds a value to an existing entry or creates a new entry if it doesn't exist.
+    /// The value is added to the current value of the entry (if it exists).
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry to update.
+    /// - `value`: The value to add to the current entry.
+    ///
+    /// # Returns
+    /// The new value after adding `value` to the existing entry, or an error if
+    /// the value is not finite (e.g., NaN or infinity), or a `TypeMismatch`
+    /// error if the entry holds a value that is not a number, unless the
+    /// `lenient_numbers` option is set.
+    pub fn add(&self, key: &str, value: f64) -> Result<f64> {
+        self.instrument("add", Some(key), || {
+            let current_value = self.current_number(key)?;
+
+            if !current_value.is_finite() {
+                return Err(
+                    RusqliteError::ToSqlConversionFailure(Box::new(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "Non-finite value",
+                    )))
+                    .into(),
+                );
+            }
+
+            let new_value = current_value + value;
+            self.set(key, new_value)?;
+            Ok(new_value)
+        })
+    }
+
+    /// Retrieves all data entries from the database as a vector of tuples.
+    ///
+    /// # Returns
+    /// A `Result` containing a vector of tuples where each tuple consists of
+    /// a key (`String`) and a corresponding value (`serde_json::Value`).
+    pub fn all(&self) -> Result<Vec<(String, Value)>> {
+        self.instrument("all", None, || {
+            let mut stmt = self.database.prepare(&format!(
+                "SELECT ID, JSON, {} FROM {}",
+                self.checksum_column(),
+                self.table
+            ))?;
+            let rows = stmt.query_map([], |row| {
+                let id: String = row.get(0)?;
+                let json_str: String = row.get(1)?;
+                Ok((id, json_str, row.get::<_, Option<i64>>(2)?))
+            })?;
+
+            let mut data = Vec::new();
+            for row in rows {
+                let (id, json_str, stored) = row?;
+                self.verify_checksum(&id, &json_str, stored)?;
+                let json: Value = from_str(&json_str).unwrap_or(Value::Null);
+                data.push((id, json));
+            }
+
+            Ok(data)
+        })
+    }
+
+    /// Retrieves all data entries from the database with the JSON text of
+    /// their values, without parsing them, as [`SQLiteDriver::get_json`] does.
+    ///
+    /// # Returns
+    /// A `Result` containing the key and JSON text of every entry, or a
+    /// `Corrupted` error if a stored value is not valid JSON.
+    pub fn all_json(&self) -> Result<Vec<(String, Box<RawValue>)>> {
+        self.instrument("all_json", None, || {
+            let mut stmt = self.database.prepare(&format!(
+                "SELECT ID, JSON, {} FROM {}",
+                self.checksum_column(),
+                self.table
+            ))?;
+            let rows = stmt.query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get(2)?,
+                ))
+            })?;
+
+            let mut data = Vec::new();
+            for row in rows {
+                let (id, json_str, stored) = row?;
+                self.verify_checksum(&id, &json_str, stored)?;
+                match RawValue::from_string(json_str) {
+                    Ok(json) => data.push((id, json)),
+                    Err(source) => return Err(ChromoeError::Corrupted { key: id, source }),
+                }
+            }
+
+            Ok(data)
+        })
+    }
+
+    /// Retrieves the entries matching a set of filters, sorted by key.
+    ///
+    /// The filters are applied by the SQL query, so periodic jobs can fetch
+    /// only what changed since they last ran. Expired entries are left out.
+    ///
+    /// # Parameters
+    /// - `options`: The prefix, modification time and number of the entries
+    ///   to return, and their order. See [`ListOptions`].
+    ///
+    /// # Returns
+    /// A `Result` containing the matching keys and values, a `Config` error if
+    /// `updated_after` is set without the `change_log` option, or a
+    /// `Corrupted` error if a matching value is not valid JSON.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use std::time::SystemTime;
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::ListOptions;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:?changes=true").unwrap();
+    /// driver.set("user:1", 10).unwrap();
+    /// driver.set("guild:1", 20).unwrap();
+    ///
+    /// let synced_at = SystemTime::now();
+    /// std::thread::sleep(std::time::Duration::from_millis(5));
+    /// driver.set("user:2", 30).unwrap();
+    ///
+    /// let users = driver
+    ///     .all_with(ListOptions { prefix: Some("user:".to_string()), ..Default::default() })
+    ///     .unwrap();
+    /// assert_eq!(users.len(), 2);
+    ///
+    /// let changed = driver
+    ///     .all_with(ListOptions { updated_after: Some(synced_at), ..Default::default() })
+    ///     .unwrap();
+    /// assert_eq!(changed, vec![("user:2".to_string(), serde_json::json!(30))]);
+    /// ```
+    pub fn all_with(&self, options: ListOptions) -> Result<Vec<(String, Value)>> {
+        if options.updated_after.is_some() && !self.options.change_log {
+            return Err(ChromoeError::Config(
+                "filtering by modification time requires the `change_log` option".to_string(),
+            ));
+        }
+
+        self.instrument("all_with", None, || {
+            let mut sql = format!(
+                "SELECT ID, JSON, {1} FROM {0} WHERE (?1 IS NULL OR (ID >= ?1 AND substr(ID, 1, length(?1)) = ?1))",
+                self.table,
+                self.checksum_column()
+            );
+            if options.updated_after.is_some() {
+                sql.push_str(&format!(
+                    " AND ID IN (SELECT ID FROM {}_changes WHERE TIMESTAMP > ?2)",
+                    self.table
+                ));
+            }
+            if self.options.expiry.is_some() {
+                sql.push_str(&format!(
+                    " AND ID NOT IN (SELECT ID FROM {}_expiry WHERE EXPIRES_AT <= ?3)",
+                    self.table
+                ));
+            }
+            sql.push_str(&format!(" ORDER BY ID {} LIMIT ?4", options.order.as_sql()));
+
+            let limit = options
+                .limit
+                .map_or(-1, |limit| limit.min(i64::MAX as usize) as i64);
+            let mut stmt = self.database.prepare(&sql)?;
+            let rows = stmt
+                .query_map(
+                    params![
+                        options.prefix,
+                        options.updated_after.map(unix_millis),
+                        unix_millis(SystemTime::now()),
+                        limit
+                    ],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get(2)?)),
+                )?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            rows.into_iter()
+                .map(|(key, json, stored)| self.parse_row(key, &json, stored))
+                .collect()
+        })
+    }
+
+    /// Retrieves a page of entries sorted by key, starting after a cursor.
+    ///
+    /// Pages are delimited by keys rather than offsets, so entries inserted or
+    /// deleted while paginating don't cause entries of later pages to be
+    /// skipped or returned twice. Expired entries are left out.
+    ///
+    /// # Parameters
+    /// - `cursor`: The cursor returned with the previous page, or `None` for
+    ///   the first page.
+    /// - `limit`: The largest number of entries in the page, at least `1`.
+    ///
+    /// # Returns
+    /// A `Result` containing the page, or a `Corrupted` error if a value of
+    /// the page is not valid JSON.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::Cursor;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// for user in ["alice", "bob", "carol"] {
+    ///     driver.set(user, 0).unwrap();
+    /// }
+    ///
+    /// let first = driver.page(None, 2).unwrap();
+    /// assert_eq!(first.entries.len(), 2);
+    ///
+    /// // A token a web client may send back.
+    /// let token = first.next.unwrap().to_string();
+    /// driver.set("aaron", 0).unwrap();
+    ///
+    /// let second = driver.page(Some(token.parse::<Cursor>().unwrap()), 2).unwrap();
+    /// assert_eq!(second.entries[0].0, "carol");
+    /// assert!(second.next.is_none());
+    /// ```
+    pub fn page(&self, cursor: Option<Cursor>, limit: usize) -> Result<Page> {
+        self.instrument("page", None, || {
+            let mut sql = format!(
+                "SELECT ID, JSON, {1} FROM {0} WHERE (?1 IS NULL OR ID > ?1)",
+                self.table,
+                self.checksum_column()
+            );
+            if self.options.expiry.is_some() {
+                sql.push_str(&format!(
+                    " AND ID NOT IN (SELECT ID FROM {}_expiry WHERE EXPIRES_AT <= ?2)",
+                    self.table
+                ));
+            }
+            sql.push_str(" ORDER BY ID LIMIT ?3");
+
+            // One more entry than the page holds tells whether a page follows.
+            let limit = limit.clamp(1, i64::MAX as usize - 1);
+            let mut stmt = self.database.prepare(&sql)?;
+            let mut rows = stmt
+                .query_map(
+                    params![
+                        cursor.as_ref().map(Cursor::key),
+                        unix_millis(SystemTime::now()),
+                        (limit + 1) as i64
+                    ],
+                    |row| {
+                        Ok((
+                            row.get::<_, String>(0)?,
+                            row.get::<_, String>(1)?,
+                            row.get(2)?,
+                        ))
+                    },
+                )?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            let more = rows.len() > limit;
+            rows.truncate(limit);
+            let next = match rows.last() {
+                Some((key, _, _)) if more => Some(Cursor::after(key.clone())),
+                _ => None,
+            };
+            let entries = rows
+                .into_iter()
+                .map(|(key, json, stored)| self.parse_row(key, &json, stored))
+                .collect::<Result<_>>()?;
+
+            Ok(Page { entries, next })
+        })
+    }
+
+    /// Passes every entry through a function, writing back the entries it
+    /// modifies, as [`SQLiteDriver::update_all_with`] does with the default
+    /// options.
+    ///
+    /// # Parameters
+    /// - `update`: A function given the key and value of an entry, which it
+    ///   may modify.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries scanned and updated.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("alice", json!({ "name": " Alice " })).unwrap();
+    /// driver.set("bob", json!({ "name": "Bob" })).unwrap();
+    ///
+    /// let progress = driver
+    ///     .update_all(|_, value| {
+    ///         if let Some(name) = value["name"].as_str() {
+    ///             value["name"] = json!(name.trim());
+    ///         }
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    ///
+    /// assert_eq!((progress.scanned, progress.updated), (2, 1));
+    /// assert_eq!(driver.get::<String>("alice.name").unwrap(), Some("Alice".to_string()));
+    /// ```
+    pub fn update_all<F>(&self, update: F) -> Result<BulkProgress>
+    where
+        F: FnMut(&str, &mut Value) -> Result<()>,
+    {
+        self.update_all_with(BulkUpdateOptions::default(), update)
+    }
+
+    /// Passes every entry through a function, in order of their keys, and
+    /// writes back the entries it modifies.
+    ///
+    /// Entries are read and written back in batches, each in a transaction,
+    /// so the table isn't locked for the whole update and an entry can't
+    /// change between being read and written back. If the function fails,
+    /// the batch it failed in is rolled back, while earlier batches are kept.
+    /// Expired entries are skipped.
+    ///
+    /// # Parameters
+    /// - `options`: The prefix of the updated keys, the size of the batches
+    ///   and the progress function. See [`BulkUpdateOptions`].
+    /// - `update`: A function given the key and value of an entry, which it
+    ///   may modify.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries scanned and updated, the
+    /// error of the update function, or a `Corrupted` error if a value is
+    /// not valid JSON.
+    pub fn update_all_with<F>(
+        &self,
+        options: BulkUpdateOptions,
+        mut update: F,
+    ) -> Result<BulkProgress>
+    where
+        F: FnMut(&str, &mut Value) -> Result<()>,
+    {
+        self.instrument("update_all", None, || {
+            let batch_size = options.batch_size.clamp(1, i64::MAX as usize);
+            let mut sql = format!(
+                "SELECT ID, JSON, {1} FROM {0} WHERE (?1 IS NULL OR ID > ?1) \
+                 AND (?2 IS NULL OR (ID >= ?2 AND substr(ID, 1, length(?2)) = ?2))",
+                self.table,
+                self.checksum_column()
+            );
+            if self.options.expiry.is_some() {
+                sql.push_str(&format!(
+                    " AND ID NOT IN (SELECT ID FROM {}_expiry WHERE EXPIRES_AT <= ?3)",
+                    self.table
+                ));
+            }
+            sql.push_str(" ORDER BY ID LIMIT ?4");
+
+            let mut progress = BulkProgress::default();
+            let mut after: Option<String> = None;
+            loop {
+                let scanned = self.atomic(|| {
+                    let rows = self
+                        .database
+                        .prepare_cached(&sql)?
+                        .query_map(
+                            params![
+                                after,
+                                options.prefix,
+                                unix_millis(SystemTime::now()),
+                                batch_size as i64
+                            ],
+                            |row| {
+                                Ok((
+                                    row.get::<_, String>(0)?,
+                                    row.get::<_, String>(1)?,
+                                    row.get(2)?,
+                                ))
+                            },
+                        )?
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+                    let scanned = rows.len();
+                    for (key, json, stored) in rows {
+                        let (key, value) = self.parse_row(key, &json, stored)?;
+                        let mut updated = value.clone();
+                        update(&key, &mut updated)?;
+                        if updated != value {
+                            self.upsert_row_key(&key, &to_string(&updated)?)?;
+                            progress.updated += 1;
+                        }
+                        after = Some(key);
+                    }
+                    progress.scanned += scanned;
+                    Ok(scanned)
+                })?;
+
+                if let Some(on_progress) = &options.on_progress {
+                    on_progress(&progress);
+                }
+                if scanned < batch_size {
+                    return Ok(progress);
+                }
+            }
+        })
+    }
+
+    /// Writes every entry of the table as a SQL dump of plain `INSERT`
+    /// statements, which the sqlite3 CLI can load with `.read`.
+    ///
+    /// The dump creates the table if it doesn't exist, and names it without
+    /// its schema, so it can be loaded into another database. The rows are
+    /// read in a single statement, so the dump reflects a consistent state of
+    /// the table.
+    ///
+    /// # Parameters
+    /// - `writer`: Where the dump is written, such as a file.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries written.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let source = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// source.set("alice", json!({ "name": "O'Neil" })).unwrap();
+    ///
+    /// let mut dump = Vec::new();
+    /// assert_eq!(source.export_sql(&mut dump).unwrap(), 1);
+    /// assert!(String::from_utf8_lossy(&dump)
+    ///     .contains(r#"INSERT INTO json VALUES('alice','{"name":"O''Neil"}');"#));
+    ///
+    /// let target = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// assert_eq!(target.import_sql(dump.as_slice()).unwrap(), 1);
+    /// assert_eq!(target.get::<String>("alice.name").unwrap(), Some("O'Neil".to_string()));
+    /// ```
+    pub fn export_sql<W>(&self, mut writer: W) -> Result<usize>
+    where
+        W: Write,
+    {
+        self.instrument("export_sql", None, || {
+            let (_, table) = self.schema_and_table();
+            writeln!(writer, "BEGIN TRANSACTION;")?;
+            writeln!(
+                writer,
+                "CREATE TABLE IF NOT EXISTS {} (ID TEXT PRIMARY KEY, JSON TEXT);",
+                table
+            )?;
+
+            let mut stmt = self
+                .database
+                .prepare(&format!("SELECT ID, JSON FROM {} ORDER BY ID", self.table))?;
+            let mut rows = stmt.query([])?;
+            let mut count = 0;
+            while let Some(row) = rows.next()? {
+                let key: String = row.get(0)?;
+                let json: String = row.get(1)?;
+                writeln!(
+                    writer,
+                    "INSERT INTO {} VALUES({},{});",
+                    table,
+                    sql_dump::quote(&key),
+                    sql_dump::quote(&json)
+                )?;
+                count += 1;
+            }
+
+            writeln!(writer, "COMMIT;")?;
+            writer.flush()?;
+            Ok(count)
+        })
+    }
+
+    /// Stores the entries of a SQL dump, such as one written by
+    /// [`SQLiteDriver::export_sql`] or by the `.dump` command of the sqlite3
+    /// CLI, replacing the entries with the same keys.
+    ///
+    /// Only the rows inserted into a table with the same name as this one
+    /// are stored, so a dump of a whole database can be imported. Transaction,
+    /// `CREATE` and `PRAGMA` statements are skipped, and any other statement
+    /// is rejected rather than run. Every entry is stored in a single
+    /// transaction, so either the whole dump is imported or nothing is.
+    ///
+    /// # Parameters
+    /// - `reader`: Where the dump is read from, such as a file.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries stored, a `Config` error if
+    /// the dump has a statement other than those above or a row that is not a
+    /// key and a text, or a `Corrupted` error if a value is not valid JSON.
+    pub fn import_sql<R>(&self, mut reader: R) -> Result<usize>
+    where
+        R: Read,
+    {
+        let mut sql = String::new();
+        reader.read_to_string(&mut sql)?;
+
+        self.instrument("import_sql", None, || {
+            let (_, table) = self.schema_and_table();
+            let rows = sql_dump::parse_inserts(&sql, table)?;
+            for (key, json) in &rows {
+                if let Err(source) = from_str::<Value>(json) {
+                    return Err(ChromoeError::Corrupted {
+                        key: key.clone(),
+                        source,
+                    });
+                }
+            }
+
+            self.atomic(|| {
+                for (key, json) in &rows {
+                    self.upsert_row_key(key, json)?;
+                }
+                Ok(rows.len())
+            })
+        })
+    }
+
+    /// Writes every entry of the table as a TOML document, with a key or a
+    /// table for every entry.
+    ///
+    /// # Parameters
+    /// - `writer`: Where the document is written, such as a file.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries written, or a `Config`
+    /// error if a value can't be represented in TOML, such as `null`.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("prefix", "!").unwrap();
+    /// driver.set("welcome", json!({ "enabled": true, "channel": "general" })).unwrap();
+    ///
+    /// let mut document = Vec::new();
+    /// driver.export_toml(&mut document).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(document).unwrap(),
+    ///     "prefix = \"!\"\n\n[welcome]\nchannel = \"general\"\nenabled = true\n"
+    /// );
+    /// ```
+    pub fn export_toml<W>(&self, mut writer: W) -> Result<usize>
+    where
+        W: Write,
+    {
+        let entries = self.entries_map()?;
+        let document = toml::to_string(&entries)
+            .map_err(|e| ChromoeError::Config(format!("cannot write TOML: {}", e)))?;
+        writer.write_all(document.as_bytes())?;
+        writer.flush()?;
+        Ok(entries.len())
+    }
+
+    /// Stores every key of a TOML document as an entry, replacing the entries
+    /// with the same keys. Every entry is stored in a single transaction.
+    ///
+    /// # Parameters
+    /// - `reader`: Where the document is read from, such as a file.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries stored, or a `Config`
+    /// error if the document is not valid TOML.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// let seed = "prefix = \"!\"\n\n[welcome]\nenabled = true\n";
+    ///
+    /// assert_eq!(driver.import_toml(seed.as_bytes()).unwrap(), 2);
+    /// assert_eq!(driver.get::<bool>("welcome.enabled").unwrap(), Some(true));
+    /// ```
+    pub fn import_toml<R>(&self, mut reader: R) -> Result<usize>
+    where
+        R: Read,
+    {
+        let mut document = String::new();
+        reader.read_to_string(&mut document)?;
+        let entries: Map<String, Value> = toml::from_str(&document)
+            .map_err(|e: toml::de::Error| ChromoeError::Config(e.to_string()))?;
+        self.store_entries(entries)
+    }
+
+    /// Writes every entry of the table as a YAML mapping of the keys to the
+    /// values.
+    ///
+    /// # Parameters
+    /// - `writer`: Where the document is written, such as a file.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries written.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("welcome", json!({ "enabled": true, "channel": "general" })).unwrap();
+    ///
+    /// let mut document = Vec::new();
+    /// driver.export_yaml(&mut document).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(document).unwrap(),
+    ///     "welcome:\n  channel: general\n  enabled: true\n"
+    /// );
+    /// ```
+    #[cfg(feature = "yaml")]
+    pub fn export_yaml<W>(&self, mut writer: W) -> Result<usize>
+    where
+        W: Write,
+    {
+        let entries = self.entries_map()?;
+        serde_yaml::to_writer(&mut writer, &entries)
+            .map_err(|e| ChromoeError::Config(format!("cannot write YAML: {}", e)))?;
+        writer.flush()?;
+        Ok(entries.len())
+    }
+
+    /// Stores every key of a YAML mapping as an entry, replacing the entries
+    /// with the same keys. Every entry is stored in a single transaction.
+    ///
+    /// # Parameters
+    /// - `reader`: Where the document is read from, such as a file.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries stored, or a `Config`
+    /// error if the document is not a YAML mapping with string keys.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// let seed = "prefix: \"!\"\nwelcome:\n  enabled: true\n  roles: [member]\n";
+    ///
+    /// assert_eq!(driver.import_yaml(seed.as_bytes()).unwrap(), 2);
+    /// assert_eq!(driver.get::<Vec<String>>("welcome.roles").unwrap(), Some(vec!["member".to_string()]));
+    /// ```
+    #[cfg(feature = "yaml")]
+    pub fn import_yaml<R>(&self, reader: R) -> Result<usize>
+    where
+        R: Read,
+    {
+        let entries: Map<String, Value> = serde_yaml::from_reader(reader)
+            .map_err(|e| ChromoeError::Config(format!("invalid YAML: {}", e)))?;
+        self.store_entries(entries)
+    }
+
+    /// Stores default entries, such as the default settings of a guild,
+    /// combining them with the entries already stored.
+    ///
+    /// Every entry is stored in a single transaction, so applying the same
+    /// seed on every start is safe: entries modified since are left as they
+    /// are, unless the mode is `Overwrite`.
+    ///
+    /// # Parameters
+    /// - `entries`: The keys and values of the seed entries.
+    /// - `mode`: How a seed entry is combined with the entry stored at its
+    ///   key. See [`SeedMode`].
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries written.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::SeedMode;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("settings", json!({ "prefix": "?" })).unwrap();
+    ///
+    /// let seed = json!({
+    ///     "settings": { "prefix": "!", "locale": "en" },
+    ///     "welcome": { "enabled": false }
+    /// });
+    /// let entries = seed.as_object().unwrap().clone();
+    ///
+    /// assert_eq!(driver.seed(entries.clone(), SeedMode::OnlyIfMissing).unwrap(), 1);
+    /// assert_eq!(driver.get("settings").unwrap(), Some(json!({ "prefix": "?" })));
+    ///
+    /// assert_eq!(driver.seed(entries.clone(), SeedMode::FillMissing).unwrap(), 1);
+    /// assert_eq!(driver.get("settings").unwrap(), Some(json!({ "prefix": "?", "locale": "en" })));
+    ///
+    /// // Applying the seed again writes nothing.
+    /// assert_eq!(driver.seed(entries, SeedMode::FillMissing).unwrap(), 0);
+    /// ```
+    pub fn seed(&self, entries: Map<String, Value>, mode: SeedMode) -> Result<usize> {
+        self.instrument("seed", None, || {
+            self.atomic(|| {
+                let mut written = 0;
+                for (key, value) in entries {
+                    let existing = self.get_row_key::<Value>(&key)?;
+                    if let Some(value) = mode.resolve(existing.as_ref(), value) {
+                        self.upsert_row_key(&key, &to_string(&value)?)?;
+                        written += 1;
+                    }
+                }
+                Ok(written)
+            })
+        })
+    }
+
+    /// Stores the default entries of a file, as [`SQLiteDriver::seed`] does.
+    ///
+    /// The format of the file is chosen by its extension: `.json` for a JSON
+    /// object, `.toml` for a TOML document, and `.yaml` or `.yml` for a YAML
+    /// mapping with the `yaml` feature.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the file, such as `seed.json`.
+    /// - `mode`: How a seed entry is combined with the entry stored at its
+    ///   key. See [`SeedMode`].
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries written, an `Io` error if
+    /// the file can't be read, or a `Config` error if it can't be parsed or
+    /// has another extension.
+    pub fn seed_from_file<P>(&self, path: P, mode: SeedMode) -> Result<usize>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        let entries: Map<String, Value> = match extension {
+            Some("json") => from_str(&contents).map_err(|e| {
+                ChromoeError::Config(format!("invalid seed file `{}`: {}", path.display(), e))
+            })?,
+            Some("toml") => toml::from_str(&contents).map_err(|e: toml::de::Error| {
+                ChromoeError::Config(format!("invalid seed file `{}`: {}", path.display(), e))
+            })?,
+            #[cfg(feature = "yaml")]
+            Some("yaml" | "yml") => serde_yaml::from_str(&contents).map_err(|e| {
+                ChromoeError::Config(format!("invalid seed file `{}`: {}", path.display(), e))
+            })?,
+            _ => {
+                return Err(ChromoeError::Config(format!(
+                    "unsupported seed file `{}`",
+                    path.display()
+                )))
+            }
+        };
+        self.seed(entries, mode)
+    }
+
+    /// Writes every entry of the table to a compressed and encrypted file,
+    /// such as a backup shared with someone else.
+    ///
+    /// The entries are compressed and encrypted with AES-256-GCM, under a key
+    /// derived from the passphrase with Argon2id, so the file reveals nothing
+    /// but its size without the passphrase, and can't be modified unnoticed.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the file, which is replaced if it exists.
+    /// - `passphrase`: The passphrase needed to import the file.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries written.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let path = std::env::temp_dir().join("chromoe-export-encrypted.bin");
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("user", json!({ "email": "reina@example.com" })).unwrap();
+    ///
+    /// assert_eq!(driver.export_encrypted(&path, "correct horse").unwrap(), 1);
+    ///
+    /// let restored = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// assert!(restored.import_encrypted(&path, "wrong horse").is_err());
+    /// assert_eq!(restored.import_encrypted(&path, "correct horse").unwrap(), 1);
+    /// assert_eq!(restored.get::<String>("user.email").unwrap(), Some("reina@example.com".to_string()));
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(feature = "encryption")]
+    pub fn export_encrypted<P>(&self, path: P, passphrase: &str) -> Result<usize>
+    where
+        P: AsRef<Path>,
+    {
+        let entries = self.entries_map()?;
+        let bundle = crate::driver::bundle::seal(&serde_json::to_vec(&entries)?, passphrase)?;
+        std::fs::write(path, bundle)?;
+        Ok(entries.len())
+    }
+
+    /// Stores the entries of a file written by
+    /// [`SQLiteDriver::export_encrypted`], replacing the entries with the same
+    /// keys. Every entry is stored in a single transaction.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the file.
+    /// - `passphrase`: The passphrase the file was written with.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of entries stored, an `Io` error if
+    /// the file can't be read, or a `Config` error if it is not an encrypted
+    /// export, the passphrase is wrong or the file was modified.
+    #[cfg(feature = "encryption")]
+    pub fn import_encrypted<P>(&self, path: P, passphrase: &str) -> Result<usize>
+    where
+        P: AsRef<Path>,
+    {
+        let bundle = std::fs::read(path)?;
+        let data = crate::driver::bundle::open(&bundle, passphrase)?;
+        let entries: Map<String, Value> = serde_json::from_slice(&data)?;
+        self.store_entries(entries)
+    }
+
+    /// Reads every entry of the table as a map sorted by key.
+    fn entries_map(&self) -> Result<Map<String, Value>> {
+        let mut entries = self.all()?;
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(entries.into_iter().collect())
+    }
+
+    /// Stores entries in a single transaction, replacing the entries with the
+    /// same keys.
+    fn store_entries(&self, entries: Map<String, Value>) -> Result<usize> {
+        self.instrument("import", None, || {
+            self.atomic(|| {
+                for (key, value) in &entries {
+                    self.upsert_row_key(key, &to_string(value)?)?;
+                }
+                Ok(entries.len())
+            })
+        })
+    }
+
+    /// Takes an immutable, point-in-time copy of the table.
+    ///
+    /// The rows are read in a single statement, so the snapshot reflects a
+    /// consistent state of the table even while other connections keep writing.
+    /// Buffered writes are flushed before the copy is taken, and expired entries
+    /// are left out of it.
+    ///
+    /// # Returns
+    /// A `Result` containing a `Snapshot` of the table.
+    pub fn snapshot(&self) -> Result<Snapshot> {
+        self.instrument("snapshot", None, || {
+            let taken_at = SystemTime::now();
+            // The copy lives in its own database, so it can't use the schema a
+            // qualified table name refers to.
+            let copy = SQLiteDriver::new(Some(SQLiteDriverOptions {
+                file_name: String::from(":memory:"),
+                table_name: self.schema_and_table().1.to_string(),
+                open_mode: SQLiteOpenMode::Memory,
+                ..Default::default()
+            }))?;
+
+            let mut sql = format!("SELECT ID, JSON FROM {}", self.table);
+            if self.options.expiry.is_some() {
+                sql.push_str(&format!(
+                    " WHERE ID NOT IN (SELECT ID FROM {}_expiry WHERE EXPIRES_AT <= ?1)",
+                    self.table
+                ));
+            }
+            let mut stmt = self.database.prepare(&sql)?;
+            let mut rows = match self.options.expiry {
+                Some(_) => stmt.query(params![unix_millis(taken_at)])?,
+                None => stmt.query([])?,
+            };
+
+            copy.atomic(|| {
+                let mut insert = copy.database.prepare(&format!(
+                    "INSERT INTO {} (ID, JSON) VALUES (?, ?)",
+                    copy.table
+                ))?;
+                while let Some(row) = rows.next()? {
+                    insert.execute(params![row.get::<_, String>(0)?, row.get::<_, String>(1)?])?;
+                }
+                Ok(())
+            })?;
+
+            Ok(Snapshot::new(copy, taken_at))
+        })
+    }
+
+    /// Tries to acquire the advisory lock with the given name.
+    ///
+    /// Locks are stored in the `chromoe_locks` table of the database, so every
+    /// process sharing the database file observes them. A lock that is not
+    /// released expires after `ttl`, after which another owner may acquire it.
+    /// Acquisition is a single atomic statement.
+    ///
+    /// # Parameters
+    /// - `key`: The name of the lock, such as `payouts:daily`.
+    /// - `ttl`: How long the lock is held before it expires.
+    ///
+    /// # Returns
+    /// A `Result` containing the acquired `Lock`, or `None` if another owner
+    /// currently holds an unexpired lock with this name.
+    pub fn acquire_lock(&self, key: &str, ttl: Duration) -> Result<Option<Lock>> {
+        self.instrument("acquire_lock", Some(key), || {
+            self.prepare_locks()?;
+
+            let now = unix_millis(SystemTime::now());
+            let expires_at = now.saturating_add(ttl.as_millis().min(i64::MAX as u128) as i64);
+            let token = lock_token();
+
+            let acquired = self.database.execute(
+                "INSERT INTO chromoe_locks (NAME, OWNER, EXPIRES_AT) VALUES (?1, ?2, ?3) \
+                 ON CONFLICT(NAME) DO UPDATE SET OWNER = excluded.OWNER, \
+                 EXPIRES_AT = excluded.EXPIRES_AT WHERE chromoe_locks.EXPIRES_AT <= ?4",
+                params![key, token, expires_at, now],
+            )?;
+
+            Ok((acquired == 1).then(|| Lock {
+                name: key.to_string(),
+                token,
+                expires_at: UNIX_EPOCH + Duration::from_millis(expires_at as u64),
+            }))
+        })
+    }
+
+    /// Releases a lock acquired with `acquire_lock`.
+    ///
+    /// # Parameters
+    /// - `lock`: The lock to release.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if the lock was released, or `false` if it had
+    /// already expired and been acquired by another owner, or was already released.
+    pub fn release_lock(&self, lock: &Lock) -> Result<bool> {
+        self.instrument("release_lock", Some(&lock.name), || {
+            self.prepare_locks()?;
+            let released = self.database.execute(
+                "DELETE FROM chromoe_locks WHERE NAME = ?1 AND OWNER = ?2",
+                params![lock.name, lock.token],
+            )?;
+            Ok(released == 1)
+        })
+    }
+
+    /// Creates the table holding advisory locks if it doesn't already exist.
+    fn prepare_locks(&self) -> Result<()> {
+        self.database.execute(
+            "CREATE TABLE IF NOT EXISTS chromoe_locks \
+             (NAME TEXT PRIMARY KEY, OWNER TEXT NOT NULL, EXPIRES_AT INTEGER NOT NULL)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Schedules a job to run at a given time, such as a reminder or the end of
+    /// a mute, so it survives restarts of the application.
+    ///
+    /// Jobs are stored in the `chromoe_jobs` table of the database, shared by
+    /// every table. A worker polls [`SQLiteDriver::due_jobs`] and runs the jobs
+    /// it claims with [`SQLiteDriver::claim_job`].
+    ///
+    /// # Parameters
+    /// - `key`: The key of the job, such as `unmute:guild_1:user_1`, which
+    ///   cancels it with [`SQLiteDriver::cancel_jobs`]. Several jobs may share a
+    ///   key.
+    /// - `run_at`: When the job is due, as a `SystemTime` or any type
+    ///   converting into one.
+    /// - `payload`: The value the job is run with.
+    ///
+    /// # Returns
+    /// A `Result` containing the ID of the job.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use std::time::{Duration, SystemTime};
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// let now = SystemTime::now();
+    ///
+    /// driver.schedule("remind:user_1", now, json!({ "text": "Drink water" })).unwrap();
+    /// driver.schedule("unmute:user_2", now + Duration::from_secs(600), json!({})).unwrap();
+    ///
+    /// let due = driver.due_jobs(now).unwrap();
+    /// assert_eq!(due.len(), 1);
+    ///
+    /// // Only one worker claims a job, so it runs once.
+    /// let job = driver.claim_job(due[0].id).unwrap().unwrap();
+    /// assert_eq!(job.payload["text"], "Drink water");
+    /// assert_eq!(driver.claim_job(job.id).unwrap(), None);
+    /// ```
+    pub fn schedule<T, R>(&self, key: &str, run_at: R, payload: T) -> Result<u64>
+    where
+        T: Serialize,
+        R: Into<SystemTime>,
+    {
+        self.instrument("schedule", Some(key), || {
+            let payload = to_string(&payload)?;
+            self.prepare_jobs()?;
+            let id: i64 = self.database.query_row(
+                "INSERT INTO chromoe_jobs (KEY, RUN_AT, PAYLOAD) VALUES (?1, ?2, ?3) RETURNING ID",
+                params![key, unix_millis(run_at.into()), payload],
+                |row| row.get(0),
+            )?;
+            Ok(id as u64)
+        })
+    }
+
+    /// Retrieves the jobs that are due at a given time, from the earliest due,
+    /// without claiming them.
+    ///
+    /// # Parameters
+    /// - `now`: The time jobs are due by, usually `SystemTime::now()`.
+    ///
+    /// # Returns
+    /// A `Result` containing the due jobs.
+    pub fn due_jobs<R>(&self, now: R) -> Result<Vec<Job>>
+    where
+        R: Into<SystemTime>,
+    {
+        self.instrument("due_jobs", None, || {
+            self.prepare_jobs()?;
+            let mut stmt = self.database.prepare(
+                "SELECT ID, KEY, RUN_AT, PAYLOAD FROM chromoe_jobs \
+                 WHERE RUN_AT <= ?1 ORDER BY RUN_AT, ID",
+            )?;
+            let rows = stmt.query_map(params![unix_millis(now.into())], job_row)?;
+            rows.map(|row| parse_job(row?)).collect()
+        })
+    }
+
+    /// Returns when the next job is due, such as to sleep until then.
+    ///
+    /// # Returns
+    /// A `Result` containing the time the earliest job is due, or `None` if no
+    /// job is scheduled.
+    pub fn next_job_at(&self) -> Result<Option<SystemTime>> {
+        self.instrument("next_job_at", None, || {
+            self.prepare_jobs()?;
+            let run_at: Option<i64> =
+                self.database
+                    .query_row("SELECT MIN(RUN_AT) FROM chromoe_jobs", [], |row| row.get(0))?;
+            Ok(run_at.map(|run_at| UNIX_EPOCH + Duration::from_millis(run_at.max(0) as u64)))
+        })
+    }
+
+    /// Claims a job, removing it from the schedule. Claiming is a single atomic
+    /// statement, so when several workers or processes claim the same job, only
+    /// one of them receives it. A job claimed by a worker that stops before
+    /// running it is not run again.
+    ///
+    /// # Parameters
+    /// - `id`: The ID of the job.
+    ///
+    /// # Returns
+    /// A `Result` containing the job, or `None` if it was already claimed or
+    /// cancelled.
+    pub fn claim_job(&self, id: u64) -> Result<Option<Job>> {
+        self.instrument("claim_job", None, || {
+            self.prepare_jobs()?;
+            self.database
+                .query_row(
+                    "DELETE FROM chromoe_jobs WHERE ID = ?1 RETURNING ID, KEY, RUN_AT, PAYLOAD",
+                    params![id.min(i64::MAX as u64) as i64],
+                    job_row,
+                )
+                .optional()?
+                .map(parse_job)
+                .transpose()
+        })
+    }
+
+    /// Cancels every job scheduled under a key that hasn't been claimed yet.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the jobs.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of jobs cancelled.
+    pub fn cancel_jobs(&self, key: &str) -> Result<usize> {
+        self.instrument("cancel_jobs", Some(key), || {
+            self.prepare_jobs()?;
+            Ok(self
+                .database
+                .execute("DELETE FROM chromoe_jobs WHERE KEY = ?1", params![key])?)
+        })
+    }
+
+    /// Creates the table holding scheduled jobs if it doesn't already exist.
+    fn prepare_jobs(&self) -> Result<()> {
+        self.database.execute_batch(
+            "CREATE TABLE IF NOT EXISTS chromoe_jobs \
+             (ID INTEGER PRIMARY KEY AUTOINCREMENT, KEY TEXT NOT NULL, \
+             RUN_AT INTEGER NOT NULL, PAYLOAD TEXT NOT NULL);
+             CREATE INDEX IF NOT EXISTS chromoe_jobs_run_at ON chromoe_jobs (RUN_AT);
+             CREATE INDEX IF NOT EXISTS chromoe_jobs_key ON chromoe_jobs (KEY);",
+        )?;
+        Ok(())
+    }
+
+    /// Publishes a message to the subscribers of a channel, including those of
+    /// other processes sharing the database file.
+    ///
+    /// Messages are stored in the `chromoe_messages` table of the database,
+    /// shared by every table, until they are removed with
+    /// [`SQLiteDriver::prune_messages`]. A message published within a
+    /// transaction is received once the transaction commits.
+    ///
+    /// # Parameters
+    /// - `channel`: The channel to publish to, such as `config.reload`.
+    /// - `payload`: The value to publish.
+    ///
+    /// # Returns
+    /// A `Result` containing the sequence number of the message.
+    pub fn publish<T>(&self, channel: &str, payload: T) -> Result<u64>
+    where
+        T: Serialize,
+    {
+        self.instrument("publish", Some(channel), || {
+            let payload = to_string(&payload)?;
+            self.prepare_messages()?;
+            let seq: i64 = self.database.query_row(
+                "INSERT INTO chromoe_messages (CHANNEL, PAYLOAD, TIMESTAMP) \
+                 VALUES (?1, ?2, ?3) RETURNING SEQ",
+                params![channel, payload, unix_millis(SystemTime::now())],
+                |row| row.get(0),
+            )?;
+            Ok(seq as u64)
+        })
+    }
+
+    /// Subscribes to the messages published to a channel from now on.
+    ///
+    /// # Parameters
+    /// - `channel`: The channel to subscribe to.
+    ///
+    /// # Returns
+    /// A `Result` containing the [`Subscriber`] receiving the messages.
+    pub fn subscribe(&self, channel: &str) -> Result<Subscriber<'_>> {
+        self.instrument("subscribe", Some(channel), || {
+            self.prepare_messages()?;
+            let seq: i64 = self.database.query_row(
+                "SELECT COALESCE(MAX(SEQ), 0) FROM chromoe_messages",
+                [],
+                |row| row.get(0),
+            )?;
+            Ok(Subscriber::new(self, channel, seq as u64))
+        })
+    }
+
+    /// Removes the messages published before a given time, which subscribers
+    /// that are behind no longer receive.
+    ///
+    /// # Parameters
+    /// - `before`: The time before which messages are removed.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of messages removed.
+    pub fn prune_messages<R>(&self, before: R) -> Result<usize>
+    where
+        R: Into<SystemTime>,
+    {
+        self.instrument("prune_messages", None, || {
+            self.prepare_messages()?;
+            Ok(self.database.execute(
+                "DELETE FROM chromoe_messages WHERE TIMESTAMP < ?1",
+                params![unix_millis(before.into())],
+            )?)
+        })
+    }
+
+    /// Reads the messages of a channel published after a sequence number.
+    ///
+    /// # Returns
+    /// A `Result` containing the messages, or a `Corrupted` error naming the
+    /// channel if a payload is not valid JSON.
+    pub(crate) fn messages_after(&self, channel: &str, seq: u64) -> Result<Vec<Message>> {
+        self.prepare_messages()?;
+        let mut stmt = self.database.prepare_cached(
+            "SELECT SEQ, CHANNEL, PAYLOAD, TIMESTAMP FROM chromoe_messages \
+             WHERE SEQ > ?1 AND CHANNEL = ?2 ORDER BY SEQ",
+        )?;
+        let rows = stmt
+            .query_map(params![seq.min(i64::MAX as u64) as i64, channel], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.into_iter()
+            .map(|(seq, channel, payload, timestamp)| {
+                let payload = from_str(&payload).map_err(|source| ChromoeError::Corrupted {
+                    key: channel.clone(),
+                    source,
+                })?;
+                Ok(Message {
+                    seq: seq as u64,
+                    channel,
+                    payload,
+                    timestamp: UNIX_EPOCH + Duration::from_millis(timestamp.max(0) as u64),
+                })
+            })
+            .collect()
+    }
+
+    /// Creates the table holding published messages if it doesn't already
+    /// exist.
+    fn prepare_messages(&self) -> Result<()> {
+        self.database.execute_batch(
+            "CREATE TABLE IF NOT EXISTS chromoe_messages \
+             (SEQ INTEGER PRIMARY KEY AUTOINCREMENT, CHANNEL TEXT NOT NULL, \
+             PAYLOAD TEXT NOT NULL, TIMESTAMP INTEGER NOT NULL);
+             CREATE INDEX IF NOT EXISTS chromoe_messages_channel \
+             ON chromoe_messages (CHANNEL, SEQ);",
+        )?;
+        Ok(())
+    }
+
+    /// Issues the next number of the sequence with the given name, such as the
+    /// number of a support ticket.
+    ///
+    /// Sequences are stored in the `chromoe_sequences` table of the database, and
+    /// start from `1`. Each number is issued by a single atomic statement, so
+    /// concurrent callers, including other processes sharing the database file,
+    /// never receive the same number.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the sequence, such as `tickets`.
+    ///
+    /// # Returns
+    /// A `Result` containing the issued number.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    ///
+    /// assert_eq!(driver.next_id("tickets").unwrap(), 1);
+    /// assert_eq!(driver.next_id("tickets").unwrap(), 2);
+    /// assert_eq!(driver.next_id("warnings").unwrap(), 1);
+    /// ```
+    pub fn next_id(&self, name: &str) -> Result<u64> {
+        self.instrument("next_id", Some(name), || {
+            self.prepare_sequences()?;
+            let id: i64 = self.database.query_row(
+                "INSERT INTO chromoe_sequences (NAME, VALUE) VALUES (?1, 1) \
+                 ON CONFLICT(NAME) DO UPDATE SET VALUE = VALUE + 1 RETURNING VALUE",
+                params![name],
+                |row| row.get(0),
+            )?;
+            Ok(id as u64)
+        })
+    }
+
+    /// Creates the table holding sequences if it doesn't already exist.
+    fn prepare_sequences(&self) -> Result<()> {
+        self.database.execute(
+            "CREATE TABLE IF NOT EXISTS chromoe_sequences \
+             (NAME TEXT PRIMARY KEY, VALUE INTEGER NOT NULL)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Starts a batch of operations on several keys, applied atomically when
+    /// it is committed.
+    ///
+    /// # Returns
+    /// An empty `Batch` of operations on this driver.
+    pub fn batch(&self) -> Batch<'_> {
+        Batch::new(self)
+    }
+
+    /// Starts declaring the migrations of the documents stored in the table,
+    /// run with [`Migrations::run`].
+    ///
+    /// # Returns
+    /// An empty set of `Migrations` of this driver's table.
+    pub fn migrations(&self) -> Migrations<'_> {
+        Migrations::new(self)
+    }
+
+    /// Returns a handle to the integer counter stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the counter is stored.
+    ///
+    /// # Returns
+    /// A `Counter` operating on the value stored at `key`.
+    pub fn counter<'a>(&'a self, key: &'a str) -> Counter<'a> {
+        Counter::new(self, key)
+    }
+
+    /// Returns a handle to the FIFO queue stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the queue is stored.
+    ///
+    /// # Returns
+    /// A `Queue` operating on the array stored at `key`.
+    pub fn queue<'a>(&'a self, key: &'a str) -> Queue<'a> {
+        Queue::new(self, key)
+    }
+
+    /// Creates a table holding fields of the JSON values of the driver's table,
+    /// which tools reading SQL, such as Grafana, can query directly.
+    ///
+    /// The view has an `ID` column holding the key of each entry, followed by
+    /// a column per path named after its fields, such as `stats_level` for
+    /// `$.stats.level`. It is filled from the current entries, then triggers
+    /// keep it in sync with every write to the table, including writes of
+    /// other processes. Creating a view that exists replaces it.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the view's table, in the database of the driver's
+    ///   table.
+    /// - `paths`: The paths of the fields, such as `$.user_id` or
+    ///   `$.stats.level`.
+    ///
+    /// # Returns
+    /// A `Result` which is an `InvalidPath` error if a path is not a path of
+    /// object fields, or a `Config` error if the name or a column name is not a
+    /// valid identifier, two paths map to the same column, or a table that
+    /// isn't a view has the name.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("alice", json!({ "user_id": "1", "xp": 120 })).unwrap();
+    ///
+    /// driver.create_view("levels", &["$.user_id", "$.xp"]).unwrap();
+    /// driver.set("bob", json!({ "user_id": "2", "xp": 80 })).unwrap();
+    ///
+    /// // Both paths would be stored in the column `user_id`.
+    /// assert!(driver.create_view("ids", &["$.user_id", "$.user.id"]).is_err());
+    ///
+    /// let xp: i64 = driver
+    ///     .database
+    ///     .query_row("SELECT SUM(xp) FROM levels", [], |row| row.get(0))
+    ///     .unwrap();
+    /// assert_eq!(xp, 200);
+    /// ```
+    pub fn create_view(&self, name: &str, paths: &[&str]) -> Result<()> {
+        let mut columns: Vec<(String, String)> = Vec::with_capacity(paths.len());
+        for path in paths {
+            let fields = field_path(path)?;
+            let column = fields.join("_");
+            if !is_valid_identifier(&column) || column.eq_ignore_ascii_case("ID") {
+                return Err(ChromoeError::Config(format!(
+                    "`{}` is not a valid column name for `{}`",
+                    column, path
+                )));
+            }
+            // SQLite compares column names ignoring case.
+            if let Some(index) = columns
+                .iter()
+                .position(|(existing, _)| existing.eq_ignore_ascii_case(&column))
+            {
+                return Err(ChromoeError::Config(format!(
+                    "`{}` and `{}` both map to the column `{}`",
+                    paths[index], path, column
+                )));
+            }
+            let quoted: String = fields
+                .iter()
+                .map(|field| format!(".\"{}\"", field.replace('\'', "''")))
+                .collect();
+            columns.push((column, format!("'${}'", quoted)));
+        }
+        if !is_valid_identifier(name) {
+            return Err(ChromoeError::Config(format!(
+                "`{}` is not a valid view name",
+                name
+            )));
+        }
+
+        self.instrument("create_view", None, || {
+            self.flush()?;
+            let (schema, table) = match self.table.split_once('.') {
+                Some((alias, table)) => (format!("{}.", alias), table),
+                None => (String::new(), self.table.as_str()),
+            };
+            let names: String = columns
+                .iter()
+                .map(|(column, _)| format!(", {}", column))
+                .collect();
+            let values = |row: &str| -> String {
+                columns
+                    .iter()
+                    .map(|(_, path)| format!(", json_extract({}JSON, {})", row, path))
+                    .collect()
+            };
+
+            self.atomic(|| {
+                self.drop_view_tables(&schema, table, name)?;
+                // Triggers may only name tables of their own database, so the
+                // tables are referred to without their alias in trigger bodies.
+                self.database.execute_batch(&format!(
+                    "CREATE TABLE {schema}{name} (ID TEXT PRIMARY KEY{names});
+                     INSERT INTO {schema}{name} (ID{names}) SELECT ID{current} FROM {full};
+                     CREATE TRIGGER {schema}{name}_view_insert AFTER INSERT ON {table} \
+                     BEGIN INSERT OR REPLACE INTO {name} (ID{names}) VALUES (NEW.ID{new}); END;
+                     CREATE TRIGGER {schema}{name}_view_update AFTER UPDATE ON {table} \
+                     BEGIN DELETE FROM {name} WHERE ID = OLD.ID; \
+                     INSERT OR REPLACE INTO {name} (ID{names}) VALUES (NEW.ID{new}); END;
+                     CREATE TRIGGER {schema}{name}_view_delete AFTER DELETE ON {table} \
+                     BEGIN DELETE FROM {name} WHERE ID = OLD.ID; END;",
+                    current = values(""),
+                    new = values("NEW."),
+                    full = self.table,
+                ))?;
+                Ok(())
+            })
+        })
+    }
+
+    /// Drops a view created with [`SQLiteDriver::create_view`], along with the
+    /// triggers keeping it in sync.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the view.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if the view existed, or a `Config` error if
+    /// a table that isn't a view of the driver's table has the name.
+    pub fn drop_view(&self, name: &str) -> Result<bool> {
+        self.instrument("drop_view", None, || {
+            let (schema, table) = match self.table.split_once('.') {
+                Some((alias, table)) => (format!("{}.", alias), table),
+                None => (String::new(), self.table.as_str()),
+            };
+            self.atomic(|| self.drop_view_tables(&schema, table, name))
+        })
+    }
+
+    /// Drops the table and triggers of a view, if it exists and is a view of
+    /// the driver's table.
+    fn drop_view_tables(&self, schema: &str, table: &str, name: &str) -> Result<bool> {
+        let master = format!("{}sqlite_master", schema);
+        let exists = |kind: &str, object: &str, of: &str| -> Result<bool> {
+            Ok(self
+                .database
+                .query_row(
+                    &format!(
+                        "SELECT 1 FROM {} WHERE type = ?1 AND name = ?2 AND tbl_name = ?3",
+                        master
+                    ),
+                    params![kind, object, of],
+                    |_| Ok(()),
+                )
+                .optional()?
+                .is_some())
+        };
+
+        if !exists("table", name, name)? {
+            return Ok(false);
+        }
+        if !exists("trigger", &format!("{}_view_insert", name), table)? {
+            return Err(ChromoeError::Config(format!(
+                "`{}` is a table, not a view of `{}`",
+                name, table
+            )));
+        }
+        self.database.execute_batch(&format!(
+            "DROP TRIGGER IF EXISTS {schema}{name}_view_insert;
+             DROP TRIGGER IF EXISTS {schema}{name}_view_update;
+             DROP TRIGGER IF EXISTS {schema}{name}_view_delete;
+             DROP TABLE {schema}{name};"
+        ))?;
+        Ok(true)
+    }
+
+    /// Retrieves the top `n` entries sorted by a numeric field of their JSON value,
+    /// such as the richest users of an economy. Sorting and limiting happen in
+    /// SQL, so only the returned entries are loaded.
+    ///
+    /// Entries where the field is missing or not a number are skipped.
+    ///
+    /// # Parameters
+    /// - `path`: An SQLite JSON path to the field to sort by, such as `$.balance`.
+    ///   Use `$` to sort by entries that are plain numbers.
+    /// - `n`: The maximum number of entries to return.
+    /// - `order`: Whether to return the highest (`Order::Desc`) or lowest
+    ///   (`Order::Asc`) values first.
+    ///
+    /// # Returns
+    /// A `Result` containing the matching entries as key and value tuples, in order.
+    pub fn top_n(&self, path: &str, n: usize, order: Order) -> Result<Vec<(String, Value)>> {
+        self.instrument("top_n", None, || {
+            let mut stmt = self.database.prepare(&format!(
+                "SELECT ID, JSON FROM {} WHERE json_type(JSON, ?1) IN ('integer', 'real') \
+                 ORDER BY json_extract(JSON, ?1) {}, ID LIMIT ?2",
+                self.table,
+                order.as_sql()
+            ))?;
+            let rows = stmt.query_map(params![path, n as i64], |row| {
+                let id: String = row.get(0)?;
+                let json_str: String = row.get(1)?;
+                Ok((id, from_str(&json_str).unwrap_or(Value::Null)))
+            })?;
+
+            Ok(rows.collect::<rusqlite::Result<Vec<(String, Value)>>>()?)
+        })
+    }
+
+    /// Returns a view of the table sorted by a field of its JSON values, for
+    /// paginating through it efficiently, such as a leaderboard.
+    ///
+    /// The first call for a field adds a generated column extracting it to the
+    /// table, along with an index on it, which later writes keep up to date.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the field to sort by, such as `$.xp` or
+    ///   `$.stats.level`.
+    /// - `order`: Whether the view starts with the lowest (`Order::Asc`) or
+    ///   highest (`Order::Desc`) values.
+    ///
+    /// # Returns
+    /// A `Result` containing the sorted view, or an `InvalidPath` error if the
+    /// path is not a path of object fields.
+    pub fn sorted_by(&self, path: &str, order: Order) -> Result<Sorted<'_>> {
+        let fields = field_path(path)?;
+        self.instrument("sorted_by", None, || {
+            let (schema, table) = match self.table.split_once('.') {
+                Some((alias, table)) => (alias, table),
+                None => ("main", self.table.as_str()),
+            };
+            let hex: String = path.bytes().map(|byte| format!("{:02x}", byte)).collect();
+            let column = format!("SORT_{}", hex);
+
+            self.atomic(|| {
+                let exists = self
+                    .database
+                    .query_row(
+                        "SELECT 1 FROM pragma_table_xinfo(?1, ?2) WHERE name = ?3",
+                        params![table, schema, column],
+                        |_| Ok(()),
+                    )
+                    .optional()?
+                    .is_some();
+                if !exists {
+                    let quoted: String = fields
+                        .iter()
+                        .map(|field| format!(".\"{}\"", field.replace('\'', "''")))
+                        .collect();
+                    self.database.execute_batch(&format!(
+                        "ALTER TABLE {table} ADD COLUMN {column} \
+                         GENERATED ALWAYS AS (json_extract(JSON, '${quoted}')) VIRTUAL;
+                         CREATE INDEX IF NOT EXISTS {schema}.{name}_{column} ON {name} ({column}, ID);",
+                        table = self.table,
+                        name = table,
+                    ))?;
+                }
+                Ok(())
+            })?;
+
+            Ok(Sorted::new(self, path, column, order))
+        })
+    }
+
+    /// Builds the query reading a page of the entries sorted by a generated
+    /// column, binding the column value and key of the entry the page starts
+    /// after to `?1` and `?2` if `after` is set, the current time to `?3` and
+    /// the size of the page to `?4`.
+    fn sorted_page_sql(&self, column: &str, order: Order, after: bool) -> String {
+        let mut sql = format!(
+            "SELECT ID, JSON FROM {} WHERE {} IS NOT NULL",
+            self.table, column
+        );
+        if after {
+            let comparison = match order {
+                Order::Asc => ">",
+                Order::Desc => "<",
+            };
+            sql.push_str(&format!(" AND ({}, ID) {} (?1, ?2)", column, comparison));
+        }
+        if self.options.expiry.is_some() {
+            sql.push_str(&format!(
+                " AND ID NOT IN (SELECT ID FROM {}_expiry WHERE EXPIRES_AT <= ?3)",
+                self.table
+            ));
+        }
+        sql.push_str(&format!(
+            " ORDER BY {column} {order}, ID {order} LIMIT ?4",
+            order = order.as_sql()
+        ));
+        sql
+    }
+
+    /// Returns the plan of the query reading the pages of the entries sorted
+    /// by a generated column after the first.
+    pub(crate) fn explain_sorted_page(
+        &self,
+        column: &str,
+        order: Order,
+    ) -> Result<Vec<QueryPlanStep>> {
+        let sql = self.sorted_page_sql(column, order, true);
+        self.explain(
+            &sql,
+            params![
+                SqlValue::Null,
+                SqlValue::Null,
+                unix_millis(SystemTime::now()),
+                1
+            ],
+        )
+    }
+
+    /// Reads a page of the entries sorted by a generated column, starting
+    /// after the given column value and key.
+    pub(crate) fn sorted_page(
+        &self,
+        column: &str,
+        order: Order,
+        after: Option<(&Value, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(String, Value)>> {
+        self.instrument("sorted_page", None, || {
+            let sql = self.sorted_page_sql(column, order, after.is_some());
+
+            // Bound the way `json_extract` returns the field.
+            let (value, key) = match after {
+                Some((value, key)) => (
+                    match value {
+                        Value::Null => SqlValue::Null,
+                        Value::Bool(flag) => SqlValue::Integer(*flag as i64),
+                        Value::Number(number) => match number.as_i64() {
+                            Some(number) => SqlValue::Integer(number),
+                            None => SqlValue::Real(number.as_f64().unwrap_or(f64::NAN)),
+                        },
+                        Value::String(text) => SqlValue::Text(text.clone()),
+                        other => SqlValue::Text(other.to_string()),
+                    },
+                    Some(key),
+                ),
+                None => (SqlValue::Null, None),
+            };
+
+            let mut stmt = self.database.prepare(&sql)?;
+            let rows = stmt
+                .query_map(
+                    params![
+                        value,
+                        key,
+                        unix_millis(SystemTime::now()),
+                        limit.min(i64::MAX as usize) as i64
+                    ],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+                )?
+                .collect::<rusqlite::Result<Vec<_>>>()?;
+
+            rows.into_iter()
+                .map(|(key, json)| match from_str(&json) {
+                    Ok(value) => Ok((key, value)),
+                    Err(source) => Err(ChromoeError::Corrupted { key, source }),
+                })
+                .collect()
+        })
+    }
+
+    /// Computes the sum of a numeric field across all entries.
+    ///
+    /// # Parameters
+    /// - `path`: An SQLite JSON path to the field, such as `$.balance`.
+    /// - `prefix`: Only include entries whose key starts with this prefix.
+    ///
+    /// # Returns
+    /// A `Result` containing the sum, which is `0.0` when no entry has the field.
+    pub fn sum(&self, path: &str, prefix: Option<&str>) -> Result<f64> {
+        self.instrument("sum", None, || {
+            Ok(self.aggregate("TOTAL", path, prefix)?.unwrap_or(0.0))
+        })
+    }
+
+    /// Computes the average of a numeric field across all entries.
+    ///
+    /// # Parameters
+    /// - `path`: An SQLite JSON path to the field, such as `$.balance`.
+    /// - `prefix`: Only include entries whose key starts with this prefix.
+    ///
+    /// # Returns
+    /// A `Result` containing the average, or `None` when no entry has the field.
+    pub fn avg(&self, path: &str, prefix: Option<&str>) -> Result<Option<f64>> {
+        self.instrument("avg", None, || self.aggregate("AVG", path, prefix))
+    }
+
+    /// Finds the smallest value of a numeric field across all entries.
+    ///
+    /// # Parameters
+    /// - `path`: An SQLite JSON path to the field, such as `$.balance`.
+    /// - `prefix`: Only include entries whose key starts with this prefix.
+    ///
+    /// # Returns
+    /// A `Result` containing the minimum, or `None` when no entry has the field.
+    pub fn min(&self, path: &str, prefix: Option<&str>) -> Result<Option<f64>> {
+        self.instrument("min", None, || self.aggregate("MIN", path, prefix))
+    }
+
+    /// Finds the largest value of a numeric field across all entries.
+    ///
+    /// # Parameters
+    /// - `path`: An SQLite JSON path to the field, such as `$.balance`.
+    /// - `prefix`: Only include entries whose key starts with this prefix.
+    ///
+    /// # Returns
+    /// A `Result` containing the maximum, or `None` when no entry has the field.
+    pub fn max(&self, path: &str, prefix: Option<&str>) -> Result<Option<f64>> {
+        self.instrument("max", None, || self.aggregate("MAX", path, prefix))
+    }
+
+    /// Counts the entries where a field equals the given value.
+    ///
+    /// # Parameters
+    /// - `path`: An SQLite JSON path to the field, such as `$.premium`.
+    /// - `value`: The value the field must be equal to.
+    /// - `prefix`: Only include entries whose key starts with this prefix.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of matching entries.
+    pub fn count_where<T>(&self, path: &str, value: T, prefix: Option<&str>) -> Result<u64>
+    where
+        T: Serialize,
+    {
+        self.instrument("count_where", None, || {
+            let expected = to_string(&value)?;
+            let count = self.database.query_row(
+                &format!(
+                    "SELECT COUNT(*) FROM {} WHERE (JSON -> ?1) = json(?2) \
+                     AND (?3 IS NULL OR substr(ID, 1, length(?3)) = ?3)",
+                    self.table
+                ),
+                params![path, expected, prefix],
+                |row| row.get(0),
+            )?;
+            Ok(count)
+        })
+    }
+
+    /// Computes an aggregate for every distinct value of a field, such as the
+    /// total balance per guild. Grouping happens in SQL.
+    ///
+    /// Entries without the grouping field are skipped, as are entries without a
+    /// numeric value for the aggregated field.
+    ///
+    /// # Parameters
+    /// - `path`: An SQLite JSON path to the field to group by, such as `$.guild_id`.
+    /// - `aggregate`: The aggregate to compute for every group.
+    ///
+    /// # Returns
+    /// A `Result` containing the group values and their aggregates, ordered by group.
+    pub fn group_by(&self, path: &str, aggregate: Aggregate<'_>) -> Result<Vec<(Value, f64)>> {
+        self.instrument("group_by", None, || {
+            let (function, field) = match aggregate {
+                Aggregate::Count => ("COUNT", None),
+                Aggregate::Sum(field) => ("TOTAL", Some(field)),
+                Aggregate::Avg(field) => ("AVG", Some(field)),
+                Aggregate::Min(field) => ("MIN", Some(field)),
+                Aggregate::Max(field) => ("MAX", Some(field)),
+            };
+            let expression = match field {
+                Some(_) => format!("{}(json_extract(JSON, ?2))", function),
+                None => "COUNT(*)".to_string(),
+            };
+
+            let mut stmt = self.database.prepare(&format!(
+                "SELECT JSON -> ?1 AS grp, {} FROM {} WHERE json_type(JSON, ?1) IS NOT NULL \
+                 AND (?2 IS NULL OR json_type(JSON, ?2) IN ('integer', 'real')) \
+                 GROUP BY grp ORDER BY grp",
+                expression, self.table
+            ))?;
+            let rows = stmt.query_map(params![path, field], |row| {
+                let group: String = row.get(0)?;
+                let value: f64 = row.get(1)?;
+                Ok((from_str(&group).unwrap_or(Value::Null), value))
+            })?;
+
+            Ok(rows.collect::<rusqlite::Result<Vec<(Value, f64)>>>()?)
+        })
+    }
+
+    /// Applies an SQL aggregate function to a numeric field across all entries.
+    ///
+    /// # Parameters
+    /// - `function`: The SQL aggregate function, such as `AVG`.
+    /// - `path`: An SQLite JSON path to the field.
+    /// - `prefix`: Only include entries whose key starts with this prefix.
+    ///
+    /// # Returns
+    /// A `Result` containing the aggregate, or `None` when no entry has the field.
+    fn aggregate(&self, function: &str, path: &str, prefix: Option<&str>) -> Result<Option<f64>> {
+        let value = self.database.query_row(
+            &format!(
+                "SELECT {}(json_extract(JSON, ?1)) FROM {} \
+                 WHERE json_type(JSON, ?1) IN ('integer', 'real') \
+                 AND (?2 IS NULL OR substr(ID, 1, length(?2)) = ?2)",
+                function, self.table
+            ),
+            params![path, prefix],
+            |row| row.get(0),
+        )?;
+        Ok(value)
+    }
+
+    /// Deletes a specific entry by key. If the key refers to a nested value,
+    /// it will remove the nested field within the JSON data.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to delete.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if a value was deleted, or `false` if there
+    /// was no value at `key`.
+    pub fn delete(&self, key: &str) -> Result<bool> {
+        self.instrument("delete", Some(key), || {
+            let parts = self.split_path(key);
+            if parts.len() == 1 {
+                return self.delete_row_key(&parts[0]);
+            }
+
+            self.atomic(|| {
+                let Some(mut root_value) = self.get_row_key::<Value>(&parts[0])? else {
+                    return Ok(false);
+                };
+
+                let path: Vec<&str> = parts[1..].iter().map(String::as_str).collect();
+                let removed = remove_path(&mut root_value, &path)?;
+                if removed {
+                    let json_string = to_string(&root_value)?;
+                    self.write_row_key(&parts[0], json_string)?;
+                }
+                Ok(removed)
+            })
+        })
+    }
+
+    /// Deletes several nested fields of the value at a key with a single write,
+    /// such as clearing some of a user's settings at once.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the value holding the fields, which may itself be nested.
+    /// - `paths`: The paths of the fields to delete, relative to `key`.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of fields deleted, or a `TypeMismatch`
+    /// error if a path leads through a value that is not an object, in which case
+    /// nothing is deleted.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver
+    ///     .set("settings", json!({ "theme": "dark", "lang": "en", "alerts": { "email": true } }))
+    ///     .unwrap();
+    ///
+    /// let deleted = driver
+    ///     .delete_many_nested("settings", &["theme", "alerts.email", "missing"])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(deleted, 2);
+    /// assert_eq!(
+    ///     driver.get::<serde_json::Value>("settings").unwrap(),
+    ///     Some(json!({ "lang": "en", "alerts": {} }))
+    /// );
+    /// ```
+    pub fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        self.instrument("delete_many_nested", Some(key), || {
+            self.atomic(|| {
+                let parts = self.split_path(key);
+                let Some(mut root_value) = self.get_row_key::<Value>(&parts[0])? else {
+                    return Ok(0);
+                };
+
+                let mut deleted = 0;
+                for path in paths {
+                    let relative = self.split_path(path);
+                    let path: Vec<&str> = parts[1..]
+                        .iter()
+                        .chain(&relative)
+                        .map(String::as_str)
+                        .collect();
+                    if remove_path(&mut root_value, &path)? {
+                        deleted += 1;
+                    }
+                }
+
+                if deleted > 0 {
+                    let json_string = to_string(&root_value)?;
+                    self.write_row_key(&parts[0], json_string)?;
+                }
+                Ok(deleted)
+            })
+        })
+    }
+
+    /// Deletes all entries in the database.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the deletion was successful.
+    pub fn delete_all(&self) -> Result<bool> {
+        self.instrument("delete_all", None, || self.delete_rows())
+    }
+
+    /// Deletes a specific row from the table by key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to delete.
+    ///
+    /// # Returns
+    /// A `Result` containing whether a row was deleted.
+    fn delete_row_key(&self, key: &str) -> Result<bool> {
+        if self.options.write_buffer.is_some() && self.database.is_autocommit() {
+            let existed = self.get_row_key::<Value>(key)?.is_some();
+            self.buffer_write(key, None)?;
+            return Ok(existed);
+        }
+
+        let deleted = self
+            .database
+            .prepare(&format!("DELETE FROM {} WHERE ID = ?", self.table))?
+            .execute(params![key])?;
+        Ok(deleted > 0)
+    }
+
+    /// Deletes all rows from the table.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the deletion was successful.
+    fn delete_rows(&self) -> Result<bool> {
+        self.write_buffer.borrow_mut().clear();
+        self.database
+            .prepare(&format!("DELETE FROM {}", self.table))?
+            .execute([])?;
+        Ok(true)
+    }
+
+    /// Retrieves the value for a given key, potentially deserialising it into the specified type.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` of the deserialised value, or a
+    /// `Corrupted` error if the stored JSON cannot be parsed, or a
+    /// `TypeMismatch` error if the value cannot be deserialised into `T`.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned + Default,
+    {
+        self.instrument("get", Some(key), || {
+            let parts = self.split_path(key);
+            self.read_json(&parts, |json| deserialize_json(&parts[0], json))
+        })
+    }
+
+    /// Reads the value stored at a key by giving its stored JSON text to a
+    /// function, so it can be deserialised into a type borrowing from the
+    /// text, such as one with `&str` fields, instead of an owned copy.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to read, which may refer to a nested
+    ///   value.
+    /// - `read`: The function given the JSON text of the value.
+    ///
+    /// # Returns
+    /// A `Result` containing the result of `read`, or `None` if there is no
+    /// value at `key`, or a `Corrupted` error if the stored JSON is not valid,
+    /// or the error returned by `read`.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde::Deserialize;
+    /// use serde_json::json;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Profile<'a> {
+    ///     name: &'a str,
+    /// }
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("users", json!({ "alice": { "name": "Alice" } })).unwrap();
+    ///
+    /// let length = driver
+    ///     .get_with("users.alice", |json| {
+    ///         let profile: Profile = serde_json::from_str(json)?;
+    ///         Ok(profile.name.len())
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(length, Some(5));
+    /// ```
+    pub fn get_with<R, F>(&self, key: &str, read: F) -> Result<Option<R>>
+    where
+        F: FnOnce(&str) -> Result<R>,
+    {
+        self.instrument("get_with", Some(key), || {
+            self.read_json(&self.split_path(key), read)
+        })
+    }
+
+    /// Gives the JSON text of the value at a path to a function, reading the
+    /// row through the write buffer. Nested values are found within the text
+    /// of the row, without parsing the values around them.
+    ///
+    /// # Parameters
+    /// - `parts`: The row key followed by the path within the row.
+    /// - `read`: The function given the JSON text of the value.
+    ///
+    /// # Returns
+    /// A `Result` containing the result of `read`, or `None` if there is no
+    /// value at the path, or a `Corrupted` error if the row is not valid JSON.
+    fn read_json<R, F>(&self, parts: &[String], read: F) -> Result<Option<R>>
+    where
+        F: FnOnce(&str) -> Result<R>,
+    {
+        let Some(json_str) = self.get_row_json(&parts[0])? else {
+            return Ok(None);
+        };
+        let root: &RawValue = from_str(&json_str).map_err(|source| ChromoeError::Corrupted {
+            key: parts[0].clone(),
+            source,
+        })?;
+        let path: Vec<&str> = parts[1..].iter().map(String::as_str).collect();
+        lookup_raw(root, &path)
+            .map(|value| read(value.get()))
+            .transpose()
+    }
+
+    /// Queries the value stored at a key with a JSONPath expression (RFC 9535),
+    /// such as `$.items[?@.rarity == 'legendary'].name`, returning every value
+    /// it selects.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the value to query, which may refer to a nested value.
+    /// - `path`: The JSONPath expression, evaluated with the value at `key` as
+    ///   its root.
+    ///
+    /// # Returns
+    /// A `Result` containing the selected values in document order, which is
+    /// empty if the key doesn't exist, or an `InvalidPath` error if the
+    /// expression cannot be parsed, or a `Json` error if a selected value
+    /// cannot be deserialised into `T`.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver
+    ///     .set("inventory", json!({ "items": [
+    ///         { "name": "Excalibur", "rarity": "legendary" },
+    ///         { "name": "Stick", "rarity": "common" },
+    ///     ] }))
+    ///     .unwrap();
+    ///
+    /// let names: Vec<String> = driver
+    ///     .get_path("inventory", "$.items[?(@.rarity=='legendary')].name")
+    ///     .unwrap();
+    /// assert_eq!(names, ["Excalibur"]);
+    /// ```
+    #[cfg(feature = "jsonpath")]
+    pub fn get_path<T>(&self, key: &str, path: &str) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let query = serde_json_path::JsonPath::parse(path)
+            .map_err(|err| ChromoeError::InvalidPath(err.to_string()))?;
+
+        self.instrument("get_path", Some(key), || {
+            let Some(value) = self.get::<Value>(key)? else {
+                return Ok(Vec::new());
+            };
+
+            query
+                .query(&value)
+                .all()
+                .into_iter()
+                .map(|selected| Ok(serde_json::from_value::<T>(selected.clone())?))
+                .collect()
+        })
+    }
+
+    /// Retrieves only the selected fields of the value stored at a key, as an
+    /// object holding each field at its path.
+    ///
+    /// The fields are extracted by SQLite with `json_extract`, so only they are
+    /// parsed and returned, which keeps reads of a few fields of large documents
+    /// cheap. Fields that don't exist are left out of the object.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the value, which may refer to a nested value.
+    /// - `paths`: The paths of the fields to retrieve, relative to the value at
+    ///   `key`, such as `$.name` and `$.stats.level`.
+    ///
+    /// # Returns
+    /// A `Result` containing the projected object, or `None` if the key doesn't
+    /// exist, or an `InvalidPath` error if a path is not a path of object fields.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::{json, Value};
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver
+    ///     .set("player", json!({ "name": "Reina", "stats": { "level": 12, "xp": 300 }, "log": [] }))
+    ///     .unwrap();
+    ///
+    /// let projected: Option<Value> = driver
+    ///     .get_projected("player", &["$.name", "$.stats.level", "$.missing"])
+    ///     .unwrap();
+    /// assert_eq!(projected, Some(json!({ "name": "Reina", "stats": { "level": 12 } })));
+    /// ```
+    pub fn get_projected<T>(&self, key: &str, paths: &[&str]) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        let fields = paths
+            .iter()
+            .map(|path| field_path(path))
+            .collect::<Result<Vec<Vec<&str>>>>()?;
+
+        self.instrument("get_projected", Some(key), || {
+            let parts = self.split_path(key);
+            let base: String = std::iter::once("$".to_string())
+                .chain(parts[1..].iter().map(|part| format!(".\"{}\"", part)))
+                .collect();
+
+            let columns: String = (0..paths.len())
+                .map(|i| format!(", JSON -> ?{}", i + 3))
+                .collect();
+            let mut args = vec![parts[0].clone(), base.clone()];
+            args.extend(fields.iter().map(|fields| {
+                fields.iter().fold(base.clone(), |path, field| {
+                    format!("{}.\"{}\"", path, field)
+                })
+            }));
+
+            let row = self
+                .database
+                .prepare(&format!(
+                    "SELECT (JSON -> ?2) IS NOT NULL{} FROM {} WHERE ID = ?1",
+                    columns, self.table
+                ))?
+                .query_row(rusqlite::params_from_iter(&args), |row| {
+                    let exists: bool = row.get(0)?;
+                    let values = (1..=paths.len())
+                        .map(|i| row.get::<_, Option<String>>(i))
+                        .collect::<rusqlite::Result<Vec<_>>>()?;
+                    Ok(exists.then_some(values))
+                })
+                .optional()?
+                .flatten();
+            let Some(values) = row else {
+                return Ok(None);
+            };
+
+            let mut projected = json!({});
+            for (fields, value) in fields.iter().zip(values) {
+                let Some(value) = value else {
+                    continue;
+                };
+                let (last, parents) = fields.split_last().expect("paths have a field");
+                let mut current = &mut projected;
+                for field in parents {
+                    current = current
+                        .as_object_mut()
+                        .expect("projections only hold objects")
+                        .entry(field.to_string())
+                        .or_insert_with(|| json!({}));
+                }
+                if let Some(object) = current.as_object_mut() {
+                    object.insert(last.to_string(), from_str(&value)?);
+                }
+            }
+            Ok(Some(serde_json::from_value(projected)?))
+        })
+    }
+
+    /// Retrieves a field of the value stored at a key as a specific type.
+    ///
+    /// The path is given separately from the key, which keeps reads of fields
+    /// of a computed key free of string formatting.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the value, which may refer to a nested value.
+    /// - `path`: The path of the field, relative to the value at `key`, such as
+    ///   `stats.level`, or an empty path for the value itself.
+    ///
+    /// # Returns
+    /// A `Result` containing the field, or `None` if it doesn't exist, or a
+    /// `TypeMismatch` error if it cannot be deserialised into `T`.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::error::ChromoeError;
+    /// use chromoe_db::structure::JsonType;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("player", json!({ "name": "Reina", "stats": { "level": 12 } })).unwrap();
+    ///
+    /// assert_eq!(driver.get_field::<u32>("player", "stats.level").unwrap(), Some(12));
+    /// assert_eq!(driver.get_field::<u32>("player", "stats.xp").unwrap(), None);
+    ///
+    /// match driver.get_field::<u32>("player", "name") {
+    ///     Err(ChromoeError::TypeMismatch { expected, found }) => {
+    ///         assert_eq!(expected, "u32");
+    ///         assert_eq!(found, JsonType::String);
+    ///     }
+    ///     other => panic!("unexpected result: {:?}", other),
+    /// }
+    /// ```
+    pub fn get_field<T>(&self, key: &str, path: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.instrument("get_field", Some(key), || {
+            let mut parts = self.split_path(key);
+            if !path.is_empty() {
+                parts.extend(self.split_path(path));
+            }
+
+            self.read_json(&parts, |json| deserialize_json(&parts[0], json))
+        })
+    }
+
+    /// Retrieves the value stored at a key, without splitting the key into a
+    /// nested path, so keys containing the path separator, such as domain
+    /// names, are used as they are.
+    ///
+    /// # Parameters
+    /// - `key`: The exact key of the entry to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` of the deserialised value.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set_raw("example.com", 3).unwrap();
+    ///
+    /// assert_eq!(driver.get_raw::<i32>("example.com").unwrap(), Some(3));
+    /// assert_eq!(driver.get::<i32>("example\\.com").unwrap(), Some(3));
+    /// assert_eq!(driver.get::<i32>("example.com").unwrap(), None);
+    /// ```
+    pub fn get_raw<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.instrument("get_raw", Some(key), || self.get_row_key(key))
+    }
+
+    /// Retrieves the JSON text of the value stored at a key, without parsing
+    /// it into a `Value`, for callers forwarding it as it is, such as over HTTP.
+    ///
+    /// The stored text of a whole entry is returned as it is, so reading a
+    /// large document allocates one string. The text of a nested value is
+    /// copied out of the text of its entry.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve, which may refer to a nested
+    ///   value.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` of the JSON text, or a `Corrupted`
+    /// error if the stored JSON is not valid.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("user", json!({ "name": "Alice", "stats": { "level": 3 } })).unwrap();
+    ///
+    /// let raw = driver.get_json("user").unwrap().unwrap();
+    /// assert_eq!(raw.get(), r#"{"name":"Alice","stats":{"level":3}}"#);
+    /// assert_eq!(driver.get_json("user.stats").unwrap().unwrap().get(), r#"{"level":3}"#);
+    /// assert!(driver.get_json("guest").unwrap().is_none());
+    /// ```
+    pub fn get_json(&self, key: &str) -> Result<Option<Box<RawValue>>> {
+        self.instrument("get_json", Some(key), || {
+            let parts = self.split_path(key);
+            if parts.len() > 1 {
+                return self.read_json(&parts, |json| Ok(RawValue::from_string(json.to_string())?));
+            }
+
+            let Some(json_str) = self.get_row_json(key)? else {
+                return Ok(None);
+            };
+            RawValue::from_string(json_str)
+                .map(Some)
+                .map_err(|source| ChromoeError::Corrupted {
+                    key: key.to_string(),
+                    source,
+                })
+        })
+    }
+
+    /// Splits a key into the row key and the path of the nested value within
+    /// it, at every unescaped path separator.
+    ///
+    /// A backslash escapes the character following it, so `\.` stands for a
+    /// literal `.` and `\\` for a literal backslash.
+    pub(crate) fn split_path(&self, key: &str) -> Vec<String> {
+        split_escaped(key, self.options.path_separator)
+    }
+
+    /// Retrieves a value for a key, directly from the row.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing the deserialised value, or `None` if the key doesn't
+    /// exist, or a `Corrupted` error if the stored JSON cannot be parsed, or a
+    /// `TypeMismatch` error if it cannot be deserialised into `T`.
+    fn get_row_key<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        match self.get_row_json(key)? {
+            Some(json_str) => deserialize_json(key, &json_str).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Retrieves the serialised JSON value of a row, through the write buffer,
+    /// after verifying its checksum.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the row to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing the JSON text, or `None` if the key doesn't exist,
+    /// or a `ChecksumMismatch` error if it doesn't match its checksum.
+    fn get_row_json(&self, key: &str) -> Result<Option<String>> {
+        let buffered = self.write_buffer.borrow().entries.get(key).cloned();
+        let row = match buffered.clone() {
+            Some(pending) => pending.map(|json| (json, None)),
+            None if self.options.expiry.is_some() => self
+                .database
+                .prepare_cached(&format!(
+                    "SELECT JSON, {1} FROM {0} WHERE ID = ?1 AND NOT EXISTS \
+                     (SELECT 1 FROM {0}_expiry WHERE ID = ?1 AND EXPIRES_AT <= ?2)",
+                    self.table,
+                    self.checksum_column()
+                ))?
+                .query_row(params![key, unix_millis(SystemTime::now())], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get(1)?))
+                })
+                .optional()?,
+            None => self
+                .database
+                .prepare_cached(&format!(
+                    "SELECT JSON, {1} FROM {0} WHERE ID = ?",
+                    self.table,
+                    self.checksum_column()
+                ))?
+                .query_row(params![key], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get(1)?))
+                })
+                .optional()?,
+        };
+
+        let Some((json_str, stored)) = row else {
+            return Ok(None);
+        };
+        self.verify_checksum(key, &json_str, stored)?;
+        if buffered.is_none() {
+            self.touch(key)?;
+        }
+        Ok(Some(json_str))
+    }
+
+    /// Stores the serialised value of a row, either directly or through the
+    /// write buffer when buffering is enabled.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the row.
+    /// - `json`: The serialised JSON value of the row.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the row was stored or buffered.
+    fn write_row_key(&self, key: &str, json: String) -> Result<()> {
+        if self.options.write_buffer.is_some() && self.database.is_autocommit() {
+            self.enforce_quotas(key, &json)?;
+            return self.buffer_write(key, Some(json));
+        }
+
+        self.upsert_row_key(key, &json)
+    }
+
+    /// Inserts or replaces the serialised value of a row in the table, within
+    /// the capacity of the cache and the quotas of the driver.
+    fn upsert_row_key(&self, key: &str, json: &str) -> Result<()> {
+        if self.options.cache.is_none() && self.options.quotas.is_empty() {
+            return self.store_row_key(key, json);
+        }
+
+        self.atomic(|| {
+            self.enforce_quotas(key, json)?;
+            self.store_row_key(key, json)
+        })
+    }
+
+    /// Inserts or replaces the serialised value of a row in the table,
+    /// without checking the quotas.
+    fn store_row_key(&self, key: &str, json: &str) -> Result<()> {
+        self.database
+            .prepare_cached(&format!(
+                "INSERT INTO {} (ID, JSON) VALUES (?1, ?2) ON CONFLICT(ID) DO UPDATE SET JSON = ?2",
+                self.table
+            ))?
+            .execute(params![key, json])?;
+        Ok(())
+    }
+
+    /// Records a pending write of a row, `None` meaning the row is deleted, and
+    /// flushes the buffer once it holds the configured number of operations.
+    fn buffer_write(&self, key: &str, json: Option<String>) -> Result<()> {
+        let full = {
+            let mut buffer = self.write_buffer.borrow_mut();
+            buffer.since.get_or_insert_with(Instant::now);
+            buffer.entries.insert(key.to_string(), json);
+            buffer.operations += 1;
+
+            let limit = self
+                .options
+                .write_buffer
+                .as_ref()
+                .map_or(0, |options| options.max_operations);
+            buffer.operations >= limit
+        };
+
+        if full {
+            self.flush_buffer()?;
+        }
+        Ok(())
+    }
+
+    /// Writes every buffered write to the database in a single transaction.
+    ///
+    /// Writes to the same key are coalesced, so only the latest value of every
+    /// key is written. Without write buffering this does nothing.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of rows written.
+    pub fn flush(&self) -> Result<usize> {
+        self.instrument("flush", None, || self.flush_buffer())
+    }
+
+    /// Checkpoints the write-ahead log, copying its pages into the database
+    /// file. Without write-ahead logging this does nothing.
+    ///
+    /// SQLite checkpoints the log when a commit makes it larger than the
+    /// `auto_checkpoint` threshold, but automatic checkpoints never wait for
+    /// readers, so a log kept busy by long-lived readers keeps growing. A
+    /// `Restart` or `Truncate` checkpoint run at a quiet time bounds its size.
+    ///
+    /// # Parameters
+    /// - `mode`: How much work the checkpoint does, and whether it waits for
+    ///   other connections.
+    ///
+    /// # Returns
+    /// A `Result` containing a [`CheckpointReport`] on the pages copied, which
+    /// is not `complete` if other connections kept pages from being copied.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::{CheckpointMode, SQLiteDriverOptions};
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::temp_with(SQLiteDriverOptions {
+    ///     wal: true,
+    ///     auto_checkpoint: Some(0),
+    ///     ..Default::default()
+    /// })
+    /// .unwrap();
+    /// driver.set("user", json!({ "coins": 100 })).unwrap();
+    ///
+    /// let report = driver.checkpoint(CheckpointMode::Full).unwrap();
+    /// assert!(report.complete);
+    /// assert!(report.wal_frames > 0);
+    ///
+    /// // The log is now empty, so truncating it leaves no frames.
+    /// let report = driver.checkpoint(CheckpointMode::Truncate).unwrap();
+    /// assert_eq!(report.wal_frames, 0);
+    /// ```
+    pub fn checkpoint(&self, mode: CheckpointMode) -> Result<CheckpointReport> {
+        self.instrument("checkpoint", None, || self.checkpoint_wal(mode))
+    }
+
+    /// Checkpoints the write-ahead log without instrumenting the checkpoint.
+    fn checkpoint_wal(&self, mode: CheckpointMode) -> Result<CheckpointReport> {
+        let mode = match mode {
+            CheckpointMode::Passive => "PASSIVE",
+            CheckpointMode::Full => "FULL",
+            CheckpointMode::Restart => "RESTART",
+            CheckpointMode::Truncate => "TRUNCATE",
+        };
+        let (busy, wal_frames, checkpointed_frames) =
+            self.database
+                .query_row(&format!("PRAGMA wal_checkpoint({})", mode), [], |row| {
+                    Ok((
+                        row.get::<_, i64>(0)?,
+                        row.get::<_, i64>(1)?,
+                        row.get::<_, i64>(2)?,
+                    ))
+                })?;
+        // Both counts are -1 when the database doesn't use write-ahead logging.
+        let wal_frames = wal_frames.max(0) as u64;
+        let checkpointed_frames = checkpointed_frames.max(0) as u64;
+        Ok(CheckpointReport {
+            complete: busy == 0 && checkpointed_frames == wal_frames,
+            wal_frames,
+            checkpointed_frames,
+        })
+    }
+
+    /// Closes the driver, making sure everything written is in the database
+    /// file before returning.
+    ///
+    /// The write buffer is flushed, the background threads deleting expired
+    /// entries and enforcing the retention policy are stopped, the write-ahead
+    /// log is checkpointed and the connection is closed. Dropping the driver
+    /// does the same, but cannot report errors, and only checkpoints the
+    /// write-ahead log as far as other connections allow without waiting.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the driver was closed, or the first error
+    /// encountered closing it.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::temp().unwrap();
+    /// driver.set("user", json!({ "coins": 100 })).unwrap();
+    /// driver.close().unwrap();
+    /// ```
+    pub fn close(mut self) -> Result<()> {
+        self.flush()?;
+        self.stop_background_threads();
+        self.checkpoint(CheckpointMode::Truncate)?;
+
+        let database = std::mem::replace(&mut self.database, Connection::open_in_memory()?);
+        database.close().map_err(|(_, err)| err)?;
+        Ok(())
+    }
+
+    /// Stops the background threads of the driver, waiting for the work they
+    /// are doing to finish.
+    fn stop_background_threads(&mut self) {
+        self.sweeper.take();
+        self.retention.take();
+    }
+
+    /// Returns the number of keys with writes waiting in the write buffer.
+    pub fn pending_writes(&self) -> usize {
+        self.write_buffer.borrow().entries.len()
+    }
+
+    /// Flushes the write buffer without instrumenting the flush itself.
+    fn flush_buffer(&self) -> Result<usize> {
+        let entries = std::mem::take(&mut *self.write_buffer.borrow_mut()).entries;
+        if entries.is_empty() {
+            return Ok(0);
+        }
+
+        let count = entries.len();
+        let written = self.atomic(|| {
+            for (key, json) in &entries {
+                match json {
+                    // Quotas were checked when the write was buffered.
+                    Some(json) => self.store_row_key(key, json)?,
+                    None => {
+                        self.database
+                            .prepare_cached(&format!("DELETE FROM {} WHERE ID = ?", self.table))?
+                            .execute(params![key])?;
+                    }
+                }
+            }
+            Ok(())
+        });
+
+        if let Err(err) = written {
+            // Keep the writes so a later flush can retry them, without
+            // overwriting anything buffered since.
+            let mut buffer = self.write_buffer.borrow_mut();
+            for (key, json) in entries {
+                buffer.entries.entry(key).or_insert(json);
+            }
+            buffer.since.get_or_insert_with(Instant::now);
+            return Err(err);
+        }
+
+        Ok(count)
+    }
+
+    /// Flushes the write buffer if its oldest write has waited for longer than
+    /// the configured maximum age.
+    fn flush_if_due(&self) -> Result<()> {
+        let due = match (&self.options.write_buffer, self.write_buffer.borrow().since) {
+            (Some(options), Some(since)) => since.elapsed() >= options.max_age,
+            _ => false,
+        };
+
+        if due {
+            self.flush_buffer()?;
+        }
+        Ok(())
+    }
+
+    /// Stores a value under a newly generated key, for records without a natural
+    /// key. Keys are generated by the `key_generator` of the options, a random
+    /// UUID by default, and are never split into nested paths.
+    ///
+    /// # Parameters
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` containing the generated key.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:?keys=uuid7").unwrap();
+    ///
+    /// let key = driver.insert(json!({ "reason": "spam" })).unwrap();
+    /// assert_eq!(driver.get::<serde_json::Value>(&key).unwrap(), Some(json!({ "reason": "spam" })));
+    /// ```
+    pub fn insert<T>(&self, value: T) -> Result<String>
+    where
+        T: Serialize,
+    {
+        self.insert_with(|_| value)
+    }
+
+    /// Stores a value built from a newly generated key, for values that embed
+    /// their own key, as described for [`SQLiteDriver::insert`].
+    ///
+    /// # Parameters
+    /// - `build`: A function building the value to store from the generated key.
+    ///
+    /// # Returns
+    /// A `Result` containing the generated key.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use serde_json::json;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    ///
+    /// let key = driver.insert_with(|id| json!({ "id": id, "status": "open" })).unwrap();
+    /// let ticket: serde_json::Value = driver.get(&key).unwrap().unwrap();
+    /// assert_eq!(ticket["id"], json!(key));
+    /// ```
+    pub fn insert_with<T, F>(&self, build: F) -> Result<String>
+    where
+        T: Serialize,
+        F: FnOnce(&str) -> T,
+    {
+        let key = self.options.key_generator.generate();
+
+        self.instrument("insert", Some(&key), || {
+            let json_string = to_string(&build(&key))
+                .map_err(|e: SerdeJsonError| RusqliteError::ToSqlConversionFailure(Box::new(e)))?;
+            self.write_row_key(&key, json_string)
+        })?;
+        Ok(key)
+    }
+
+    /// Checks if a given key exists in the database.
+    ///
+    /// # Parameters
+    /// - `key`: The key to check.
+    ///
+    /// # Returns
+    /// A `Result` containing a boolean indicating whether the key exists.
+    pub fn has(&self, key: &str) -> Result<bool> {
+        self.instrument("has", Some(key), || Ok(self.get::<Value>(key)?.is_some()))
+    }
+
+    /// Returns the size of the value stored at a key once serialised to JSON.
+    /// Nested keys report the size of the nested value only.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to inspect.
+    ///
+    /// # Returns
+    /// A `Result` containing the serialised length in bytes, or `None` if the key
+    /// doesn't exist.
+    pub fn size_of(&self, key: &str) -> Result<Option<usize>> {
+        Ok(self.get::<Value>(key)?.map(|value| value.to_string().len()))
+    }
+
+    /// Stores the current time at a key, such as when a user last claimed a
+    /// daily reward, as a number of milliseconds since the Unix epoch.
+    ///
+    /// # Parameters
+    /// - `key`: The key to store the time at, which may refer to a nested value.
+    ///
+    /// # Returns
+    /// A `Result` containing the time stored.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set_timestamp("daily.alice").unwrap();
+    ///
+    /// let cooldown = Duration::from_secs(24 * 60 * 60);
+    /// let elapsed = driver.elapsed_since("daily.alice").unwrap().unwrap();
+    /// assert!(elapsed < cooldown);
+    /// ```
+    pub fn set_timestamp(&self, key: &str) -> Result<SystemTime> {
+        let now = SystemTime::now();
+        self.set_timestamp_at(key, now)?;
+        Ok(now)
+    }
+
+    /// Stores a time at a key, as a number of milliseconds since the Unix
+    /// epoch.
+    ///
+    /// Any type converting into a `SystemTime` is accepted, such as
+    /// `chrono::DateTime` and `time::OffsetDateTime`.
+    ///
+    /// # Parameters
+    /// - `key`: The key to store the time at, which may refer to a nested value.
+    /// - `time`: The time to store. Times before the Unix epoch are stored as
+    ///   the epoch.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the time was stored.
+    pub fn set_timestamp_at<T>(&self, key: &str, time: T) -> Result<()>
+    where
+        T: Into<SystemTime>,
+    {
+        self.set(key, unix_millis(time.into()))
+    }
+
+    /// Retrieves a time stored with [`SQLiteDriver::set_timestamp`] or
+    /// [`SQLiteDriver::set_timestamp_at`].
+    ///
+    /// Any type converting from a `SystemTime` can be returned, such as
+    /// `chrono::DateTime<Utc>` and `time::OffsetDateTime`.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the time, which may refer to a nested value.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` of the time, or a `TypeMismatch`
+    /// error if the value at `key` is not a whole number of milliseconds.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// let claimed = UNIX_EPOCH + Duration::from_millis(1_700_000_000_000);
+    /// driver.set_timestamp_at("claimed", claimed).unwrap();
+    ///
+    /// assert_eq!(driver.get::<i64>("claimed").unwrap(), Some(1_700_000_000_000));
+    /// assert_eq!(driver.get_timestamp::<SystemTime>("claimed").unwrap(), Some(claimed));
+    /// ```
+    pub fn get_timestamp<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: From<SystemTime>,
+    {
+        let millis: Option<i64> = self.get(key)?;
+        Ok(millis.map(|millis| T::from(UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64))))
+    }
+
+    /// Returns how long ago the time stored at a key was, for checking
+    /// cooldowns.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the time, which may refer to a nested value.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` of the time elapsed since the stored
+    /// time, which is zero if the time is in the future, or an error as
+    /// described for [`SQLiteDriver::get_timestamp`].
+    pub fn elapsed_since(&self, key: &str) -> Result<Option<Duration>> {
+        let time: Option<SystemTime> = self.get_timestamp(key)?;
+        Ok(time.map(|time| time.elapsed().unwrap_or_default()))
+    }
+
+    /// Checks a cooldown, such as the one of a command of a user, and starts
+    /// it again if it is over. The check and the write happen atomically, so
+    /// concurrent uses only find the cooldown over once.
+    ///
+    /// The time of the last use is stored at the key like a time stored with
+    /// [`SQLiteDriver::set_timestamp`]. With the `expiry` option, a cooldown
+    /// stored in a whole entry expires along with the cooldown, so finished
+    /// cooldowns are swept.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the cooldown, which may refer to a nested value.
+    /// - `duration`: How long the cooldown lasts after a use.
+    ///
+    /// # Returns
+    /// A `Result` containing `Cooldown::Ready` if the use was recorded, or
+    /// `Cooldown::Remaining` with the time left if the cooldown is running, or
+    /// an error as described for [`SQLiteDriver::get_timestamp`].
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::Cooldown;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// let cooldown = Duration::from_secs(60);
+    ///
+    /// assert_eq!(driver.check_and_set_cooldown("work.alice", cooldown).unwrap(), Cooldown::Ready);
+    /// match driver.check_and_set_cooldown("work.alice", cooldown).unwrap() {
+    ///     Cooldown::Remaining(left) => assert!(left <= cooldown),
+    ///     Cooldown::Ready => panic!("the cooldown is running"),
+    /// }
+    /// ```
+    pub fn check_and_set_cooldown(&self, key: &str, duration: Duration) -> Result<Cooldown> {
+        self.instrument("check_and_set_cooldown", Some(key), || {
+            self.atomic(|| {
+                let now = SystemTime::now();
+                let last: Option<SystemTime> = self.get_timestamp(key)?;
+                let remaining = last
+                    .and_then(|last| last.checked_add(duration))
+                    .and_then(|ready_at| ready_at.duration_since(now).ok())
+                    .filter(|remaining| !remaining.is_zero());
+                if let Some(remaining) = remaining {
+                    return Ok(Cooldown::Remaining(remaining));
+                }
+
+                self.set_timestamp_at(key, now)?;
+                if self.options.expiry.is_some() && self.split_path(key).len() == 1 {
+                    self.set_expiry(key, now + duration)?;
+                }
+                Ok(Cooldown::Ready)
+            })
+        })
+    }
+
+    /// Returns the JSON type of the value stored at a key, including nested keys.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to inspect.
+    ///
+    /// # Returns
+    /// A `Result` containing the `JsonType` of the value, or `None` if the key
+    /// doesn't exist.
+    pub fn type_of(&self, key: &str) -> Result<Option<JsonType>> {
+        Ok(self.get::<Value>(key)?.map(|value| JsonType::of(&value)))
+    }
+
+    /// Removes a specific value from an array stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to remove from the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after removal.
+    pub fn pull<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + std::cmp::PartialEq + Serialize,
+    {
+        self.instrument("pull", Some(key), || {
+            let mut arr: Vec<T> = self.get(key)?.unwrap_or_default();
+
+            arr.retain(|x| x != &value);
+
+            self.set(key, &arr)?;
+
+            Ok(arr)
+        })
+    }
+
+    /// Removes the first occurrence of a value from an array stored at the
+    /// given key, leaving the other occurrences in place.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to remove from the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after removal.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("hand", ["ace", "king", "ace"]).unwrap();
+    ///
+    /// let hand = driver.pull_first("hand", "ace".to_string()).unwrap();
+    /// assert_eq!(hand, ["king", "ace"]);
+    /// ```
+    pub fn pull_first<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + std::cmp::PartialEq + Serialize,
+    {
+        self.instrument("pull_first", Some(key), || {
+            let mut arr: Vec<T> = self.get(key)?.unwrap_or_default();
+
+            if let Some(position) = arr.iter().position(|x| x == &value) {
+                arr.remove(position);
+            }
+
+            self.set(key, &arr)?;
+
+            Ok(arr)
+        })
+    }
+
+    /// Removes every occurrence of several values from an array stored at the
+    /// given key, in a single write.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `values`: The values to remove from the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after removal.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("roles", ["admin", "mod", "member", "mod"]).unwrap();
+    ///
+    /// let roles = driver
+    ///     .pull_many("roles", &["admin".to_string(), "mod".to_string()])
+    ///     .unwrap();
+    /// assert_eq!(roles, ["member"]);
+    /// ```
+    pub fn pull_many<T>(&self, key: &str, values: &[T]) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + std::cmp::PartialEq + Serialize,
+    {
+        self.instrument("pull_many", Some(key), || {
+            let mut arr: Vec<T> = self.get(key)?.unwrap_or_default();
+
+            arr.retain(|x| !values.contains(x));
+
+            self.set(key, &arr)?;
+
+            Ok(arr)
+        })
+    }
+
+    /// Appends a value to an array stored at the given key.
+    ///
+    /// When the array exists, the value is appended by SQLite within the
+    /// stored JSON text, so the document holding it is not parsed and
+    /// serialised again, unless the table buffers writes, expires entries, or
+    /// has quotas or a cache, which check every write.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the array is stored.
+    /// - `value`: The value to append to the array.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array after the value is appended.
+    pub fn push<T>(&self, key: &str, value: T) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        self.instrument("push", Some(key), || {
+            if let Some(arr) = self.push_in_place(key, &value)? {
+                return Ok(arr);
+            }
+
+            let mut arr: Vec<T> = self.get(key)?.unwrap_or_default();
+
+            arr.push(value);
+
+            self.set(key, &arr)?;
+
+            Ok(arr)
+        })
+    }
+
+    /// Appends a value to an existing array within its row with SQLite's JSON
+    /// functions.
+    ///
+    /// # Returns
+    /// A `Result` containing the updated array, or `None` if there is no array
+    /// at the key, or the options of the table need the row to be written by
+    /// `write_row_key`, in which case the caller appends the value itself.
+    fn push_in_place<T>(&self, key: &str, value: &T) -> Result<Option<Vec<T>>>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        if self.options.write_buffer.is_some()
+            || self.options.expiry.is_some()
+            || self.options.cache.is_some()
+            || !self.options.quotas.is_empty()
+        {
+            return Ok(None);
+        }
+
+        let parts = self.split_path(key);
+        if parts[1..].iter().any(|part| part.contains('"')) {
+            return Ok(None);
+        }
+        let path: String = std::iter::once("$".to_string())
+            .chain(parts[1..].iter().map(|part| format!(".\"{}\"", part)))
+            .collect();
+        let json = to_string(value)?;
+
+        // The updated array is read back as `T`, which rolls the write back if
+        // an element of the array is of another type.
+        self.savepoint(|| {
+            let updated: Option<String> = self
+                .database
+                .prepare_cached(&format!(
+                    "UPDATE {} SET JSON = json_insert(JSON, ?2 || '[#]', json(?3)) \
+                     WHERE ID = ?1 AND json_type(JSON, ?2) = 'array' \
+                     RETURNING json_extract(JSON, ?2)",
+                    self.table
+                ))?
+                .query_row(params![parts[0], path, json], |row| row.get(0))
+                .optional()?;
+            updated
+                .map(|array| deserialize_json(&parts[0], &array))
+                .transpose()
+        })
+    }
+
+    /// Adds a value to the unique set stored as an array at the given key, unless
+    /// it is already a member. The check and the write happen atomically.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the set is stored.
+    /// - `value`: The value to add to the set.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if the value was added, or `false` if it was
+    /// already a member.
+    pub fn sadd<T>(&self, key: &str, value: T) -> Result<bool>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        self.instrument("sadd", Some(key), || {
+            self.atomic(|| {
+                let mut members: Vec<Value> = self.get(key)?.unwrap_or_default();
+                if members.contains(&value) {
+                    return Ok(false);
+                }
+                members.push(value);
+                self.set(key, members)?;
+                Ok(true)
+            })
+        })
+    }
+
+    /// Removes a value from the unique set stored as an array at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the set is stored.
+    /// - `value`: The value to remove from the set.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if the value was removed, or `false` if it
+    /// was not a member.
+    pub fn srem<T>(&self, key: &str, value: T) -> Result<bool>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        self.instrument("srem", Some(key), || {
+            self.atomic(|| {
+                let mut members: Vec<Value> = self.get(key)?.unwrap_or_default();
+                let len = members.len();
+                members.retain(|member| member != &value);
+                if members.len() == len {
+                    return Ok(false);
+                }
+                self.set(key, members)?;
+                Ok(true)
+            })
+        })
+    }
+
+    /// Retrieves every member of the unique set stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the set is stored.
+    ///
+    /// # Returns
+    /// A `Result` containing the members, which is empty if the key doesn't exist.
+    pub fn smembers<T>(&self, key: &str) -> Result<Vec<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.instrument("smembers", Some(key), || {
+            Ok(self.get(key)?.unwrap_or_default())
+        })
+    }
+
+    /// Checks if a value is a member of the unique set stored at the given key.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry where the set is stored.
+    /// - `value`: The value to look for.
+    ///
+    /// # Returns
+    /// A `Result` containing a boolean indicating whether the value is a member.
+    pub fn sismember<T>(&self, key: &str, value: T) -> Result<bool>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        self.instrument("sismember", Some(key), || {
+            let members: Vec<Value> = self.get(key)?.unwrap_or_default();
+            Ok(members.contains(&value))
+        })
+    }
+
+    /// Sets or updates the value for a given key in the database.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the value was stored, or a `TypeMismatch`
+    /// error if a nested key leads through a value that is not an object.
+    pub fn set<T>(&self, key: &str, value: T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        self.instrument("set", Some(key), || {
+            let parts = self.split_path(key);
+            let root_key = &parts[0];
+            let path: Vec<&str> = parts[1..].iter().map(String::as_str).collect();
+
+            let mut root_value: Option<Value> = if path.is_empty() {
+                None
+            } else {
+                self.get_row_key(root_key)?
+            };
+            replace(&mut root_value, &path, Some(serde_json::to_value(value)?))?;
+
+            let json_string = to_string(&root_value)
+                .map_err(|e: SerdeJsonError| RusqliteError::ToSqlConversionFailure(Box::new(e)))?;
+            self.write_row_key(root_key, json_string)
+        })
+    }
+
+    /// Sets the value stored at a key, without splitting the key into a nested
+    /// path, as described for [`SQLiteDriver::get_raw`].
+    ///
+    /// # Parameters
+    /// - `key`: The exact key of the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` indicating whether the value was stored.
+    pub fn set_raw<T>(&self, key: &str, value: T) -> Result<()>
+    where
+        T: Serialize,
+    {
+        self.instrument("set_raw", Some(key), || {
+            let json_string = to_string(&value)
+                .map_err(|e: SerdeJsonError| RusqliteError::ToSqlConversionFailure(Box::new(e)))?;
+            self.write_row_key(key, json_string)
+        })
+    }
+
+    /// Sets the value for a given key and returns the value it replaced.
+    ///
+    /// The previous value is read and the new value written in one immediate
+    /// transaction, so no other writer, including other processes, can change
+    /// the key in between, unlike a `get` followed by a `set`.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry.
+    /// - `value`: The value to store, which will be serialised into JSON.
+    ///
+    /// # Returns
+    /// A `Result` containing the previous value, or `None` if the key didn't
+    /// exist, or a `Json` error if the previous value cannot be deserialised
+    /// into the type of `value`.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    ///
+    /// assert_eq!(driver.replace("queue.next", "job-1".to_string()).unwrap(), None);
+    /// assert_eq!(
+    ///     driver.replace("queue.next", "job-2".to_string()).unwrap(),
+    ///     Some("job-1".to_string())
+    /// );
+    /// ```
+    pub fn replace<T>(&self, key: &str, value: T) -> Result<Option<T>>
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        self.instrument("replace", Some(key), || {
+            self.atomic(|| {
+                let parts = self.split_path(key);
+                let path: Vec<&str> = parts[1..].iter().map(String::as_str).collect();
+                let previous = self
+                    .get_row_key::<Value>(&parts[0])?
+                    .and_then(|root| lookup(&root, &path).cloned());
+
+                self.set(key, value)?;
+                Ok(previous.map(serde_json::from_value).transpose()?)
+            })
+        })
+    }
+
+    /// Adds a decimal to the value stored at a key with exact decimal
+    /// arithmetic, storing the result as a decimal string such as `"10.25"`,
+    /// for amounts like currencies where `f64` rounding is unacceptable.
+    ///
+    /// The stored value may be a decimal string or a JSON number, read from
+    /// its text, and a missing value counts as `0`.
+    ///
+    /// # Parameters
+    /// - `key`: The key for the entry to update.
+    /// - `value`: The decimal to add to the current value.
+    ///
+    /// # Returns
+    /// A `Result` containing the new value, or a `TypeMismatch` error if the
+    /// entry holds something else than a decimal, unless the `lenient_numbers`
+    /// option is set, or a `Config` error if the result overflows.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use std::str::FromStr;
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use rust_decimal::Decimal;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.add_decimal("wallet", Decimal::from_str("0.10").unwrap()).unwrap();
+    /// let balance = driver.add_decimal("wallet", Decimal::from_str("0.20").unwrap()).unwrap();
+    ///
+    /// assert_eq!(balance.to_string(), "0.30");
+    /// assert_eq!(driver.get::<String>("wallet").unwrap().unwrap(), "0.30");
+    /// ```
+    #[cfg(feature = "decimal")]
+    pub fn add_decimal(&self, key: &str, value: Decimal) -> Result<Decimal> {
+        self.instrument("add_decimal", Some(key), || {
+            let current = self.current_decimal(key)?;
+            let new_value = current.checked_add(value).ok_or_else(|| {
+                ChromoeError::Config(format!("adding {} to {} overflows", value, current))
+            })?;
+            self.set(key, new_value.to_string())?;
+            Ok(new_value)
+        })
+    }
+
+    /// Subtracts a decimal from the value stored at a key with exact decimal
+    /// arithmetic, as described for [`SQLiteDriver::add_decimal`].
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to subtract from.
+    /// - `value`: The decimal to subtract from the current value.
+    ///
+    /// # Returns
+    /// A `Result` containing the new value, or an error as described for
+    /// [`SQLiteDriver::add_decimal`].
+    #[cfg(feature = "decimal")]
+    pub fn subtract_decimal(&self, key: &str, value: Decimal) -> Result<Decimal> {
+        self.instrument("subtract_decimal", Some(key), || {
+            let current = self.current_decimal(key)?;
+            let new_value = current.checked_sub(value).ok_or_else(|| {
+                ChromoeError::Config(format!("subtracting {} from {} overflows", value, current))
+            })?;
+            self.set(key, new_value.to_string())?;
+            Ok(new_value)
+        })
+    }
+
+    /// Reads the decimal that `add_decimal` and `subtract_decimal` start from,
+    /// which is `0` for a missing entry.
+    #[cfg(feature = "decimal")]
+    fn current_decimal(&self, key: &str) -> Result<Decimal> {
+        let Some(current) = self.get::<Value>(key)? else {
+            return Ok(Decimal::ZERO);
+        };
+        let parsed = match &current {
+            Value::String(text) => Decimal::from_str_exact(text).ok(),
+            Value::Number(number) => Decimal::from_str_exact(&number.to_string())
+                .or_else(|_| Decimal::from_scientific(&number.to_string()))
+                .ok(),
+            _ => None,
+        };
+        match parsed {
+            Some(decimal) => Ok(decimal),
+            None if self.options.lenient_numbers => Ok(Decimal::ZERO),
+            None => Err(ChromoeError::TypeMismatch {
+                expected: std::any::type_name::<Decimal>().to_string(),
+                found: JsonType::of(&current),
+            }),
+        }
+    }
+
+    /// Reads the number that `add` and `subtract` start from, which is `0`
+    /// for a missing entry.
+    fn current_number(&self, key: &str) -> Result<f64> {
+        if self.options.lenient_numbers {
+            let current: Option<Value> = self.get(key)?;
+            return Ok(current.and_then(|value| value.as_f64()).unwrap_or(0.0));
+        }
+        Ok(self.get(key)?.unwrap_or(0.0))
+    }
+
+    /// Subtracts a value from an existing entry. If the entry does not exist,
+    /// it initialises it with the result.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to subtract from.
+    /// - `value`: The value to subtract from the current value.
+    ///
+    /// # Returns
+    /// The new value after subtraction, or an error as described for
+    /// [`SQLiteDriver::add`].
+    pub fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        self.instrument("subtract", Some(key), || {
+            let current_value = self.current_number(key)?;
+
+            if !current_value.is_finite() {
+                return Err(
+                    RusqliteError::ToSqlConversionFailure(Box::new(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        "Non-finite value",
+                    )))
+                    .into(),
+                );
+            }
+
+            let new_value = current_value - value;
+            self.set(key, new_value)?;
+            Ok(new_value)
+        })
+    }
+}
+
+/// A handle to a [`SQLiteDriver`] whose operations use a different timeout,
+/// returned by [`SQLiteDriver::with_timeout`].
+///
+/// The handle dereferences to the driver, and restores the previous timeout
+/// when it is dropped.
+pub struct WithTimeout<'a> {
+    driver: &'a SQLiteDriver,
+    previous: Option<Duration>,
+}
+
+impl Deref for WithTimeout<'_> {
+    type Target = SQLiteDriver;
+
+    fn deref(&self) -> &Self::Target {
+        self.driver
+    }
+}
+
+impl Drop for WithTimeout<'_> {
+    fn drop(&mut self) {
+        self.driver.timeout_override.set(self.previous);
+    }
+}
+
+/// Marks an operation of a [`SQLiteDriver`] as running while it is alive, so
+/// the operations it is built on are not reported again.
+///
+/// The depth is restored when the guard is dropped, including when the
+/// operation panics.
+struct DepthGuard<'a> {
+    depth: &'a Cell<usize>,
+    previous: usize,
+}
+
+impl<'a> DepthGuard<'a> {
+    fn enter(depth: &'a Cell<usize>) -> Self {
+        let previous = depth.get();
+        depth.set(previous + 1);
+        Self { depth, previous }
+    }
+}
+
+impl Drop for DepthGuard<'_> {
+    fn drop(&mut self) {
+        self.depth.set(self.previous);
+    }
+}
+
+/// Returns whether a SQLite error was caused by an operation exceeding its
+/// deadline or giving up waiting for a lock.
+fn is_timeout(err: &RusqliteError) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(ErrorCode::OperationInterrupted | ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+    )
+}
+
+/// Converts an SQL value to JSON: blobs become arrays of bytes.
+fn sql_to_json(value: ValueRef<'_>) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(value) => json!(value),
+        ValueRef::Real(value) => json!(value),
+        ValueRef::Text(value) => json!(String::from_utf8_lossy(value)),
+        ValueRef::Blob(value) => json!(value),
+    }
+}
+
+/// Moves the options stored per table in the `chromoe_table_options` table to
+/// the `chromoe_meta` table.
+fn migrate_table_options(database: &Connection, schema: &str) -> Result<()> {
+    let legacy = database
+        .query_row(
+            &format!(
+                "SELECT 1 FROM {}.sqlite_master WHERE type = 'table' AND name = 'chromoe_table_options'",
+                schema
+            ),
+            [],
+            |_| Ok(()),
+        )
+        .optional()?
+        .is_some();
+    if legacy {
+        database.execute_batch(&format!(
+            "INSERT OR REPLACE INTO {schema}.chromoe_meta (KEY, VALUE) \
+             SELECT 'table_options:' || NAME, OPTIONS FROM {schema}.chromoe_table_options;
+             DROP TABLE {schema}.chromoe_table_options;"
+        ))?;
+    }
+    Ok(())
+}
+
+/// Splits a path of object fields, such as `$.stats.level`, into its fields.
+///
+/// # Returns
+/// A `Result` containing the fields, or an `InvalidPath` error if the path
+/// is not a path of object fields.
+fn field_path(path: &str) -> Result<Vec<&str>> {
+    path.strip_prefix("$.")
+        .map(|rest| rest.split('.').collect::<Vec<&str>>())
+        .filter(|fields| {
+            fields
+                .iter()
+                .all(|field| !field.is_empty() && !field.contains(['[', ']', '"']))
+        })
+        .ok_or_else(|| {
+            ChromoeError::InvalidPath(format!(
+                "`{}` is not a path of object fields, such as `$.stats.level`",
+                path
+            ))
+        })
+}
+
+/// Deletes the expired entries of a table in batches, reporting each
+/// deleted key to the `on_evict` function of the expiry options.
+///
+/// # Returns
+/// A `Result` containing the keys of the deleted entries.
+fn sweep(database: &Connection, table: &str, expiry: &ExpiryOptions) -> Result<Vec<String>> {
+    let batch_size = expiry.batch_size.max(1);
+    let mut evicted = Vec::new();
+    loop {
+        let now = unix_millis(SystemTime::now());
+        let tx = Transaction::new_unchecked(database, TransactionBehavior::Immediate)?;
+        let keys = tx
+            .prepare_cached(&format!(
+                "DELETE FROM {0} WHERE ID IN (SELECT ID FROM {0}_expiry \
+                 WHERE EXPIRES_AT <= ?1 ORDER BY EXPIRES_AT LIMIT ?2) RETURNING ID",
+                table
+            ))?
+            .query_map(params![now, batch_size as i64], |row| {
+                row.get::<_, String>(0)
+            })?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        // Expiries of entries deleted while the triggers were missing.
+        tx.execute(
+            &format!(
+                "DELETE FROM {0}_expiry WHERE EXPIRES_AT <= ?1 \
+                 AND ID NOT IN (SELECT ID FROM {0})",
+                table
+            ),
+            params![now],
+        )?;
+        tx.commit()?;
+
+        for key in &keys {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(key = %key, table = %table, "evicted expired entry");
+            if let Some(on_evict) = &expiry.on_evict {
+                on_evict(key);
+            }
+        }
+
+        let done = keys.len() < batch_size;
+        evicted.extend(keys);
+        if done {
+            return Ok(evicted);
+        }
+    }
+}
+
+/// Deserialises the stored JSON text of a row, or a value within it, into
+/// `T`, reporting text that is not valid JSON as a `Corrupted` error and a
+/// value of another type as a `TypeMismatch` error.
+fn deserialize_json<'a, T>(key: &str, json: &'a str) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    from_str(json).map_err(|source| {
+        // The value is only parsed again to find its type if it has another.
+        match (source.classify(), from_str::<Value>(json)) {
+            (Category::Data, Ok(value)) => ChromoeError::TypeMismatch {
+                expected: std::any::type_name::<T>().to_string(),
+                found: JsonType::of(&value),
+            },
+            _ => ChromoeError::Corrupted {
+                key: key.to_string(),
+                source,
+            },
+        }
+    })
+}
+
+/// Converts a JSON value to SQL: arrays and objects become JSON text.
+fn json_to_sql(value: Value) -> SqlValue {
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(value) => SqlValue::Integer(value as i64),
+        Value::Number(number) => match number.as_i64() {
+            Some(value) => SqlValue::Integer(value),
+            None => SqlValue::Real(number.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(value) => SqlValue::Text(value),
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+/// Deletes the entries of a table older than the maximum age of a retention
+/// policy, in batches, and returns their keys.
+fn enforce_retention(
+    database: &Connection,
+    table: &str,
+    retention: &RetentionOptions,
+) -> Result<Vec<String>> {
+    // Timestamps stored in the values are Unix milliseconds or dates.
+    let timestamp = match &retention.timestamp {
+        RetentionTimestamp::UpdatedAt => format!(
+            "(SELECT MAX(TIMESTAMP) FROM {0}_changes AS c WHERE c.ID = {0}.ID)",
+            table
+        ),
+        RetentionTimestamp::Path(_) => "(CASE json_type(JSON, ?3) \
+             WHEN 'text' THEN CAST(unixepoch(JSON ->> ?3, 'subsec') * 1000 AS INTEGER) \
+             WHEN 'integer' THEN JSON ->> ?3 WHEN 'real' THEN JSON ->> ?3 END)"
+            .to_string(),
+    };
+    let max_age = retention.max_age.as_millis().min(i64::MAX as u128) as i64;
+    let batch_size = retention.batch_size.max(1);
+    let mut args = vec![SqlValue::Null, SqlValue::Integer(batch_size as i64)];
+    if let RetentionTimestamp::Path(path) = &retention.timestamp {
+        args.push(SqlValue::Text(path.clone()));
+    }
+
+    let mut deleted = Vec::new();
+    loop {
+        args[0] = SqlValue::Integer(unix_millis(SystemTime::now()).saturating_sub(max_age));
+        let tx = Transaction::new_unchecked(database, TransactionBehavior::Immediate)?;
+        let keys = tx
+            .prepare_cached(&format!(
+                "DELETE FROM {0} WHERE ID IN (SELECT ID FROM {0} WHERE {1} < ?1 LIMIT ?2) \
+                 RETURNING ID",
+                table, timestamp
+            ))?
+            .query_map(params_from_iter(&args), |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        tx.commit()?;
+
+        #[cfg(feature = "tracing")]
+        for key in &keys {
+            tracing::debug!(key = %key, table = %table, "deleted entry past retention");
+        }
+
+        let done = keys.len() < batch_size;
+        deleted.extend(keys);
+        if done {
+            return Ok(deleted);
+        }
+    }
+}
+
+/// Computes the checksum stored with a value, the 64-bit FNV-1a hash of its
+/// JSON text.
+fn checksum(json: &str) -> i64 {
+    let hash = json.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    });
+    hash as i64
+}
+
+/// Returns the number of milliseconds between the Unix epoch and `time`.
+fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |elapsed| {
+        elapsed.as_millis().min(i64::MAX as u128) as i64
+    })
+}
+
+/// The `ID`, `KEY`, `RUN_AT` and `PAYLOAD` columns of a scheduled job.
+type JobRow = (i64, String, i64, String);
+
+/// Reads the columns of a job from a row of its ID, key, due time and payload.
+fn job_row(row: &Row<'_>) -> rusqlite::Result<JobRow> {
+    Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+}
+
+/// Parses a row read by `job_row` into a `Job`.
+///
+/// # Returns
+/// A `Result` containing the job, or a `Corrupted` error naming the key of the
+/// job if its payload is not valid JSON.
+fn parse_job((id, key, run_at, payload): JobRow) -> Result<Job> {
+    let payload = from_str(&payload).map_err(|source| ChromoeError::Corrupted {
+        key: key.clone(),
+        source,
+    })?;
+    Ok(Job {
+        id: id as u64,
+        key,
+        run_at: UNIX_EPOCH + Duration::from_millis(run_at.max(0) as u64),
+        payload,
+    })
+}
+
+/// Generates a token identifying the owner of a lock, unique across processes.
+fn lock_token() -> String {
+    static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!(
+        "{}-{:x}-{}",
+        std::process::id(),
+        nanos,
+        SEQUENCE.fetch_add(1, AtomicOrdering::Relaxed)
+    )
+}
+
+impl Driver for SQLiteDriver {
+    fn ping(&self) -> Result<()> {
+        SQLiteDriver::ping(self)
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        SQLiteDriver::all(self)
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        SQLiteDriver::get(self, key)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        SQLiteDriver::set(self, key, value)
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        SQLiteDriver::has(self, key)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        SQLiteDriver::delete(self, key)
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        SQLiteDriver::delete_all(self)
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        SQLiteDriver::add(self, key, value)
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        SQLiteDriver::subtract(self, key, value)
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        SQLiteDriver::push(self, key, value)
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        SQLiteDriver::pull(self, key, value)
+    }
+
+    fn insert(&self, value: Value) -> Result<String> {
+        SQLiteDriver::insert(self, value)
+    }
+
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        SQLiteDriver::replace(self, key, value)
+    }
+
+    fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        SQLiteDriver::delete_many_nested(self, key, paths)
+    }
+
+    fn entry(&self, key: &str) -> Result<Option<DataSet>> {
+        SQLiteDriver::entry(self, key)
+    }
+
+    fn entries(&self) -> Result<Vec<DataSet>> {
+        SQLiteDriver::entries(self)
+    }
+
+    fn split_key(&self, key: &str) -> Vec<String> {
+        self.split_path(key)
+    }
+
+    fn join_key(&self, path: &[String]) -> Option<String> {
+        Some(join_escaped(path, self.options.path_separator))
+    }
+}
 
-        let new_value = current_value - value;
-        self.set(key, new_value)?;
-        Ok(new_value)
+impl ChangeLog for SQLiteDriver {
+    fn changes_since(&self, seq: u64) -> Result<Vec<Change>> {
+        SQLiteDriver::changes_since(self, seq)
     }
 }
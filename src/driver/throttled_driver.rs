@@ -0,0 +1,308 @@
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::driver::Driver;
+use crate::error::{ChromoeError, Result};
+use crate::structure::DataSet;
+
+/// What a [`ThrottledDriver`] does with an operation exceeding its limits.
+///
+/// # Variants
+///
+/// - `Wait`: Blocks the calling thread until the operation is within the limits.
+/// - `Reject`: Fails the operation with a `RateLimited` error.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ThrottleMode {
+    /// Wait until the operation is within the limits.
+    #[default]
+    Wait,
+    /// Reject the operation.
+    Reject,
+}
+
+/// The operations a throttled driver may currently run.
+#[derive(Debug)]
+struct ThrottleState {
+    /// The operations that may start before exceeding the rate, refilled
+    /// continuously up to one second's worth.
+    tokens: f64,
+    /// When `tokens` was last refilled.
+    refilled_at: Instant,
+    /// The number of operations currently running.
+    in_flight: usize,
+}
+
+/// A driver wrapper bounding the number of operations per second and the
+/// number of operations running at once, protecting shared backends from a
+/// misbehaving command loop.
+///
+/// The rate is enforced with a token bucket holding one second's worth of
+/// operations, so short bursts up to the rate are allowed. Operations
+/// exceeding a limit wait or are rejected according to the [`ThrottleMode`].
+/// Limits apply to the wrapper, so a driver shared between threads must be
+/// shared through one `ThrottledDriver`.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::driver::throttled_driver::{ThrottleMode, ThrottledDriver};
+/// use chromoe_db::driver::Driver;
+/// use chromoe_db::error::ChromoeError;
+/// use serde_json::json;
+///
+/// let driver = ThrottledDriver::new(SQLiteDriver::from_uri("sqlite://:memory:").unwrap())
+///     .with_rate(2)
+///     .with_mode(ThrottleMode::Reject);
+///
+/// driver.set("a", json!(1)).unwrap();
+/// driver.set("b", json!(2)).unwrap();
+/// assert!(matches!(driver.set("c", json!(3)), Err(ChromoeError::RateLimited(_))));
+/// ```
+#[derive(Debug)]
+pub struct ThrottledDriver<D> {
+    driver: D,
+    rate: Option<u32>,
+    max_in_flight: Option<usize>,
+    mode: ThrottleMode,
+    state: Mutex<ThrottleState>,
+    released: Condvar,
+}
+
+/// An operation admitted by a throttled driver, counted as in flight until
+/// it is dropped.
+struct Permit<'a> {
+    state: &'a Mutex<ThrottleState>,
+    released: &'a Condvar,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        lock(self.state).in_flight -= 1;
+        self.released.notify_one();
+    }
+}
+
+impl<D> ThrottledDriver<D>
+where
+    D: Driver,
+{
+    /// Wraps a driver, without limits until they are set.
+    ///
+    /// # Parameters
+    /// - `driver`: The driver to throttle.
+    ///
+    /// # Returns
+    /// The `ThrottledDriver` wrapping `driver`.
+    pub fn new(driver: D) -> Self {
+        ThrottledDriver {
+            driver,
+            rate: None,
+            max_in_flight: None,
+            mode: ThrottleMode::default(),
+            state: Mutex::new(ThrottleState {
+                tokens: 0.0,
+                refilled_at: Instant::now(),
+                in_flight: 0,
+            }),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Limits the number of operations started per second.
+    ///
+    /// # Parameters
+    /// - `per_second`: The number of operations per second, at least `1`.
+    ///
+    /// # Returns
+    /// The `ThrottledDriver` with the rate set.
+    pub fn with_rate(mut self, per_second: u32) -> Self {
+        let per_second = per_second.max(1);
+        self.rate = Some(per_second);
+        lock(&self.state).tokens = per_second as f64;
+        self
+    }
+
+    /// Limits the number of operations running at once.
+    ///
+    /// # Parameters
+    /// - `max`: The number of operations running at once, at least `1`.
+    ///
+    /// # Returns
+    /// The `ThrottledDriver` with the limit set.
+    pub fn with_max_in_flight(mut self, max: usize) -> Self {
+        self.max_in_flight = Some(max.max(1));
+        self
+    }
+
+    /// Sets what happens to operations exceeding a limit.
+    ///
+    /// # Parameters
+    /// - `mode`: Whether operations wait or are rejected.
+    ///
+    /// # Returns
+    /// The `ThrottledDriver` with the mode set.
+    pub fn with_mode(mut self, mode: ThrottleMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Returns the wrapped driver, which is not throttled.
+    pub fn inner(&self) -> &D {
+        &self.driver
+    }
+
+    /// Consumes the wrapper, returning the wrapped driver.
+    pub fn into_inner(self) -> D {
+        self.driver
+    }
+
+    /// Waits until an operation is within the limits, or rejects it.
+    ///
+    /// # Returns
+    /// A `Result` containing the permit of the operation, or a `RateLimited`
+    /// error if it is rejected.
+    fn acquire(&self) -> Result<Permit<'_>> {
+        let mut state = lock(&self.state);
+        loop {
+            if let Some(rate) = self.rate {
+                let now = Instant::now();
+                let elapsed = now.duration_since(state.refilled_at).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * rate as f64).min(rate as f64);
+                state.refilled_at = now;
+            }
+
+            let busy = self.max_in_flight.is_some_and(|max| state.in_flight >= max);
+            let limited = self.rate.is_some() && state.tokens < 1.0;
+            if !busy && !limited {
+                if self.rate.is_some() {
+                    state.tokens -= 1.0;
+                }
+                state.in_flight += 1;
+                return Ok(Permit {
+                    state: &self.state,
+                    released: &self.released,
+                });
+            }
+
+            if self.mode == ThrottleMode::Reject {
+                return Err(ChromoeError::RateLimited(if busy {
+                    format!("{} operations are already running", state.in_flight)
+                } else {
+                    format!("more than {} operations per second", self.rate.unwrap_or(0))
+                }));
+            }
+
+            state = if busy {
+                self.released.wait(state).unwrap_or_else(|e| e.into_inner())
+            } else {
+                let rate = self.rate.unwrap_or(1) as f64;
+                let wait = Duration::from_secs_f64((1.0 - state.tokens) / rate);
+                self.released
+                    .wait_timeout(state, wait)
+                    .unwrap_or_else(|e| e.into_inner())
+                    .0
+            };
+        }
+    }
+}
+
+impl<D> Driver for ThrottledDriver<D>
+where
+    D: Driver,
+{
+    fn ping(&self) -> Result<()> {
+        let _permit = self.acquire()?;
+        self.driver.ping()
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        let _permit = self.acquire()?;
+        self.driver.all()
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        let _permit = self.acquire()?;
+        self.driver.get(key)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        let _permit = self.acquire()?;
+        self.driver.set(key, value)
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        let _permit = self.acquire()?;
+        self.driver.has(key)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        let _permit = self.acquire()?;
+        self.driver.delete(key)
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        let _permit = self.acquire()?;
+        self.driver.delete_all()
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        let _permit = self.acquire()?;
+        self.driver.add(key, value)
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        let _permit = self.acquire()?;
+        self.driver.subtract(key, value)
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        let _permit = self.acquire()?;
+        self.driver.push(key, value)
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        let _permit = self.acquire()?;
+        self.driver.pull(key, value)
+    }
+
+    fn insert(&self, value: Value) -> Result<String> {
+        let _permit = self.acquire()?;
+        self.driver.insert(value)
+    }
+
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        let _permit = self.acquire()?;
+        self.driver.replace(key, value)
+    }
+
+    fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        let _permit = self.acquire()?;
+        self.driver.delete_many_nested(key, paths)
+    }
+
+    fn entry(&self, key: &str) -> Result<Option<DataSet>> {
+        let _permit = self.acquire()?;
+        self.driver.entry(key)
+    }
+
+    fn entries(&self) -> Result<Vec<DataSet>> {
+        let _permit = self.acquire()?;
+        self.driver.entries()
+    }
+
+    fn split_key(&self, key: &str) -> Vec<String> {
+        self.driver.split_key(key)
+    }
+
+    fn join_key(&self, path: &[String]) -> Option<String> {
+        self.driver.join_key(path)
+    }
+}
+
+/// Locks the throttle state, recovering it if another thread panicked.
+fn lock(state: &Mutex<ThrottleState>) -> MutexGuard<'_, ThrottleState> {
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
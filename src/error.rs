@@ -0,0 +1,190 @@
+use std::fmt;
+
+use crate::structure::JsonType;
+
+/// Errors returned by the chromoe-db drivers.
+///
+/// Every driver operation returns a [`ChromoeError`] on failure, wrapping the
+/// underlying database or serialisation error where there is one, so callers
+/// only ever need to handle a single error type regardless of the backend.
+#[derive(Debug)]
+pub enum ChromoeError {
+    /// An error raised by the underlying SQLite connection.
+    #[cfg(feature = "sqlite")]
+    Sqlite(rusqlite::Error),
+    /// A value could not be serialised into or deserialised from JSON.
+    Json(serde_json::Error),
+    /// A connection string could not be parsed into driver options.
+    InvalidUri(String),
+    /// Driver configuration loaded from the environment or a file is invalid.
+    Config(String),
+    /// A JSON path expression could not be parsed.
+    InvalidPath(String),
+    /// A stored value does not have the type it was read as.
+    TypeMismatch {
+        /// The type the value was read as.
+        expected: String,
+        /// The JSON type of the stored value.
+        found: JsonType,
+    },
+    /// The value stored at a key is not valid JSON.
+    Corrupted {
+        /// The key of the corrupted value.
+        key: String,
+        /// The error raised when parsing the value.
+        source: serde_json::Error,
+    },
+    /// The value stored at a key doesn't match the checksum stored with it,
+    /// which means it was damaged after it was written.
+    ChecksumMismatch {
+        /// The key of the damaged value.
+        key: String,
+    },
+    /// An I/O error, such as failing to read a configuration file.
+    Io(std::io::Error),
+    /// A request to a remote server failed or was rejected.
+    Http(String),
+    /// An operation did not complete within its configured timeout.
+    Timeout(String),
+    /// An operation was rejected because a configured rate or concurrency
+    /// limit was reached.
+    RateLimited(String),
+    /// A write was rejected because it would exceed a configured quota.
+    QuotaExceeded(String),
+    /// An operation was rejected because the permissions of a scoped driver
+    /// don't allow it.
+    PermissionDenied(String),
+    /// An error raised by a Cloudflare D1 database binding.
+    #[cfg(feature = "d1")]
+    D1(worker::Error),
+    /// An error raised by the underlying RocksDB database.
+    #[cfg(feature = "rocksdb")]
+    RocksDb(rocksdb::Error),
+    /// An error raised by the ScyllaDB or Cassandra driver.
+    #[cfg(feature = "scylla")]
+    Scylla(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl fmt::Display for ChromoeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "sqlite")]
+            ChromoeError::Sqlite(err) => write!(f, "sqlite error: {}", err),
+            ChromoeError::Json(err) => write!(f, "json error: {}", err),
+            ChromoeError::InvalidUri(reason) => write!(f, "invalid connection uri: {}", reason),
+            ChromoeError::Config(reason) => write!(f, "invalid configuration: {}", reason),
+            ChromoeError::InvalidPath(reason) => write!(f, "invalid path: {}", reason),
+            ChromoeError::TypeMismatch { expected, found } => {
+                write!(f, "type mismatch: expected {}, found {}", expected, found)
+            }
+            ChromoeError::Corrupted { key, source } => {
+                write!(f, "corrupted value at `{}`: {}", key, source)
+            }
+            ChromoeError::ChecksumMismatch { key } => {
+                write!(f, "checksum mismatch at `{}`", key)
+            }
+            ChromoeError::Io(err) => write!(f, "io error: {}", err),
+            ChromoeError::Http(reason) => write!(f, "http error: {}", reason),
+            ChromoeError::Timeout(reason) => write!(f, "timed out: {}", reason),
+            ChromoeError::RateLimited(reason) => write!(f, "rate limited: {}", reason),
+            ChromoeError::QuotaExceeded(reason) => write!(f, "quota exceeded: {}", reason),
+            ChromoeError::PermissionDenied(reason) => write!(f, "permission denied: {}", reason),
+            #[cfg(feature = "d1")]
+            ChromoeError::D1(err) => write!(f, "d1 error: {}", err),
+            #[cfg(feature = "rocksdb")]
+            ChromoeError::RocksDb(err) => write!(f, "rocksdb error: {}", err),
+            #[cfg(feature = "scylla")]
+            ChromoeError::Scylla(err) => write!(f, "scylla error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ChromoeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "sqlite")]
+            ChromoeError::Sqlite(err) => Some(err),
+            ChromoeError::Json(err) => Some(err),
+            ChromoeError::Corrupted { source, .. } => Some(source),
+            ChromoeError::Io(err) => Some(err),
+            #[cfg(feature = "d1")]
+            ChromoeError::D1(err) => Some(err),
+            #[cfg(feature = "rocksdb")]
+            ChromoeError::RocksDb(err) => Some(err),
+            #[cfg(feature = "scylla")]
+            ChromoeError::Scylla(err) => Some(err.as_ref()),
+            ChromoeError::InvalidUri(_)
+            | ChromoeError::Config(_)
+            | ChromoeError::InvalidPath(_)
+            | ChromoeError::TypeMismatch { .. }
+            | ChromoeError::ChecksumMismatch { .. }
+            | ChromoeError::Http(_)
+            | ChromoeError::Timeout(_)
+            | ChromoeError::RateLimited(_)
+            | ChromoeError::QuotaExceeded(_)
+            | ChromoeError::PermissionDenied(_) => None,
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+impl From<rusqlite::Error> for ChromoeError {
+    fn from(err: rusqlite::Error) -> Self {
+        ChromoeError::Sqlite(err)
+    }
+}
+
+impl From<serde_json::Error> for ChromoeError {
+    fn from(err: serde_json::Error) -> Self {
+        ChromoeError::Json(err)
+    }
+}
+
+impl From<std::io::Error> for ChromoeError {
+    fn from(err: std::io::Error) -> Self {
+        ChromoeError::Io(err)
+    }
+}
+
+#[cfg(feature = "d1")]
+impl From<worker::Error> for ChromoeError {
+    fn from(err: worker::Error) -> Self {
+        ChromoeError::D1(err)
+    }
+}
+
+#[cfg(feature = "rocksdb")]
+impl From<rocksdb::Error> for ChromoeError {
+    fn from(err: rocksdb::Error) -> Self {
+        ChromoeError::RocksDb(err)
+    }
+}
+
+/// Converts a request that failed before receiving a response into a
+/// `ChromoeError`, reporting requests that exceeded the agent's timeout as
+/// [`ChromoeError::Timeout`].
+#[cfg(any(
+    feature = "http",
+    feature = "libsql",
+    feature = "couchdb",
+    feature = "etcd"
+))]
+pub(crate) fn transport_error(err: ureq::Transport) -> ChromoeError {
+    let timed_out = std::error::Error::source(&err)
+        .and_then(|source| source.downcast_ref::<std::io::Error>())
+        .is_some_and(|source| {
+            matches!(
+                source.kind(),
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock
+            )
+        });
+
+    if timed_out {
+        ChromoeError::Timeout(err.to_string())
+    } else {
+        ChromoeError::Http(err.to_string())
+    }
+}
+
+/// A specialised `Result` type for chromoe-db operations.
+pub type Result<T> = std::result::Result<T, ChromoeError>;
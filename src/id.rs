@@ -0,0 +1,176 @@
+use std::fmt;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::error::{ChromoeError, Result};
+
+/// The number of bits of an identifier holding the worker id.
+const WORKER_BITS: u32 = 10;
+
+/// The number of bits of an identifier holding the sequence number.
+const SEQUENCE_BITS: u32 = 12;
+
+/// The highest sequence number issued within one millisecond.
+const MAX_SEQUENCE: u64 = (1 << SEQUENCE_BITS) - 1;
+
+/// The state of a generator: the millisecond of the last identifier and its
+/// sequence number.
+#[derive(Debug, Default)]
+struct State {
+    millis: u64,
+    sequence: u64,
+}
+
+/// A generator of Snowflake identifiers: 64-bit integers that are unique across
+/// workers and increase with the time they were generated at.
+///
+/// An identifier holds, from the most significant bit, the milliseconds since
+/// the epoch of the generator in 41 bits, the worker id in 10 bits, and a
+/// sequence number in 12 bits. Up to 4096 identifiers are generated per
+/// millisecond and worker. Identifiers keep increasing if the system clock goes
+/// backwards, and when more identifiers are generated within one millisecond,
+/// by running ahead of the clock until it catches up.
+///
+/// Sorting identifiers sorts records by creation time, which allows records to
+/// be paginated in creation order without storing timestamps. Using a
+/// `Snowflake` as the [`KeyGenerator`](crate::structure::KeyGenerator) of a
+/// driver makes `insert` generate such keys.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use chromoe_db::id::Snowflake;
+///
+/// let snowflake = Snowflake::new(3)
+///     .unwrap()
+///     .with_epoch(UNIX_EPOCH + Duration::from_millis(1_420_070_400_000));
+///
+/// let first = snowflake.generate();
+/// let second = snowflake.generate();
+///
+/// assert!(second > first);
+/// assert_eq!(Snowflake::worker_id_of(first), 3);
+/// ```
+pub struct Snowflake {
+    epoch: SystemTime,
+    worker_id: u16,
+    state: Mutex<State>,
+}
+
+impl Snowflake {
+    /// The epoch used unless another is set with [`Snowflake::with_epoch`],
+    /// 2024-01-01 00:00:00 UTC.
+    pub const DEFAULT_EPOCH_MILLIS: u64 = 1_704_067_200_000;
+
+    /// The highest worker id, since worker ids are stored in 10 bits.
+    pub const MAX_WORKER_ID: u16 = (1 << WORKER_BITS) - 1;
+
+    /// Creates a generator for the given worker.
+    ///
+    /// Every process generating identifiers for the same data must use a
+    /// different worker id, or identifiers may collide.
+    ///
+    /// # Parameters
+    /// - `worker_id`: The id of the worker, from `0` to [`Snowflake::MAX_WORKER_ID`].
+    ///
+    /// # Returns
+    /// A `Result` containing the `Snowflake` generator, or a `Config` error if
+    /// the worker id is out of range.
+    pub fn new(worker_id: u16) -> Result<Self> {
+        if worker_id > Self::MAX_WORKER_ID {
+            return Err(ChromoeError::Config(format!(
+                "worker id {} exceeds the maximum of {}",
+                worker_id,
+                Self::MAX_WORKER_ID
+            )));
+        }
+
+        Ok(Snowflake {
+            epoch: UNIX_EPOCH + Duration::from_millis(Self::DEFAULT_EPOCH_MILLIS),
+            worker_id,
+            state: Mutex::new(State::default()),
+        })
+    }
+
+    /// Sets the epoch identifiers count milliseconds from.
+    ///
+    /// Identifiers have room for about 69 years after the epoch, so it should be
+    /// close to the time the application started generating identifiers. The
+    /// epoch must stay the same for identifiers to remain unique and ordered.
+    ///
+    /// # Parameters
+    /// - `epoch`: The epoch of the generator.
+    ///
+    /// # Returns
+    /// The `Snowflake` generator counting from `epoch`.
+    pub fn with_epoch(mut self, epoch: SystemTime) -> Self {
+        self.epoch = epoch;
+        self
+    }
+
+    /// Returns the epoch of the generator.
+    pub fn epoch(&self) -> SystemTime {
+        self.epoch
+    }
+
+    /// Returns the worker id of the generator.
+    pub fn worker_id(&self) -> u16 {
+        self.worker_id
+    }
+
+    /// Generates a new identifier, greater than every identifier previously
+    /// generated by this generator.
+    pub fn generate(&self) -> u64 {
+        let now = SystemTime::now()
+            .duration_since(self.epoch)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if now > state.millis {
+            state.millis = now;
+            state.sequence = 0;
+        } else if state.sequence < MAX_SEQUENCE {
+            state.sequence += 1;
+        } else {
+            state.millis += 1;
+            state.sequence = 0;
+        }
+
+        (state.millis << (WORKER_BITS + SEQUENCE_BITS))
+            | ((self.worker_id as u64) << SEQUENCE_BITS)
+            | state.sequence
+    }
+
+    /// Returns the time an identifier generated by this generator was generated at.
+    ///
+    /// # Parameters
+    /// - `id`: The identifier.
+    ///
+    /// # Returns
+    /// The time encoded in the identifier, to the millisecond.
+    pub fn timestamp_of(&self, id: u64) -> SystemTime {
+        self.epoch + Duration::from_millis(id >> (WORKER_BITS + SEQUENCE_BITS))
+    }
+
+    /// Returns the worker id encoded in an identifier.
+    ///
+    /// # Parameters
+    /// - `id`: The identifier.
+    ///
+    /// # Returns
+    /// The id of the worker that generated the identifier.
+    pub fn worker_id_of(id: u64) -> u16 {
+        ((id >> SEQUENCE_BITS) & Self::MAX_WORKER_ID as u64) as u16
+    }
+}
+
+impl fmt::Debug for Snowflake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Snowflake")
+            .field("epoch", &self.epoch)
+            .field("worker_id", &self.worker_id)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,2 +1,43 @@
 pub mod structure;
+#[cfg(feature = "sqlite")]
+pub mod batch;
+#[cfg(feature = "sqlite")]
+pub mod counter;
+pub mod database;
+pub mod diff;
+#[cfg(feature = "serenity")]
+pub mod discord;
 pub mod driver;
+pub mod error;
+pub mod id;
+#[cfg(feature = "sqlite")]
+pub mod manager;
+pub mod metrics;
+#[cfg(feature = "sqlite")]
+pub mod migrations;
+#[cfg(feature = "sqlite")]
+pub mod pubsub;
+#[cfg(feature = "sqlite")]
+pub mod queue;
+pub mod replication;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "sessions")]
+pub mod sessions;
+#[cfg(feature = "sqlite")]
+pub mod snapshot;
+#[cfg(feature = "sqlite")]
+pub mod sorted;
+#[cfg(feature = "sqlite")]
+pub mod transaction;
+pub mod uri;
+#[cfg(feature = "web")]
+pub mod web;
+
+pub use database::Database;
+pub use diff::diff;
+#[cfg(feature = "sqlite")]
+pub use manager::ChromoeManager;
+pub use replication::{import, replicate};
+#[cfg(feature = "sqlite")]
+pub use rusqlite;
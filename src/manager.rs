@@ -0,0 +1,343 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde_json::Value;
+
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::driver::Driver;
+use crate::error::{ChromoeError, Result};
+use crate::structure::{ChromoeManagerOptions, DataSet, SQLiteDriverOptions};
+
+/// Manager owning many named SQLite databases, such as one file per guild.
+///
+/// Databases are opened lazily the first time they are asked for with
+/// [`ChromoeManager::db`], from `<directory>/<name>.sqlite`. At most
+/// `max_open` databases are kept open: opening another one closes the least
+/// recently used database that no [`ManagedDriver`] refers to.
+///
+/// The manager can be shared between threads, and the drivers it hands out
+/// lock their database for every operation.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::Driver;
+/// use chromoe_db::structure::ChromoeManagerOptions;
+/// use chromoe_db::ChromoeManager;
+/// use serde_json::json;
+///
+/// let directory = std::env::temp_dir().join("chromoe-manager-doc");
+/// let manager = ChromoeManager::new(ChromoeManagerOptions {
+///     directory: directory.to_string_lossy().into_owned(),
+///     max_open: 2,
+///     ..Default::default()
+/// })
+/// .unwrap();
+///
+/// manager.db("guild_123").unwrap().set("prefix", json!("!")).unwrap();
+/// manager.db("guild_456").unwrap().set("prefix", json!("?")).unwrap();
+/// manager.db("guild_789").unwrap().set("prefix", json!("$")).unwrap();
+///
+/// // `guild_123` was closed to stay within two open databases, and is
+/// // opened again from its file.
+/// assert_eq!(manager.open_count(), 2);
+/// assert_eq!(manager.db("guild_123").unwrap().get("prefix").unwrap(), Some(json!("!")));
+/// # std::fs::remove_dir_all(directory).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct ChromoeManager {
+    options: ChromoeManagerOptions,
+    /// The open databases, from the least to the most recently used.
+    open: Mutex<Vec<ManagedDriver>>,
+}
+
+impl ChromoeManager {
+    /// Creates a new instance of the `ChromoeManager`, creating its directory
+    /// if it doesn't exist.
+    ///
+    /// # Parameters
+    /// - `options`: Configuration options for the manager.
+    ///
+    /// # Returns
+    /// A `Result` containing either the `ChromoeManager` instance or an error
+    /// if `max_open` is zero or the directory cannot be created.
+    pub fn new(options: ChromoeManagerOptions) -> Result<Self> {
+        if options.max_open == 0 {
+            return Err(ChromoeError::Config(
+                "the manager must keep at least one database open".to_string(),
+            ));
+        }
+
+        std::fs::create_dir_all(&options.directory)?;
+        Ok(ChromoeManager {
+            options,
+            open: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Returns the database with the given name, opening it if it isn't open.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the database, made of ASCII letters, digits, `_`
+    ///   and `-`, which names its file.
+    ///
+    /// # Returns
+    /// A `Result` containing the driver of the database, or a `Config` error
+    /// if the name is invalid, or a `RateLimited` error if `max_open`
+    /// databases are open and all of them are in use.
+    pub fn db(&self, name: &str) -> Result<ManagedDriver> {
+        validate_name(name)?;
+
+        let mut open = self.lock_open();
+        if let Some(position) = open.iter().position(|db| db.name == name) {
+            let db = open.remove(position);
+            open.push(db.clone());
+            return Ok(db);
+        }
+
+        if open.len() >= self.options.max_open {
+            let idle = open.iter().position(|db| !db.in_use()).ok_or_else(|| {
+                ChromoeError::RateLimited(format!("all {} open databases are in use", open.len()))
+            })?;
+            open.remove(idle).close()?;
+        }
+
+        let file_name = Path::new(&self.options.directory)
+            .join(format!("{}.sqlite", name))
+            .to_string_lossy()
+            .into_owned();
+        let driver = SQLiteDriver::new(Some(SQLiteDriverOptions {
+            file_name,
+            ..self.options.driver.clone()
+        }))?;
+
+        let db = ManagedDriver {
+            name: name.to_string(),
+            driver: Arc::new(Mutex::new(driver)),
+        };
+        open.push(db.clone());
+        Ok(db)
+    }
+
+    /// Returns the names of the open databases, from the least to the most
+    /// recently used.
+    pub fn open_names(&self) -> Vec<String> {
+        self.lock_open().iter().map(|db| db.name.clone()).collect()
+    }
+
+    /// Returns the number of open databases.
+    pub fn open_count(&self) -> usize {
+        self.lock_open().len()
+    }
+
+    /// Closes a database, if it is open. A database still in use is flushed
+    /// instead, and stays open and counted towards `max_open` until it is no
+    /// longer used and closed again or evicted.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the database.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if the database was closed, or `false` if
+    /// it wasn't open or is still in use, or the error of closing it.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::structure::ChromoeManagerOptions;
+    /// use chromoe_db::ChromoeManager;
+    ///
+    /// let directory = std::env::temp_dir().join("chromoe-manager-close-doc");
+    /// let manager = ChromoeManager::new(ChromoeManagerOptions {
+    ///     directory: directory.to_string_lossy().into_owned(),
+    ///     ..Default::default()
+    /// })
+    /// .unwrap();
+    ///
+    /// let db = manager.db("guild_123").unwrap();
+    /// assert!(!manager.close("guild_123").unwrap());
+    /// assert_eq!(manager.open_count(), 1);
+    ///
+    /// drop(db);
+    /// assert!(manager.close("guild_123").unwrap());
+    /// assert_eq!(manager.open_count(), 0);
+    /// # std::fs::remove_dir_all(directory).unwrap();
+    /// ```
+    pub fn close(&self, name: &str) -> Result<bool> {
+        let mut open = self.lock_open();
+        let Some(position) = open.iter().position(|db| db.name == name) else {
+            return Ok(false);
+        };
+        if open[position].in_use() {
+            open[position].lock().flush()?;
+            return Ok(false);
+        }
+        open.remove(position).close()?;
+        Ok(true)
+    }
+
+    /// Closes every open database, as [`ChromoeManager::close`] does, flushing
+    /// the databases still in use and keeping them open.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of databases closed, or the first
+    /// error encountered, leaving the databases not closed yet open.
+    pub fn close_all(&self) -> Result<usize> {
+        let mut open = self.lock_open();
+        let mut closed = 0;
+        let mut position = 0;
+        while position < open.len() {
+            if open[position].in_use() {
+                open[position].lock().flush()?;
+                position += 1;
+            } else {
+                open.remove(position).close()?;
+                closed += 1;
+            }
+        }
+        Ok(closed)
+    }
+
+    /// Locks the list of open databases, ignoring poisoning as the list is
+    /// never left half-updated.
+    fn lock_open(&self) -> MutexGuard<'_, Vec<ManagedDriver>> {
+        self.open.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Checks that a database name can be used as a file name.
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if !valid {
+        return Err(ChromoeError::Config(format!(
+            "invalid database name `{}`: names are made of ASCII letters, digits, `_` and `-`",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// A database opened by a [`ChromoeManager`].
+///
+/// Every operation of the [`Driver`] trait locks the database while it runs.
+/// [`ManagedDriver::lock`] gives access to the whole `SQLiteDriver` API, and
+/// runs several operations without other threads interleaving theirs. The
+/// database stays open while a `ManagedDriver` refers to it.
+#[derive(Debug, Clone)]
+pub struct ManagedDriver {
+    name: String,
+    driver: Arc<Mutex<SQLiteDriver>>,
+}
+
+impl ManagedDriver {
+    /// Returns the name of the database.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Locks the database, waiting for the operations of other threads on it
+    /// to finish.
+    ///
+    /// # Returns
+    /// A guard dereferencing to the driver of the database.
+    pub fn lock(&self) -> MutexGuard<'_, SQLiteDriver> {
+        self.driver.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns whether a driver other than the one of the manager refers to
+    /// the database.
+    fn in_use(&self) -> bool {
+        Arc::strong_count(&self.driver) > 1
+    }
+
+    /// Closes the database if no other driver refers to it, or flushes it.
+    fn close(self) -> Result<()> {
+        match Arc::try_unwrap(self.driver) {
+            Ok(driver) => driver
+                .into_inner()
+                .unwrap_or_else(|e| e.into_inner())
+                .close(),
+            Err(driver) => {
+                driver.lock().unwrap_or_else(|e| e.into_inner()).flush()?;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Driver for ManagedDriver {
+    fn ping(&self) -> Result<()> {
+        self.lock().ping()
+    }
+
+    fn all(&self) -> Result<Vec<(String, Value)>> {
+        Driver::all(&*self.lock())
+    }
+
+    fn get(&self, key: &str) -> Result<Option<Value>> {
+        Driver::get(&*self.lock(), key)
+    }
+
+    fn set(&self, key: &str, value: Value) -> Result<()> {
+        Driver::set(&*self.lock(), key, value)
+    }
+
+    fn has(&self, key: &str) -> Result<bool> {
+        Driver::has(&*self.lock(), key)
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        Driver::delete(&*self.lock(), key)
+    }
+
+    fn delete_all(&self) -> Result<bool> {
+        Driver::delete_all(&*self.lock())
+    }
+
+    fn add(&self, key: &str, value: f64) -> Result<f64> {
+        Driver::add(&*self.lock(), key, value)
+    }
+
+    fn subtract(&self, key: &str, value: f64) -> Result<f64> {
+        Driver::subtract(&*self.lock(), key, value)
+    }
+
+    fn push(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        Driver::push(&*self.lock(), key, value)
+    }
+
+    fn pull(&self, key: &str, value: Value) -> Result<Vec<Value>> {
+        Driver::pull(&*self.lock(), key, value)
+    }
+
+    fn insert(&self, value: Value) -> Result<String> {
+        Driver::insert(&*self.lock(), value)
+    }
+
+    fn replace(&self, key: &str, value: Value) -> Result<Option<Value>> {
+        Driver::replace(&*self.lock(), key, value)
+    }
+
+    fn delete_many_nested(&self, key: &str, paths: &[&str]) -> Result<usize> {
+        Driver::delete_many_nested(&*self.lock(), key, paths)
+    }
+
+    fn entry(&self, key: &str) -> Result<Option<DataSet>> {
+        Driver::entry(&*self.lock(), key)
+    }
+
+    fn entries(&self) -> Result<Vec<DataSet>> {
+        Driver::entries(&*self.lock())
+    }
+
+    fn split_key(&self, key: &str) -> Vec<String> {
+        Driver::split_key(&*self.lock(), key)
+    }
+
+    fn join_key(&self, path: &[String]) -> Option<String> {
+        Driver::join_key(&*self.lock(), path)
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// The upper bounds of the latency histogram buckets kept by [`InMemoryMetrics`].
+/// Operations slower than the last bound are counted in a final overflow bucket.
+pub const LATENCY_BUCKETS: [Duration; 9] = [
+    Duration::from_micros(100),
+    Duration::from_micros(500),
+    Duration::from_millis(1),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+];
+
+/// A completed driver operation, as reported to a [`MetricsSink`].
+///
+/// # Fields
+///
+/// - `operation`: The name of the operation, such as `get` or `set`.
+/// - `table`: The table the operation ran against.
+/// - `key`: The key the operation acted on, if any.
+/// - `duration`: How long the operation took.
+/// - `rows_affected`: The number of rows the operation changed.
+/// - `success`: Whether the operation completed without an error.
+#[derive(Debug, Clone, Copy)]
+pub struct OperationEvent<'a> {
+    /// The name of the operation.
+    pub operation: &'static str,
+    /// The table the operation ran against.
+    pub table: &'a str,
+    /// The key the operation acted on, if any.
+    pub key: Option<&'a str>,
+    /// How long the operation took.
+    pub duration: Duration,
+    /// The number of rows the operation changed.
+    pub rows_affected: u64,
+    /// Whether the operation completed without an error.
+    pub success: bool,
+}
+
+/// A receiver for driver operation metrics.
+///
+/// Drivers call [`record`] once for every completed operation, which allows
+/// operation counters and latency histograms to be exported to systems such as
+/// the `metrics` crate or Prometheus.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use std::sync::atomic::{AtomicU64, Ordering};
+/// use std::sync::Arc;
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::metrics::{MetricsSink, OperationEvent};
+/// use chromoe_db::structure::{SQLiteDriverOptions, SQLiteOpenMode};
+///
+/// #[derive(Default)]
+/// struct WriteCounter(AtomicU64);
+///
+/// impl MetricsSink for WriteCounter {
+///     fn record(&self, event: &OperationEvent<'_>) {
+///         if event.operation == "set" {
+///             self.0.fetch_add(1, Ordering::Relaxed);
+///         }
+///     }
+/// }
+///
+/// let mut driver = SQLiteDriver::new(Some(SQLiteDriverOptions {
+///     open_mode: SQLiteOpenMode::Memory,
+///     ..Default::default()
+/// }))
+/// .unwrap();
+///
+/// let counter = Arc::new(WriteCounter::default());
+/// driver.add_metrics_sink(counter.clone());
+/// driver.set("name", "Reina").unwrap();
+///
+/// assert_eq!(counter.0.load(Ordering::Relaxed), 1);
+/// ```
+///
+/// [`record`]: MetricsSink::record
+pub trait MetricsSink: Send + Sync {
+    /// Records a completed operation.
+    fn record(&self, event: &OperationEvent<'_>);
+}
+
+/// Aggregated metrics for a single kind of operation.
+///
+/// # Fields
+///
+/// - `count`: The number of times the operation ran.
+/// - `errors`: The number of times the operation failed.
+/// - `rows_affected`: The total number of rows changed by the operation.
+/// - `total_duration`: The combined time spent in the operation.
+/// - `max_duration`: The slowest run of the operation.
+/// - `histogram`: The number of runs falling into each of the [`LATENCY_BUCKETS`],
+///   followed by the overflow bucket.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OperationMetrics {
+    /// The number of times the operation ran.
+    pub count: u64,
+    /// The number of times the operation failed.
+    pub errors: u64,
+    /// The total number of rows changed by the operation.
+    pub rows_affected: u64,
+    /// The combined time spent in the operation.
+    pub total_duration: Duration,
+    /// The slowest run of the operation.
+    pub max_duration: Duration,
+    /// Run counts per latency bucket, followed by the overflow bucket.
+    pub histogram: Vec<u64>,
+}
+
+impl OperationMetrics {
+    /// Returns the mean duration of the operation, or zero if it never ran.
+    pub fn average_duration(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            let nanos = self.total_duration.as_nanos() / u128::from(self.count);
+            Duration::new(
+                (nanos / 1_000_000_000) as u64,
+                (nanos % 1_000_000_000) as u32,
+            )
+        }
+    }
+}
+
+/// The built-in [`MetricsSink`] keeping per-operation counters and latency
+/// histograms in memory.
+///
+/// Every driver owns one, which can be queried through `driver.metrics()`.
+#[derive(Debug, Default)]
+pub struct InMemoryMetrics {
+    operations: Mutex<BTreeMap<&'static str, OperationMetrics>>,
+}
+
+impl InMemoryMetrics {
+    /// Returns a copy of the metrics recorded so far, keyed by operation name.
+    pub fn snapshot(&self) -> BTreeMap<String, OperationMetrics> {
+        self.operations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .map(|(operation, metrics)| (operation.to_string(), metrics.clone()))
+            .collect()
+    }
+
+    /// Discards all recorded metrics.
+    pub fn reset(&self) {
+        self.operations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+}
+
+impl MetricsSink for InMemoryMetrics {
+    fn record(&self, event: &OperationEvent<'_>) {
+        let mut operations = self
+            .operations
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let metrics = operations.entry(event.operation).or_default();
+
+        if metrics.histogram.is_empty() {
+            metrics.histogram = vec![0; LATENCY_BUCKETS.len() + 1];
+        }
+
+        metrics.count += 1;
+        if !event.success {
+            metrics.errors += 1;
+        }
+        metrics.rows_affected += event.rows_affected;
+        metrics.total_duration += event.duration;
+        metrics.max_duration = metrics.max_duration.max(event.duration);
+
+        let bucket = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| event.duration <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        metrics.histogram[bucket] += 1;
+    }
+}
@@ -0,0 +1,268 @@
+use std::ops::Deref;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::driver::json_path::{lookup, remove_path, replace};
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::error::{ChromoeError, Result};
+use crate::transaction::TransactionScope;
+
+/// A migration step registered with [`Migrations::register`].
+type Step<'a> = Box<dyn FnOnce(&Migration<'_, '_>) -> Result<()> + 'a>;
+
+/// Ordered migrations reshaping the documents stored in a table, each run
+/// once.
+///
+/// `Migrations` are obtained from `SQLiteDriver::migrations`. Steps are
+/// registered with a version, and [`Migrations::run`] runs the steps with a
+/// version above the last one applied to the table, in order. Every step runs
+/// in its own transaction along with recording its version in the
+/// `chromoe_meta` table, so a failing step leaves the table as the previous
+/// step left it, and is retried on the next run.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use serde_json::json;
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// driver.set("alice", json!({ "coins": 100 })).unwrap();
+///
+/// let applied = driver
+///     .migrations()
+///     .register(1, |m| {
+///         m.rename_field("coins", "wallet.coins")?;
+///         Ok(())
+///     })
+///     .register(2, |m| {
+///         m.set_default("wallet.bank", 0)?;
+///         Ok(())
+///     })
+///     .run()
+///     .unwrap();
+///
+/// assert_eq!(applied, [1, 2]);
+/// assert_eq!(driver.get("alice").unwrap(), Some(json!({ "wallet": { "coins": 100, "bank": 0 } })));
+///
+/// // Steps already applied are not run again.
+/// assert!(driver.migrations().register(1, |_| unreachable!()).run().unwrap().is_empty());
+/// ```
+pub struct Migrations<'a> {
+    driver: &'a SQLiteDriver,
+    steps: Vec<(u32, Step<'a>)>,
+}
+
+impl std::fmt::Debug for Migrations<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Migrations")
+            .field(
+                "versions",
+                &self
+                    .steps
+                    .iter()
+                    .map(|(version, _)| version)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl<'a> Migrations<'a> {
+    /// Creates an empty set of migrations of the table of `driver`.
+    pub(crate) fn new(driver: &'a SQLiteDriver) -> Self {
+        Migrations {
+            driver,
+            steps: Vec::new(),
+        }
+    }
+
+    /// Registers a migration step.
+    ///
+    /// # Parameters
+    /// - `version`: The version of the step, at least `1`. Steps run in the
+    ///   order of their versions, whatever the order they are registered in.
+    /// - `step`: The step, given the [`Migration`] it runs in.
+    ///
+    /// # Returns
+    /// The `Migrations` with the step registered.
+    pub fn register<F>(mut self, version: u32, step: F) -> Self
+    where
+        F: FnOnce(&Migration<'_, '_>) -> Result<()> + 'a,
+    {
+        self.steps.push((version, Box::new(step)));
+        self
+    }
+
+    /// Returns the version of the last step applied to the table.
+    ///
+    /// # Returns
+    /// A `Result` containing the version, which is `0` if no step was applied.
+    pub fn version(&self) -> Result<u32> {
+        let key = self.meta_key();
+        match self.driver.read_meta(&key)? {
+            Some(version) => version.parse().map_err(|_| {
+                ChromoeError::Config(format!("invalid migration version `{}`", version))
+            }),
+            None => Ok(0),
+        }
+    }
+
+    /// Runs the registered steps that were not applied to the table yet.
+    ///
+    /// # Returns
+    /// A `Result` containing the versions of the steps run, in order, or the
+    /// error of the step that failed, or a `Config` error if a version is `0`
+    /// or registered twice.
+    pub fn run(mut self) -> Result<Vec<u32>> {
+        self.steps.sort_by_key(|(version, _)| *version);
+        for pair in self.steps.windows(2) {
+            if pair[0].0 == pair[1].0 {
+                return Err(ChromoeError::Config(format!(
+                    "migration {} is registered twice",
+                    pair[0].0
+                )));
+            }
+        }
+        if self.steps.first().is_some_and(|(version, _)| *version == 0) {
+            return Err(ChromoeError::Config(
+                "migration versions start at 1".to_string(),
+            ));
+        }
+
+        let key = self.meta_key();
+        let current = self.version()?;
+        let mut applied = Vec::new();
+        for (version, step) in self.steps {
+            if version <= current {
+                continue;
+            }
+
+            self.driver.with_transaction(|tx| {
+                step(&Migration { tx, version })?;
+                tx.write_meta(&key, &version.to_string())
+            })?;
+
+            #[cfg(feature = "tracing")]
+            tracing::info!(version, table = %self.driver.table, "applied migration");
+            applied.push(version);
+        }
+        Ok(applied)
+    }
+
+    /// Returns the key of the `chromoe_meta` table recording the version.
+    fn meta_key(&self) -> String {
+        let table = &self.driver.table;
+        let table = table
+            .rsplit_once('.')
+            .map_or(table.as_str(), |(_, table)| table);
+        format!("migrations:{}", table)
+    }
+}
+
+/// A migration step being run, in a transaction of the table it migrates.
+///
+/// `Migration` dereferences to the driver, so every operation of the driver
+/// is available in a step, along with helpers reshaping every document of the
+/// table. Fields are given as dot-separated paths within a document, such as
+/// `stats.level`.
+pub struct Migration<'t, 'd> {
+    tx: &'t TransactionScope<'d>,
+    version: u32,
+}
+
+impl Migration<'_, '_> {
+    /// Returns the version of the step.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Rewrites every document of the table.
+    ///
+    /// # Parameters
+    /// - `update`: A function given the key and value of a document, which it
+    ///   may modify. Modified documents are written back.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of documents modified.
+    pub fn update_all<F>(&self, update: F) -> Result<usize>
+    where
+        F: FnMut(&str, &mut Value) -> Result<()>,
+    {
+        Ok(self.tx.update_all(update)?.updated)
+    }
+
+    /// Moves a field of every document that has it.
+    ///
+    /// # Parameters
+    /// - `from`: The path of the field.
+    /// - `to`: The new path of the field, whose missing parents are created.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of documents modified.
+    pub fn rename_field(&self, from: &str, to: &str) -> Result<usize> {
+        let from: Vec<&str> = from.split('.').collect();
+        let to: Vec<&str> = to.split('.').collect();
+        self.update_all(|_, document| {
+            let Some(value) = lookup(document, &from).cloned() else {
+                return Ok(());
+            };
+            remove_path(document, &from)?;
+            let mut root = Some(std::mem::take(document));
+            replace(&mut root, &to, Some(value))?;
+            *document = root.unwrap_or_default();
+            Ok(())
+        })
+    }
+
+    /// Removes a field from every document that has it.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the field.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of documents modified.
+    pub fn remove_field(&self, path: &str) -> Result<usize> {
+        let path: Vec<&str> = path.split('.').collect();
+        self.update_all(|_, document| {
+            if document.is_object() {
+                remove_path(document, &path)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Sets a field of every object document that doesn't have it.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the field, whose missing parents are created.
+    /// - `value`: The value of the field.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of documents modified.
+    pub fn set_default<T>(&self, path: &str, value: T) -> Result<usize>
+    where
+        T: Serialize,
+    {
+        let path: Vec<&str> = path.split('.').collect();
+        let value = serde_json::to_value(value)?;
+        self.update_all(|_, document| {
+            if !document.is_object() || lookup(document, &path).is_some() {
+                return Ok(());
+            }
+            let mut root = Some(std::mem::take(document));
+            replace(&mut root, &path, Some(value.clone()))?;
+            *document = root.unwrap_or_default();
+            Ok(())
+        })
+    }
+}
+
+impl Deref for Migration<'_, '_> {
+    type Target = SQLiteDriver;
+
+    fn deref(&self) -> &SQLiteDriver {
+        self.tx
+    }
+}
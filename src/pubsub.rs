@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::error::Result;
+use crate::structure::Message;
+
+/// The messages published to a channel from the time of subscribing, such as
+/// signals sent by a dashboard process to a bot process sharing the database.
+///
+/// A `Subscriber` is obtained from `SQLiteDriver::subscribe`, and polls the
+/// messages table of the database, so it receives the messages published by
+/// every process. Iterating blocks until the next message arrives, polling
+/// every 100 milliseconds unless another interval is set with
+/// [`Subscriber::interval`].
+///
+/// # Example Usage
+///
+/// ```rust
+/// use std::time::Duration;
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use serde_json::json;
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// let mut reloads = driver.subscribe("config.reload").unwrap();
+///
+/// driver.publish("config.reload", json!({ "guild": "123" })).unwrap();
+/// driver.publish("other", json!(null)).unwrap();
+///
+/// let message = reloads.recv_timeout(Duration::from_secs(1)).unwrap().unwrap();
+/// assert_eq!(message.payload["guild"], "123");
+/// assert!(reloads.poll().unwrap().is_empty());
+/// ```
+#[derive(Debug)]
+pub struct Subscriber<'a> {
+    driver: &'a SQLiteDriver,
+    channel: String,
+    seq: u64,
+    interval: Duration,
+    pending: VecDeque<Message>,
+}
+
+impl<'a> Subscriber<'a> {
+    /// Creates a subscriber to the messages of a channel published after the
+    /// message with the given sequence number.
+    pub(crate) fn new(driver: &'a SQLiteDriver, channel: &str, seq: u64) -> Self {
+        Subscriber {
+            driver,
+            channel: channel.to_string(),
+            seq,
+            interval: Duration::from_millis(100),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Sets how often the messages table is polled while waiting for a
+    /// message.
+    ///
+    /// # Parameters
+    /// - `interval`: The time between two polls.
+    ///
+    /// # Returns
+    /// The subscriber polling at `interval`.
+    pub fn interval(self, interval: Duration) -> Self {
+        Subscriber { interval, ..self }
+    }
+
+    /// Returns the channel of the subscriber.
+    pub fn channel(&self) -> &str {
+        &self.channel
+    }
+
+    /// Returns the messages published since the last message received,
+    /// without waiting.
+    ///
+    /// # Returns
+    /// A `Result` containing the new messages, in the order they were
+    /// published.
+    pub fn poll(&mut self) -> Result<Vec<Message>> {
+        self.fetch()?;
+        Ok(self.pending.drain(..).collect())
+    }
+
+    /// Waits for the next message, up to a timeout.
+    ///
+    /// # Parameters
+    /// - `timeout`: How long to wait for a message.
+    ///
+    /// # Returns
+    /// A `Result` containing the next message, or `None` if none was published
+    /// before the timeout.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<Message>> {
+        // A timeout too long to represent as an instant waits forever.
+        let deadline = Instant::now().checked_add(timeout);
+        loop {
+            if let Some(message) = self.pending.pop_front() {
+                return Ok(Some(message));
+            }
+            self.fetch()?;
+            if !self.pending.is_empty() {
+                continue;
+            }
+
+            let wait = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Ok(None);
+                    }
+                    self.interval.min(deadline - now)
+                }
+                None => self.interval,
+            };
+            thread::sleep(wait);
+        }
+    }
+
+    /// Reads the messages published since the last one read into the pending
+    /// messages.
+    fn fetch(&mut self) -> Result<()> {
+        let messages = self.driver.messages_after(&self.channel, self.seq)?;
+        if let Some(last) = messages.last() {
+            self.seq = last.seq;
+        }
+        self.pending.extend(messages);
+        Ok(())
+    }
+}
+
+impl Iterator for Subscriber<'_> {
+    type Item = Result<Message>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(message) = self.pending.pop_front() {
+                return Some(Ok(message));
+            }
+            if let Err(err) = self.fetch() {
+                return Some(Err(err));
+            }
+            if self.pending.is_empty() {
+                thread::sleep(self.interval);
+            }
+        }
+    }
+}
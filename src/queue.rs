@@ -0,0 +1,117 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::error::Result;
+
+/// A first-in, first-out queue stored as an array at a key.
+///
+/// A `Queue` is obtained from `SQLiteDriver::queue`. Every operation runs in an
+/// immediate transaction, so several workers sharing one database file can
+/// enqueue and dequeue concurrently without an item being processed twice.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// let songs = driver.queue("music.guild_1");
+///
+/// songs.enqueue("Song A").unwrap();
+/// songs.enqueue("Song B").unwrap();
+///
+/// assert_eq!(songs.dequeue::<String>().unwrap(), Some("Song A".to_string()));
+/// assert_eq!(songs.len().unwrap(), 1);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Queue<'a> {
+    driver: &'a SQLiteDriver,
+    key: &'a str,
+}
+
+impl<'a> Queue<'a> {
+    /// Creates a handle to the queue stored at `key`.
+    pub(crate) fn new(driver: &'a SQLiteDriver, key: &'a str) -> Self {
+        Queue { driver, key }
+    }
+
+    /// Returns the key where the queue is stored.
+    pub fn key(&self) -> &str {
+        self.key
+    }
+
+    /// Appends a value to the back of the queue.
+    ///
+    /// # Parameters
+    /// - `value`: The value to enqueue.
+    ///
+    /// # Returns
+    /// A `Result` containing the length of the queue after the value was added.
+    pub fn enqueue<T>(&self, value: T) -> Result<usize>
+    where
+        T: Serialize,
+    {
+        let value = serde_json::to_value(value)?;
+        self.driver.atomic(|| {
+            let mut items = self.items()?;
+            items.push(value);
+            self.driver.set(self.key, &items)?;
+            Ok(items.len())
+        })
+    }
+
+    /// Removes and returns the value at the front of the queue.
+    ///
+    /// # Returns
+    /// A `Result` containing the dequeued value, or `None` if the queue is empty.
+    pub fn dequeue<T>(&self) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.driver.atomic(|| {
+            let mut items = self.items()?;
+            if items.is_empty() {
+                return Ok(None);
+            }
+            let value = items.remove(0);
+            self.driver.set(self.key, &items)?;
+            Ok(Some(serde_json::from_value(value)?))
+        })
+    }
+
+    /// Returns the value at the front of the queue without removing it.
+    ///
+    /// # Returns
+    /// A `Result` containing the front value, or `None` if the queue is empty.
+    pub fn peek<T>(&self) -> Result<Option<T>>
+    where
+        T: DeserializeOwned,
+    {
+        match self.items()?.into_iter().next() {
+            Some(value) => Ok(Some(serde_json::from_value(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the number of values in the queue.
+    pub fn len(&self) -> Result<usize> {
+        Ok(self.items()?.len())
+    }
+
+    /// Returns whether the queue is empty.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Removes every value from the queue.
+    pub fn clear(&self) -> Result<()> {
+        self.driver.set(self.key, Vec::<Value>::new())
+    }
+
+    /// Reads the stored array, treating a missing entry as an empty queue.
+    fn items(&self) -> Result<Vec<Value>> {
+        Ok(self.driver.get(self.key)?.unwrap_or_default())
+    }
+}
@@ -0,0 +1,204 @@
+use std::fs;
+use std::path::Path;
+
+use crate::driver::Driver;
+use crate::error::{ChromoeError, Result};
+use serde_json::Value;
+
+use crate::structure::{Change, ConflictStrategy, ReplicationOptions};
+
+/// A source of the ordered changes made to a store, such as the change log of
+/// the `SQLiteDriver`.
+///
+/// Implementing `ChangeLog` allows a store to be used as the primary of
+/// [`replicate`].
+pub trait ChangeLog {
+    /// Reads the changes made after the change with sequence number `seq`, in
+    /// the order they were made.
+    fn changes_since(&self, seq: u64) -> Result<Vec<Change>>;
+}
+
+/// Applies the changes made to a primary to a replica, one way.
+///
+/// Every change read from the primary's change log is written to the replica
+/// with `set`, or `delete` for deletions, in the order the changes were made, so
+/// the replica can use any backend, such as an analytics store fed by a SQLite
+/// primary. Changed values are combined with the values already in the replica
+/// using the `conflict` strategy of the options, and the replica is not
+/// otherwise modified.
+///
+/// The position reached is stored in the checkpoint file after every batch, so
+/// replication can be stopped and resumed. Changes applied after the last
+/// checkpoint are applied again when resuming, which leaves the replica in the
+/// same state since changes only ever replace whole values.
+///
+/// With `follow` enabled, this function only returns on error, and is usually
+/// run on a dedicated thread.
+///
+/// # Parameters
+/// - `primary`: The store to read changes from.
+/// - `replica`: The driver to apply changes to.
+/// - `options`: Options controlling where replication starts and whether it follows
+///   new changes.
+///
+/// # Returns
+/// A `Result` containing the sequence number of the last change applied, or the
+/// first error encountered reading changes, applying them or writing the checkpoint.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::structure::{ReplicationOptions, SQLiteDriverOptions, SQLiteOpenMode};
+///
+/// let primary = SQLiteDriver::new(Some(SQLiteDriverOptions {
+///     open_mode: SQLiteOpenMode::Memory,
+///     change_log: true,
+///     ..Default::default()
+/// }))
+/// .unwrap();
+/// let replica = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+///
+/// primary.set("coins", 100).unwrap();
+/// primary.set("gems", 5).unwrap();
+/// primary.delete("gems").unwrap();
+///
+/// let options = ReplicationOptions {
+///     follow: false,
+///     ..Default::default()
+/// };
+/// let seq = chromoe_db::replicate(&primary, &replica, options).unwrap();
+///
+/// assert_eq!(seq, 3);
+/// assert_eq!(replica.get::<u64>("coins").unwrap(), Some(100));
+/// assert!(!replica.has("gems").unwrap());
+/// ```
+pub fn replicate<P, R>(primary: &P, replica: &R, options: ReplicationOptions) -> Result<u64>
+where
+    P: ChangeLog + ?Sized,
+    R: Driver + ?Sized,
+{
+    let mut seq = match &options.checkpoint {
+        Some(path) if path.exists() => read_checkpoint(path)?,
+        _ => options.start_seq,
+    };
+
+    loop {
+        let changes = primary.changes_since(seq)?;
+
+        if changes.is_empty() {
+            if !options.follow {
+                return Ok(seq);
+            }
+            std::thread::sleep(options.poll_interval);
+            continue;
+        }
+
+        for change in changes {
+            match change.value {
+                Some(value) => {
+                    apply(replica, &change.key, value, &options.conflict)?;
+                }
+                None => {
+                    replica.delete(&change.key)?;
+                }
+            }
+            seq = change.seq;
+        }
+
+        if let Some(path) = &options.checkpoint {
+            write_checkpoint(path, seq)?;
+        }
+    }
+}
+
+/// Writes entries into a driver, resolving keys that already hold a value with
+/// a conflict strategy.
+///
+/// # Parameters
+/// - `driver`: The driver to write to.
+/// - `entries`: The keys and values to write.
+/// - `strategy`: How an entry is combined with the value already stored at its key.
+///
+/// # Returns
+/// A `Result` containing the number of entries written, which excludes entries
+/// discarded by the strategy.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::structure::ConflictStrategy;
+/// use serde_json::json;
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// driver.set("coins", 100).unwrap();
+///
+/// let entries = vec![
+///     ("coins".to_string(), json!(5)),
+///     ("gems".to_string(), json!(1)),
+/// ];
+/// let written = chromoe_db::import(&driver, entries, &ConflictStrategy::Skip).unwrap();
+///
+/// assert_eq!(written, 1);
+/// assert_eq!(driver.get::<u64>("coins").unwrap(), Some(100));
+/// ```
+pub fn import<D, I>(driver: &D, entries: I, strategy: &ConflictStrategy) -> Result<usize>
+where
+    D: Driver + ?Sized,
+    I: IntoIterator<Item = (String, Value)>,
+{
+    let mut written = 0;
+    for (key, value) in entries {
+        if apply(driver, &key, value, strategy)? {
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+/// Writes a value to a key, resolving a conflict with the value already stored
+/// there, and returns whether anything was written.
+fn apply<D>(driver: &D, key: &str, value: Value, strategy: &ConflictStrategy) -> Result<bool>
+where
+    D: Driver + ?Sized,
+{
+    let value = match strategy {
+        // Overwriting doesn't depend on the stored value, so it isn't read.
+        ConflictStrategy::Overwrite => Some(value),
+        strategy => match driver.get(key)? {
+            Some(existing) => strategy.resolve(key, &existing, value),
+            None => Some(value),
+        },
+    };
+
+    match value {
+        Some(value) => {
+            driver.set(key, value)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Reads the sequence number stored in a checkpoint file.
+fn read_checkpoint(path: &Path) -> Result<u64> {
+    let contents = fs::read_to_string(path)?;
+    contents.trim().parse().map_err(|_| {
+        ChromoeError::Config(format!(
+            "checkpoint `{}` does not contain a sequence number",
+            path.display()
+        ))
+    })
+}
+
+/// Stores a sequence number in a checkpoint file, replacing it atomically so a
+/// crash never leaves a partially written checkpoint.
+fn write_checkpoint(path: &Path, seq: u64) -> Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(".tmp");
+
+    fs::write(&temporary, seq.to_string())?;
+    fs::rename(&temporary, path)?;
+    Ok(())
+}
@@ -0,0 +1,440 @@
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use axum::extract::{Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::value::RawValue;
+use serde_json::{json, Map, Value};
+use tokio::net::{TcpListener, ToSocketAddrs};
+
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::error::{ChromoeError, Result};
+use crate::uri::is_valid_identifier;
+
+/// Configuration options for the HTTP server.
+///
+/// # Fields
+///
+/// - `token`: The bearer token clients must send in the `Authorization` header.
+///   When `None`, requests are not authenticated, which is only suitable for
+///   servers bound to a trusted network.
+/// - `tables`: The tables served besides the driver's own, which are created
+///   on first use if they don't exist. When `None`, every existing table of the
+///   database laid out as a driver table is served, except for the tables the
+///   drivers keep their own data in, such as `chromoe_meta`.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::server::ServerOptions;
+///
+/// let options = ServerOptions {
+///     token: Some("secret".to_string()),
+///     tables: Some(vec!["economy".to_string(), "settings".to_string()]),
+/// };
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerOptions {
+    /// The bearer token required to access the API.
+    pub token: Option<String>,
+    /// The tables served besides the driver's own.
+    pub tables: Option<Vec<String>>,
+}
+
+/// The number of handles to other tables kept open, beyond which the handle
+/// opened first is closed.
+const MAX_OPEN_TABLES: usize = 16;
+
+/// The driver served by the API, along with handles for the other tables of its
+/// database, opened on first use.
+struct Tables {
+    driver: SQLiteDriver,
+    /// The tables served besides the driver's own, or `None` for every table.
+    allowed: Option<Vec<String>>,
+    handles: HashMap<String, SQLiteDriver>,
+    /// The names of the open handles, in the order they were opened.
+    opened: VecDeque<String>,
+}
+
+impl Tables {
+    /// Returns the driver for a table, opening a handle to it if needed.
+    fn get(&mut self, name: &str) -> std::result::Result<&SQLiteDriver, ServerError> {
+        if name == self.driver.table {
+            return Ok(&self.driver);
+        }
+
+        if !self.handles.contains_key(name) {
+            let served = match &self.allowed {
+                Some(allowed) => allowed.iter().any(|table| table == name),
+                None => self.is_data_table(name)?,
+            };
+            if !served {
+                return Err(ServerError::NotFound(format!("unknown table `{}`", name)));
+            }
+
+            let handle = self.driver.table(name).map_err(|err| match err {
+                ChromoeError::Config(_) => {
+                    ServerError::NotFound(format!("unknown table `{}`", name))
+                }
+                err => ServerError::Driver(err),
+            })?;
+            if self.opened.len() >= MAX_OPEN_TABLES {
+                if let Some(oldest) = self.opened.pop_front() {
+                    self.handles.remove(&oldest);
+                }
+            }
+            self.handles.insert(name.to_string(), handle);
+            self.opened.push_back(name.to_string());
+        }
+
+        Ok(&self.handles[name])
+    }
+
+    /// Returns whether a table exists in the database of the driver with the
+    /// `ID` and `JSON` columns of a driver table, and isn't one of the tables
+    /// the drivers keep their own data in.
+    fn is_data_table(&self, name: &str) -> Result<bool> {
+        if !is_valid_identifier(name) || name.starts_with("chromoe_") || name.starts_with("sqlite_")
+        {
+            return Ok(false);
+        }
+
+        let rows = self.driver.query_raw(
+            "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1 AND \
+             (SELECT group_concat(name, ',') FROM \
+             (SELECT upper(name) AS name FROM pragma_table_info(?1) ORDER BY cid)) = 'ID,JSON'",
+            [name],
+        )?;
+        Ok(!rows.is_empty())
+    }
+}
+
+#[derive(Clone)]
+struct AppState {
+    tables: Arc<Mutex<Tables>>,
+    table: Arc<str>,
+    token: Option<Arc<str>>,
+}
+
+/// An error returned by an API handler.
+enum ServerError {
+    Unauthorized,
+    BadRequest(String),
+    /// An unknown or disallowed table, or an unknown operation.
+    NotFound(String),
+    /// A key without a value in a served table.
+    NoValue(String),
+    Driver(ChromoeError),
+}
+
+impl From<ChromoeError> for ServerError {
+    fn from(err: ChromoeError) -> Self {
+        ServerError::Driver(err)
+    }
+}
+
+impl IntoResponse for ServerError {
+    fn into_response(self) -> Response {
+        let (status, code, message) = match self {
+            ServerError::Unauthorized => (
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+                "missing or invalid token".to_string(),
+            ),
+            ServerError::BadRequest(message) => (StatusCode::BAD_REQUEST, "bad_request", message),
+            ServerError::NotFound(message) => (StatusCode::NOT_FOUND, "not_found", message),
+            ServerError::NoValue(message) => (StatusCode::NOT_FOUND, "no_value", message),
+            ServerError::Driver(err) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "driver_error",
+                err.to_string(),
+            ),
+        };
+        (status, Json(json!({ "error": message, "code": code }))).into_response()
+    }
+}
+
+/// Builds the router exposing a driver as a REST API, for embedding into an
+/// existing axum application.
+///
+/// The API serves the driver's own table and, for file-backed databases, the
+/// other tables of the same database given by the `tables` option, or every
+/// existing driver table of the database without it. Other tables respond
+/// with `404`.
+///
+/// Errors respond with a JSON object holding a message under `error` and a
+/// machine-readable `code`. A `404` for a key without a value has the code
+/// `no_value`, telling it apart from an unknown table (`not_found`):
+///
+/// - `GET /tables/{table}` returns every entry of the table as a JSON object.
+/// - `DELETE /tables/{table}` deletes every entry of the table.
+/// - `GET /tables/{table}/{key}` returns the value at a key, or `404` if absent.
+/// - `PUT /tables/{table}/{key}` stores the JSON body at a key.
+/// - `PATCH /tables/{table}/{key}` applies the JSON body to the value at a key as a
+///   JSON merge patch (RFC 7386) and returns the patched value.
+/// - `DELETE /tables/{table}/{key}` deletes the value at a key, or responds with
+///   `404` if there is none.
+/// - `POST /tables/{table}/{key}/add` and `POST /tables/{table}/{key}/subtract` add a
+///   number to or subtract it from the number at a key, returning the new number.
+/// - `POST /tables/{table}/{key}/push` and `POST /tables/{table}/{key}/pull` append a
+///   value to or remove it from the array at a key, returning the updated array.
+/// - `GET /ping` responds with `204` once the driver has been pinged.
+///
+/// Keys may be nested using dot notation, as with the driver itself.
+///
+/// # Parameters
+/// - `driver`: The driver to serve.
+/// - `options`: The server options.
+///
+/// # Returns
+/// An axum `Router` serving the API.
+pub fn router(driver: SQLiteDriver, options: ServerOptions) -> Router {
+    let state = AppState {
+        table: Arc::from(driver.table.as_str()),
+        tables: Arc::new(Mutex::new(Tables {
+            driver,
+            allowed: options.tables,
+            handles: HashMap::new(),
+            opened: VecDeque::new(),
+        })),
+        token: options.token.map(Arc::from),
+    };
+
+    Router::new()
+        .route("/tables/{table}", get(get_table).delete(delete_table))
+        .route(
+            "/tables/{table}/{key}",
+            get(get_key)
+                .put(put_key)
+                .patch(patch_key)
+                .delete(delete_key),
+        )
+        .route("/tables/{table}/{key}/{operation}", post(update_key))
+        .route("/ping", get(ping))
+        .layer(middleware::from_fn_with_state(state.clone(), authenticate))
+        .with_state(state)
+}
+
+/// Serves a driver as a REST API until the server fails.
+///
+/// See [`router`] for the endpoints. Requests must be made from within a Tokio
+/// runtime.
+///
+/// # Parameters
+/// - `driver`: The driver to serve.
+/// - `addr`: The address to listen on.
+/// - `options`: The server options.
+///
+/// # Returns
+/// A `Result` which is only returned if the listener cannot be bound or fails.
+///
+/// # Example Usage
+///
+/// ```rust,no_run
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::server::{serve, ServerOptions};
+///
+/// # async fn run() -> chromoe_db::error::Result<()> {
+/// let driver = SQLiteDriver::from_uri("sqlite://json.sqlite")?;
+///
+/// serve(driver, "127.0.0.1:8080", ServerOptions {
+///     token: Some("secret".to_string()),
+///     ..Default::default()
+/// })
+/// .await
+/// # }
+/// ```
+pub async fn serve<A>(driver: SQLiteDriver, addr: A, options: ServerOptions) -> Result<()>
+where
+    A: ToSocketAddrs,
+{
+    let listener = TcpListener::bind(addr).await?;
+    axum::serve(listener, router(driver, options)).await?;
+    Ok(())
+}
+
+/// Rejects requests without the configured bearer token.
+async fn authenticate(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> std::result::Result<Response, ServerError> {
+    if let Some(token) = &state.token {
+        let authorized = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|provided| constant_time_eq(provided.as_bytes(), token.as_bytes()));
+
+        if !authorized {
+            return Err(ServerError::Unauthorized);
+        }
+    }
+
+    Ok(next.run(request).await)
+}
+
+/// Compares two byte strings in a time that depends only on their lengths, so
+/// the time taken to reject a token doesn't reveal how much of it matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+/// Runs a driver operation on a blocking thread against the given table.
+async fn with_table<T, F>(
+    state: &AppState,
+    table: String,
+    operation: F,
+) -> std::result::Result<T, ServerError>
+where
+    T: Send + 'static,
+    F: FnOnce(&SQLiteDriver) -> Result<T> + Send + 'static,
+{
+    let tables = state.tables.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut tables = tables
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let driver = tables.get(&table)?;
+        Ok(operation(driver)?)
+    })
+    .await
+    .map_err(|err| ServerError::Driver(ChromoeError::Io(std::io::Error::other(err))))?
+}
+
+async fn get_table(
+    State(state): State<AppState>,
+    Path(table): Path<String>,
+) -> std::result::Result<Json<BTreeMap<String, Box<RawValue>>>, ServerError> {
+    let entries = with_table(&state, table, |driver| driver.all_json()).await?;
+    Ok(Json(entries.into_iter().collect()))
+}
+
+async fn delete_table(
+    State(state): State<AppState>,
+    Path(table): Path<String>,
+) -> std::result::Result<StatusCode, ServerError> {
+    with_table(&state, table, |driver| driver.delete_all()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_key(
+    State(state): State<AppState>,
+    Path((table, key)): Path<(String, String)>,
+) -> std::result::Result<Json<Box<RawValue>>, ServerError> {
+    let lookup = key.clone();
+    match with_table(&state, table, move |driver| driver.get_json(&lookup)).await? {
+        Some(value) => Ok(Json(value)),
+        None => Err(ServerError::NoValue(format!("no value at `{}`", key))),
+    }
+}
+
+async fn put_key(
+    State(state): State<AppState>,
+    Path((table, key)): Path<(String, String)>,
+    Json(value): Json<Value>,
+) -> std::result::Result<StatusCode, ServerError> {
+    with_table(&state, table, move |driver| driver.set(&key, value)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn patch_key(
+    State(state): State<AppState>,
+    Path((table, key)): Path<(String, String)>,
+    Json(patch): Json<Value>,
+) -> std::result::Result<Json<Value>, ServerError> {
+    let patched = with_table(&state, table, move |driver| {
+        driver.atomic(|| {
+            let mut value = driver.get::<Value>(&key)?.unwrap_or(Value::Null);
+            merge_patch(&mut value, patch);
+            driver.set(&key, &value)?;
+            Ok(value)
+        })
+    })
+    .await?;
+    Ok(Json(patched))
+}
+
+async fn delete_key(
+    State(state): State<AppState>,
+    Path((table, key)): Path<(String, String)>,
+) -> std::result::Result<StatusCode, ServerError> {
+    let lookup = key.clone();
+    if with_table(&state, table, move |driver| driver.delete(&lookup)).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(ServerError::NoValue(format!("no value at `{}`", key)))
+    }
+}
+
+async fn update_key(
+    State(state): State<AppState>,
+    Path((table, key, operation)): Path<(String, String, String)>,
+    Json(value): Json<Value>,
+) -> std::result::Result<Json<Value>, ServerError> {
+    let number = || {
+        value
+            .as_f64()
+            .ok_or_else(|| ServerError::BadRequest(format!("`{}` expects a number", operation)))
+    };
+
+    let updated = match operation.as_str() {
+        "add" => {
+            let amount = number()?;
+            with_table(&state, table, move |driver| driver.add(&key, amount))
+                .await
+                .map(Value::from)?
+        }
+        "subtract" => {
+            let amount = number()?;
+            with_table(&state, table, move |driver| driver.subtract(&key, amount))
+                .await
+                .map(Value::from)?
+        }
+        "push" => with_table(&state, table, move |driver| driver.push(&key, value))
+            .await
+            .map(Value::Array)?,
+        "pull" => with_table(&state, table, move |driver| driver.pull(&key, value))
+            .await
+            .map(Value::Array)?,
+        _ => {
+            return Err(ServerError::NotFound(format!(
+                "unknown operation `{}`",
+                operation
+            )))
+        }
+    };
+    Ok(Json(updated))
+}
+
+async fn ping(State(state): State<AppState>) -> std::result::Result<StatusCode, ServerError> {
+    let table = state.table.to_string();
+    with_table(&state, table, |driver| driver.ping()).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Applies a JSON merge patch (RFC 7386) to a value.
+fn merge_patch(target: &mut Value, patch: Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch;
+        return;
+    };
+
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(&key);
+            } else {
+                merge_patch(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use time::OffsetDateTime;
+use tower_sessions_core::session::{Id, Record};
+use tower_sessions_core::session_store::{self, ExpiredDeletion, SessionStore};
+
+use crate::driver::blocking_driver::BlockingDriver;
+use crate::driver::{AsyncDriver, Driver};
+use crate::error::ChromoeError;
+
+/// The prefix of the keys sessions are stored at.
+const KEY_PREFIX: &str = "session:";
+
+/// The stored representation of a session.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredSession {
+    data: HashMap<String, Value>,
+    /// When the session expires, in milliseconds since the Unix epoch.
+    expires_at: i64,
+}
+
+impl StoredSession {
+    /// Returns whether the session has expired.
+    fn expired(&self) -> bool {
+        self.expires_at <= unix_millis(OffsetDateTime::now_utc())
+    }
+}
+
+/// A `tower-sessions` session store keeping web sessions in a chromoe driver,
+/// so a bot dashboard can share the storage of the bot.
+///
+/// Every session is stored as a JSON object at the key `session:<id>`,
+/// holding its `data` and the time it `expires_at`, in milliseconds since the
+/// Unix epoch. Expired sessions are never loaded, and
+/// are removed by [`ExpiredDeletion::delete_expired`], which reads every key
+/// of the store, so sessions are best kept in a table of their own.
+///
+/// Any [`AsyncDriver`] can hold sessions, and blocking drivers such as the
+/// `SQLiteDriver` are run on Tokio's blocking thread pool by
+/// [`ChromoeSessionStore::blocking`].
+///
+/// # Example Usage
+///
+/// ```rust,no_run
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::sessions::ChromoeSessionStore;
+/// use chromoe_db::structure::SQLiteDriverOptions;
+///
+/// # fn run() -> chromoe_db::error::Result<()> {
+/// let driver = SQLiteDriver::new(Some(SQLiteDriverOptions {
+///     table_name: "sessions".to_string(),
+///     ..Default::default()
+/// }))?;
+/// let store = ChromoeSessionStore::blocking(driver);
+///
+/// // Used with `tower_sessions::SessionManagerLayer::new(store)`.
+/// # Ok(())
+/// # }
+/// ```
+pub struct ChromoeSessionStore<D> {
+    driver: D,
+}
+
+impl<D> ChromoeSessionStore<D>
+where
+    D: AsyncDriver + Send + Sync + 'static,
+{
+    /// Creates a new session store on an asynchronous driver.
+    ///
+    /// # Parameters
+    /// - `driver`: The driver sessions are stored in.
+    ///
+    /// # Returns
+    /// The `ChromoeSessionStore` storing sessions in `driver`.
+    pub fn new(driver: D) -> Self {
+        ChromoeSessionStore { driver }
+    }
+
+    /// Returns the driver sessions are stored in.
+    pub fn driver(&self) -> &D {
+        &self.driver
+    }
+}
+
+impl<D> ChromoeSessionStore<BlockingDriver<D>>
+where
+    D: Driver + Send + 'static,
+{
+    /// Creates a new session store on a blocking driver, running its
+    /// operations through a [`BlockingDriver`].
+    ///
+    /// # Parameters
+    /// - `driver`: The driver sessions are stored in.
+    ///
+    /// # Returns
+    /// The `ChromoeSessionStore` storing sessions in `driver`.
+    pub fn blocking(driver: D) -> Self {
+        ChromoeSessionStore::new(BlockingDriver::new(driver))
+    }
+}
+
+impl<D> fmt::Debug for ChromoeSessionStore<D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChromoeSessionStore")
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl<D> SessionStore for ChromoeSessionStore<D>
+where
+    D: AsyncDriver + Send + Sync + 'static,
+{
+    async fn create(&self, record: &mut Record) -> session_store::Result<()> {
+        // Random IDs collide rarely, but a new session must never take over
+        // the data of another one.
+        while self.driver.has(&key(&record.id)).await.map_err(backend)? {
+            record.id = Id::default();
+        }
+        self.save(record).await
+    }
+
+    async fn save(&self, record: &Record) -> session_store::Result<()> {
+        let session = StoredSession {
+            data: record.data.clone(),
+            expires_at: unix_millis(record.expiry_date),
+        };
+        let value = serde_json::to_value(session)
+            .map_err(|e| session_store::Error::Encode(e.to_string()))?;
+        self.driver
+            .set(&key(&record.id), value)
+            .await
+            .map_err(backend)
+    }
+
+    async fn load(&self, id: &Id) -> session_store::Result<Option<Record>> {
+        let Some(value) = self.driver.get(&key(id)).await.map_err(backend)? else {
+            return Ok(None);
+        };
+        let session: StoredSession = serde_json::from_value(value)
+            .map_err(|e| session_store::Error::Decode(e.to_string()))?;
+        if session.expired() {
+            return Ok(None);
+        }
+
+        let expiry_date =
+            OffsetDateTime::from_unix_timestamp_nanos(i128::from(session.expires_at) * 1_000_000)
+                .map_err(|e| session_store::Error::Decode(e.to_string()))?;
+        Ok(Some(Record {
+            id: *id,
+            data: session.data,
+            expiry_date,
+        }))
+    }
+
+    async fn delete(&self, id: &Id) -> session_store::Result<()> {
+        self.driver.delete(&key(id)).await.map_err(backend)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<D> ExpiredDeletion for ChromoeSessionStore<D>
+where
+    D: AsyncDriver + Send + Sync + 'static,
+{
+    async fn delete_expired(&self) -> session_store::Result<()> {
+        for (key, value) in self.driver.all().await.map_err(backend)? {
+            if !key.starts_with(KEY_PREFIX) {
+                continue;
+            }
+            // Entries that aren't sessions are left alone.
+            let Ok(session) = serde_json::from_value::<StoredSession>(value) else {
+                continue;
+            };
+            if session.expired() {
+                self.driver.delete(&key).await.map_err(backend)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the key a session is stored at.
+fn key(id: &Id) -> String {
+    format!("{}{}", KEY_PREFIX, id)
+}
+
+/// Returns a time in milliseconds since the Unix epoch.
+fn unix_millis(time: OffsetDateTime) -> i64 {
+    (time.unix_timestamp_nanos() / 1_000_000) as i64
+}
+
+/// Reports an error of the driver as an error of the session store.
+fn backend(err: ChromoeError) -> session_store::Error {
+    session_store::Error::Backend(err.to_string())
+}
@@ -0,0 +1,152 @@
+use std::time::SystemTime;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::error::Result;
+use crate::structure::{Diff, JsonType};
+
+/// An immutable, point-in-time copy of a table.
+///
+/// A `Snapshot` is obtained from `SQLiteDriver::snapshot`, which copies every row
+/// of the table into a private in-memory database in a single statement. Reads
+/// from the snapshot are therefore consistent with each other, and unaffected by
+/// writes made to the driver afterwards, which makes it suitable for generating
+/// reports and exports from a live database.
+///
+/// The copy holds the whole table in memory, so snapshots of very large tables
+/// are best taken sparingly.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// driver.set("balance", 100).unwrap();
+///
+/// let snapshot = driver.snapshot().unwrap();
+/// driver.set("balance", 50).unwrap();
+///
+/// assert_eq!(snapshot.get::<i64>("balance").unwrap(), Some(100));
+/// assert_eq!(driver.get::<i64>("balance").unwrap(), Some(50));
+/// ```
+#[derive(Debug)]
+pub struct Snapshot {
+    driver: SQLiteDriver,
+    taken_at: SystemTime,
+}
+
+impl Snapshot {
+    /// Wraps an in-memory driver holding a copy of the table.
+    pub(crate) fn new(driver: SQLiteDriver, taken_at: SystemTime) -> Self {
+        Snapshot { driver, taken_at }
+    }
+
+    /// Returns when the snapshot was taken.
+    pub fn taken_at(&self) -> SystemTime {
+        self.taken_at
+    }
+
+    /// Returns the name of the table the snapshot was taken from.
+    pub fn table(&self) -> &str {
+        &self.driver.table
+    }
+
+    /// Retrieves the value stored at a key, including nested keys, as it was
+    /// when the snapshot was taken.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to retrieve.
+    ///
+    /// # Returns
+    /// A `Result` containing an `Option` with the value if found, or `None` otherwise.
+    pub fn get<T>(&self, key: &str) -> Result<Option<T>>
+    where
+        T: DeserializeOwned + Default,
+    {
+        self.driver.get(key)
+    }
+
+    /// Checks if a key existed when the snapshot was taken.
+    ///
+    /// # Parameters
+    /// - `key`: The key to check.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if the key existed, `false` otherwise.
+    pub fn has(&self, key: &str) -> Result<bool> {
+        self.driver.has(key)
+    }
+
+    /// Retrieves every entry of the snapshot.
+    ///
+    /// # Returns
+    /// A `Result` containing a vector of key and value pairs.
+    pub fn all(&self) -> Result<Vec<(String, Value)>> {
+        self.driver.all()
+    }
+
+    /// Returns the number of entries in the snapshot.
+    ///
+    /// # Returns
+    /// A `Result` containing the number of rows copied into the snapshot.
+    pub fn len(&self) -> Result<usize> {
+        let count: i64 = self.driver.database.query_row(
+            &format!("SELECT COUNT(*) FROM {}", self.driver.table),
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Returns whether the snapshot holds no entries.
+    ///
+    /// # Returns
+    /// A `Result` containing `true` if the table was empty when the snapshot was taken.
+    pub fn is_empty(&self) -> Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Returns the JSON type of the value stored at a key, including nested keys.
+    ///
+    /// # Parameters
+    /// - `key`: The key of the entry to inspect.
+    ///
+    /// # Returns
+    /// A `Result` containing the `JsonType` of the value, or `None` if the key is absent.
+    pub fn type_of(&self, key: &str) -> Result<Option<JsonType>> {
+        self.driver.type_of(key)
+    }
+
+    /// Compares the snapshot with a later snapshot, such as one taken after a
+    /// job ran, as [`diff`](crate::diff) does.
+    ///
+    /// # Parameters
+    /// - `other`: The snapshot to compare with.
+    ///
+    /// # Returns
+    /// A `Result` containing the entries added in `other`, removed from this
+    /// snapshot and changed between them.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// driver.set("balance", 100).unwrap();
+    ///
+    /// let before = driver.snapshot().unwrap();
+    /// driver.set("balance", 50).unwrap();
+    /// let after = driver.snapshot().unwrap();
+    ///
+    /// let diff = before.diff(&after).unwrap();
+    /// assert_eq!(diff.changed[0].key, "balance");
+    /// assert!(diff.added.is_empty() && diff.removed.is_empty());
+    /// ```
+    pub fn diff(&self, other: &Snapshot) -> Result<Diff> {
+        crate::diff(&self.driver, &other.driver)
+    }
+}
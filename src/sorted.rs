@@ -0,0 +1,129 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::error::Result;
+use crate::structure::{Order, QueryPlanStep};
+
+/// A view of a table sorted by a field of its JSON values, such as the
+/// leaderboard of an economy.
+///
+/// A `Sorted` view is obtained from `SQLiteDriver::sorted_by`, which adds an
+/// indexed generated column holding the field to the table. Pages are read
+/// with keyset pagination, starting after the field and key of the last entry
+/// of the previous page, so every page is found through the index no matter
+/// how deep it is. Entries where the field is missing are left out, and ties
+/// are broken by key.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::structure::Order;
+/// use serde_json::json;
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+/// driver.set("alice", json!({ "xp": 300 })).unwrap();
+/// driver.set("bob", json!({ "xp": 500 })).unwrap();
+/// driver.set("carol", json!({ "xp": 300 })).unwrap();
+///
+/// let leaderboard = driver.sorted_by("$.xp", Order::Desc).unwrap();
+///
+/// let first = leaderboard.first_page(2).unwrap();
+/// assert_eq!(first[0].0, "bob");
+/// assert_eq!(first[1].0, "carol");
+///
+/// let next = leaderboard.page_after(Some((300, "carol")), 2).unwrap();
+/// assert_eq!(next, vec![("alice".to_string(), json!({ "xp": 300 }))]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Sorted<'a> {
+    driver: &'a SQLiteDriver,
+    path: String,
+    column: String,
+    order: Order,
+}
+
+impl<'a> Sorted<'a> {
+    /// Creates a view sorted by the generated column holding `path`.
+    pub(crate) fn new(driver: &'a SQLiteDriver, path: &str, column: String, order: Order) -> Self {
+        Sorted {
+            driver,
+            path: path.to_string(),
+            column,
+            order,
+        }
+    }
+
+    /// Returns the path of the field the view is sorted by.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the order of the view.
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
+    /// Returns the plan SQLite chooses for reading the pages of the view, to
+    /// check that they are read through the index of the field.
+    ///
+    /// # Returns
+    /// A `Result` containing the steps of the plan, in order.
+    ///
+    /// # Example Usage
+    ///
+    /// ```rust
+    /// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+    /// use chromoe_db::structure::Order;
+    ///
+    /// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+    /// let leaderboard = driver.sorted_by("$.xp", Order::Desc).unwrap();
+    ///
+    /// let plan = leaderboard.explain().unwrap();
+    /// assert!(plan.iter().any(|step| step.detail.contains("USING INDEX")));
+    /// ```
+    pub fn explain(&self) -> Result<Vec<QueryPlanStep>> {
+        self.driver.explain_sorted_page(&self.column, self.order)
+    }
+
+    /// Retrieves the first entries of the view.
+    ///
+    /// # Parameters
+    /// - `limit`: The largest number of entries to return.
+    ///
+    /// # Returns
+    /// A `Result` containing the keys and values of the entries, in order.
+    pub fn first_page(&self, limit: usize) -> Result<Vec<(String, Value)>> {
+        self.page_after::<Value>(None, limit)
+    }
+
+    /// Retrieves the entries following an entry of the view.
+    ///
+    /// # Parameters
+    /// - `last`: The field and key of the last entry of the previous page, or
+    ///   `None` for the first page.
+    /// - `limit`: The largest number of entries to return.
+    ///
+    /// # Returns
+    /// A `Result` containing the keys and values of the entries, in order.
+    pub fn page_after<T>(
+        &self,
+        last: Option<(T, &str)>,
+        limit: usize,
+    ) -> Result<Vec<(String, Value)>>
+    where
+        T: Serialize,
+    {
+        let last = match last {
+            Some((value, key)) => Some((serde_json::to_value(value)?, key)),
+            None => None,
+        };
+        self.driver.sorted_page(
+            &self.column,
+            self.order,
+            last.as_ref().map(|(value, key)| (value, *key)),
+            limit,
+        )
+    }
+}
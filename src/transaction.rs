@@ -0,0 +1,77 @@
+use std::ops::Deref;
+
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::error::Result;
+
+/// The driver of a transaction started with `SQLiteDriver::with_transaction`.
+///
+/// A `TransactionScope` dereferences to the driver, so every operation of the
+/// driver is available on it and runs in the transaction. Parts of the
+/// transaction can be run in a savepoint with [`TransactionScope::savepoint`],
+/// which undoes them when they fail while the rest of the transaction goes on.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::error::ChromoeError;
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://:memory:").unwrap();
+///
+/// let migrated = driver
+///     .with_transaction(|tx| {
+///         let mut migrated = Vec::new();
+///         for guild in ["guild_1", "guild_2", "guild_3"] {
+///             let result = tx.savepoint(|sp| {
+///                 sp.set(&format!("{}.version", guild), 2)?;
+///                 if guild == "guild_2" {
+///                     return Err(ChromoeError::Config("invalid guild".to_string()));
+///                 }
+///                 Ok(())
+///             });
+///             if result.is_ok() {
+///                 migrated.push(guild);
+///             }
+///         }
+///         Ok(migrated)
+///     })
+///     .unwrap();
+///
+/// assert_eq!(migrated, ["guild_1", "guild_3"]);
+/// assert!(!driver.has("guild_2").unwrap());
+/// ```
+#[derive(Debug)]
+pub struct TransactionScope<'a> {
+    driver: &'a SQLiteDriver,
+}
+
+impl<'a> TransactionScope<'a> {
+    /// Creates the scope of a transaction running on `driver`.
+    pub(crate) fn new(driver: &'a SQLiteDriver) -> Self {
+        TransactionScope { driver }
+    }
+
+    /// Runs part of the transaction in a savepoint, undoing what it did if it
+    /// fails without aborting the transaction. Savepoints can be nested.
+    ///
+    /// # Parameters
+    /// - `run`: The operations to run, given the scope of the savepoint.
+    ///
+    /// # Returns
+    /// A `Result` containing the value returned by `run`, or its error once
+    /// its changes are rolled back.
+    pub fn savepoint<T, F>(&self, run: F) -> Result<T>
+    where
+        F: FnOnce(&TransactionScope<'a>) -> Result<T>,
+    {
+        self.driver.savepoint(|| run(self))
+    }
+}
+
+impl Deref for TransactionScope<'_> {
+    type Target = SQLiteDriver;
+
+    fn deref(&self) -> &SQLiteDriver {
+        self.driver
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+
+use crate::error::{ChromoeError, Result};
+
+/// A parsed driver connection string.
+///
+/// Connection strings follow the familiar `scheme://path?key=value&key=value`
+/// layout, which allows a deployment to configure a store from a single
+/// environment variable. The scheme selects the driver, the path is
+/// interpreted by that driver (a file name for SQLite) and the query
+/// parameters carry driver-specific options.
+///
+/// # Fields
+///
+/// - `scheme`: The scheme of the connection string, such as `sqlite`.
+/// - `path`: Everything between `://` and the query string, percent-decoded.
+/// - `params`: The percent-decoded query parameters.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use chromoe_db::uri::ConnectionUri;
+///
+/// let uri = ConnectionUri::parse("sqlite://data/json.sqlite?table=economy&wal=true").unwrap();
+///
+/// assert_eq!(uri.scheme, "sqlite");
+/// assert_eq!(uri.path, "data/json.sqlite");
+/// assert_eq!(uri.param("table"), Some("economy"));
+///
+/// // `+` is a space in query parameters only.
+/// let uri = ConnectionUri::parse("sqlite://data/a+b.sqlite?table=a+b").unwrap();
+/// assert_eq!(uri.path, "data/a+b.sqlite");
+/// assert_eq!(uri.param("table"), Some("a b"));
+///
+/// assert!(ConnectionUri::parse("sqlite://data/%+5.sqlite").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionUri {
+    /// The scheme identifying the driver, such as `sqlite`.
+    pub scheme: String,
+    /// The driver-specific location, such as a database file name.
+    pub path: String,
+    /// Query parameters carrying driver-specific options.
+    pub params: HashMap<String, String>,
+}
+
+impl ConnectionUri {
+    /// Parses a connection string into its scheme, path and parameters.
+    ///
+    /// # Parameters
+    /// - `uri`: The connection string to parse.
+    ///
+    /// # Returns
+    /// A `Result` containing the parsed `ConnectionUri`, or an `InvalidUri`
+    /// error if the string has no scheme or contains malformed escapes.
+    pub fn parse(uri: &str) -> Result<Self> {
+        let (scheme, rest) = uri
+            .split_once("://")
+            .ok_or_else(|| ChromoeError::InvalidUri(format!("missing scheme in `{}`", uri)))?;
+
+        if scheme.is_empty() {
+            return Err(ChromoeError::InvalidUri(format!("missing scheme in `{}`", uri)));
+        }
+
+        let (path, query) = match rest.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (rest, None),
+        };
+
+        let mut params = HashMap::new();
+        if let Some(query) = query {
+            for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                params.insert(percent_decode(key, true)?, percent_decode(value, true)?);
+            }
+        }
+
+        Ok(ConnectionUri {
+            scheme: scheme.to_ascii_lowercase(),
+            path: percent_decode(path, false)?,
+            params,
+        })
+    }
+
+    /// Returns the value of a query parameter, if present.
+    ///
+    /// # Parameters
+    /// - `key`: The name of the parameter.
+    ///
+    /// # Returns
+    /// The parameter value, or `None` if the parameter is absent.
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.params.get(key).map(String::as_str)
+    }
+
+    /// Returns a query parameter interpreted as a boolean.
+    ///
+    /// Accepts `true`/`false`, `1`/`0`, `yes`/`no` and `on`/`off`.
+    ///
+    /// # Parameters
+    /// - `key`: The name of the parameter.
+    ///
+    /// # Returns
+    /// A `Result` containing the parsed flag, `None` if the parameter is absent,
+    /// or an `InvalidUri` error if the value is not a recognised boolean.
+    pub fn bool_param(&self, key: &str) -> Result<Option<bool>> {
+        match self.param(key) {
+            None => Ok(None),
+            Some(value) => match value.to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => Ok(Some(true)),
+                "false" | "0" | "no" | "off" => Ok(Some(false)),
+                _ => Err(ChromoeError::InvalidUri(format!(
+                    "expected a boolean for `{}`, found `{}`",
+                    key, value
+                ))),
+            },
+        }
+    }
+}
+
+/// Decodes `%XX` escapes in a connection string component, and `+` as a space
+/// in query parameters, where forms encode spaces that way.
+fn percent_decode(input: &str, query: bool) -> Result<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = input
+                    .get(i + 1..i + 3)
+                    .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| {
+                        ChromoeError::InvalidUri(format!("malformed escape in `{}`", input))
+                    })?;
+                decoded.push(hex);
+                i += 3;
+            }
+            b'+' if query => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8(decoded)
+        .map_err(|_| ChromoeError::InvalidUri(format!("invalid utf-8 in `{}`", input)))
+}
+
+/// Percent-encodes a key for use as a single URL path segment.
+#[cfg(any(feature = "http", feature = "couchdb"))]
+pub(crate) fn encode_segment(key: &str) -> String {
+    let mut encoded = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(byte as char)
+            }
+            byte => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Returns whether `name` is safe to interpolate into SQL as a table name.
+pub(crate) fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use axum::extract::{FromRef, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde_json::json;
+
+use crate::driver::blocking_driver::BlockingDriver;
+use crate::driver::sqlite_driver::SQLiteDriver;
+use crate::error::{ChromoeError, Result};
+
+/// The shared driver of an axum application, handing out a handle to each of
+/// the tables of its database.
+///
+/// `DbState` is cheap to clone and can be the state of a router, or a field of
+/// the application's own state from which it is obtained through `FromRef`.
+/// Handlers then receive a [`DbHandle`] of the table they use. Handles are
+/// opened on first use and reused by later requests, and run the operations
+/// of the driver on Tokio's blocking thread pool, as a [`BlockingDriver`]
+/// does.
+///
+/// # Example Usage
+///
+/// ```rust
+/// use axum::routing::get;
+/// use axum::Router;
+/// use chromoe_db::driver::sqlite_driver::SQLiteDriver;
+/// use chromoe_db::driver::AsyncDriver;
+/// use chromoe_db::web::{DbHandle, DbState, Table};
+/// use serde_json::Value;
+///
+/// struct Users;
+///
+/// impl Table for Users {
+///     const NAME: &'static str = "users";
+/// }
+///
+/// async fn coins(users: DbHandle<Users>) -> String {
+///     let coins = users.get("alice.coins").await.ok().flatten();
+///     coins.unwrap_or(Value::Null).to_string()
+/// }
+///
+/// async fn motd(db: DbHandle) -> String {
+///     let motd = db.get("motd").await.ok().flatten();
+///     motd.unwrap_or(Value::Null).to_string()
+/// }
+///
+/// let driver = SQLiteDriver::from_uri("sqlite://dashboard.sqlite").unwrap();
+/// let app: Router = Router::new()
+///     .route("/coins", get(coins))
+///     .route("/motd", get(motd))
+///     .with_state(DbState::new(driver));
+/// # std::fs::remove_file("dashboard.sqlite").unwrap();
+/// ```
+#[derive(Clone)]
+pub struct DbState {
+    driver: Arc<BlockingDriver<SQLiteDriver>>,
+    table: Arc<str>,
+    handles: Arc<Mutex<HashMap<String, Arc<BlockingDriver<SQLiteDriver>>>>>,
+}
+
+impl DbState {
+    /// Creates a new `DbState` sharing a driver.
+    ///
+    /// # Parameters
+    /// - `driver`: The driver whose table is served to [`DbHandle`] and whose
+    ///   database holds the other tables.
+    ///
+    /// # Returns
+    /// The `DbState` sharing `driver`.
+    pub fn new(driver: SQLiteDriver) -> Self {
+        DbState {
+            table: Arc::from(driver.table.as_str()),
+            driver: Arc::new(BlockingDriver::new(driver)),
+            handles: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Returns the driver the state was created with.
+    pub fn driver(&self) -> &Arc<BlockingDriver<SQLiteDriver>> {
+        &self.driver
+    }
+
+    /// Returns the handle to a table, opening it on first use.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the table.
+    ///
+    /// # Returns
+    /// A `Result` containing the handle, or an error if the table can't be
+    /// opened, as described for `SQLiteDriver::table`.
+    pub async fn handle(&self, name: &str) -> Result<Arc<BlockingDriver<SQLiteDriver>>> {
+        if name == &*self.table {
+            return Ok(self.driver.clone());
+        }
+        if let Some(handle) = self.lock_handles().get(name) {
+            return Ok(handle.clone());
+        }
+
+        let handle = Arc::new(self.driver.table(name).await?);
+        // A concurrent request may have opened the table meanwhile, in which
+        // case its handle is kept.
+        Ok(self
+            .lock_handles()
+            .entry(name.to_string())
+            .or_insert(handle)
+            .clone())
+    }
+
+    /// Locks the open table handles, ignoring poisoning as the map is never
+    /// left half-updated.
+    fn lock_handles(&self) -> MutexGuard<'_, HashMap<String, Arc<BlockingDriver<SQLiteDriver>>>> {
+        self.handles.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// A table, naming the table a [`DbHandle`] refers to.
+pub trait Table: Send + Sync + 'static {
+    /// The name of the table.
+    const NAME: &'static str;
+}
+
+/// The table of the driver a [`DbState`] was created with.
+pub struct DefaultTable;
+
+/// An extractor giving a handler the handle to a table of the [`DbState`] of
+/// the application, the table of its driver unless a [`Table`] is named.
+///
+/// The handle dereferences to a [`BlockingDriver`], so every operation of
+/// `AsyncDriver` can be awaited on it.
+pub struct DbHandle<T = DefaultTable> {
+    driver: Arc<BlockingDriver<SQLiteDriver>>,
+    table: PhantomData<fn() -> T>,
+}
+
+impl<T> Deref for DbHandle<T> {
+    type Target = BlockingDriver<SQLiteDriver>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.driver
+    }
+}
+
+impl<S> FromRequestParts<S> for DbHandle<DefaultTable>
+where
+    DbState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = DbRejection;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &S,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        Ok(DbHandle {
+            driver: DbState::from_ref(state).driver,
+            table: PhantomData,
+        })
+    }
+}
+
+impl<S, T> FromRequestParts<S> for DbHandle<T>
+where
+    DbState: FromRef<S>,
+    S: Send + Sync,
+    T: Table,
+{
+    type Rejection = DbRejection;
+
+    async fn from_request_parts(
+        _parts: &mut Parts,
+        state: &S,
+    ) -> std::result::Result<Self, Self::Rejection> {
+        let driver = DbState::from_ref(state)
+            .handle(T::NAME)
+            .await
+            .map_err(DbRejection)?;
+        Ok(DbHandle {
+            driver,
+            table: PhantomData,
+        })
+    }
+}
+
+/// The error returned when the table of a [`DbHandle`] can't be opened,
+/// responding with `500 Internal Server Error` and a JSON body holding the
+/// error message.
+#[derive(Debug)]
+pub struct DbRejection(pub ChromoeError);
+
+impl IntoResponse for DbRejection {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({ "error": self.0.to_string() })),
+        )
+            .into_response()
+    }
+}